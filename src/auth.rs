@@ -1,9 +1,15 @@
+#[cfg(feature = "grpc")]
+use anyhow::Context;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use std::collections::HashMap;
+#[cfg(feature = "grpc")]
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
 
 const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Credentials {
     pub basic_username: Option<String>,
     pub basic_password: Option<String>,
@@ -80,6 +86,19 @@ impl Credentials {
         headers
     }
 
+    #[cfg(feature = "grpc")]
+    pub fn metadata_map(&self) -> anyhow::Result<MetadataMap> {
+        let mut metadata = MetadataMap::new();
+        for (key, value) in self.header_map() {
+            let name = MetadataKey::from_bytes(key.as_bytes())
+                .with_context(|| format!("invalid grpc metadata key {key:?}"))?;
+            let value = MetadataValue::try_from(value.as_str())
+                .with_context(|| format!("invalid grpc metadata value for {key:?}"))?;
+            metadata.insert(name, value);
+        }
+        Ok(metadata)
+    }
+
     pub fn basic_auth(&self) -> Option<(String, String)> {
         match (self.basic_username.as_ref(), self.basic_password.as_ref()) {
             (Some(u), Some(p)) => Some((u.clone(), p.clone())),
@@ -179,4 +198,25 @@ mod tests {
         assert_eq!(headers.get("Authorization").unwrap(), "Bearer token");
         assert_eq!(headers.get("X-Custom").unwrap(), "value");
     }
+
+    #[test]
+    #[cfg(feature = "grpc")]
+    fn test_metadata_map() {
+        let creds = Credentials::new()
+            .with_bearer("token")
+            .with_api_key("my-api-key")
+            .with_header("X-Scope-OrgID", "tenant");
+        let metadata = creds.metadata_map().unwrap();
+
+        assert_eq!(metadata.get("authorization").unwrap(), "Bearer token");
+        assert_eq!(metadata.get("x-api-key").unwrap(), "my-api-key");
+        assert_eq!(metadata.get("x-scope-orgid").unwrap(), "tenant");
+    }
+
+    #[test]
+    #[cfg(feature = "grpc")]
+    fn test_metadata_map_rejects_invalid_key() {
+        let creds = Credentials::new().with_header("bad header", "value");
+        assert!(creds.metadata_map().is_err());
+    }
 }
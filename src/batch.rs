@@ -0,0 +1,149 @@
+use std::time::Duration;
+use thiserror::Error;
+
+const DEFAULT_MAX_QUEUE_SIZE: usize = 2048;
+const DEFAULT_MAX_EXPORT_BATCH_SIZE: usize = 512;
+const DEFAULT_SCHEDULED_DELAY: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_CONCURRENT_EXPORTS: usize = 1;
+
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error("max_export_batch_size ({batch}) must not exceed max_queue_size ({queue})")]
+    BatchExceedsQueue { batch: usize, queue: usize },
+}
+
+/// Tuning for the batch processors that buffer log records and spans before export.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BatchConfig {
+    pub max_queue_size: usize,
+    pub max_export_batch_size: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub scheduled_delay: Duration,
+    /// Only honoured by the span processor; log batches are always exported one at a time.
+    pub max_concurrent_exports: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_queue_size: DEFAULT_MAX_QUEUE_SIZE,
+            max_export_batch_size: DEFAULT_MAX_EXPORT_BATCH_SIZE,
+            scheduled_delay: DEFAULT_SCHEDULED_DELAY,
+            max_concurrent_exports: DEFAULT_MAX_CONCURRENT_EXPORTS,
+        }
+    }
+}
+
+impl BatchConfig {
+    pub fn with_max_queue_size(mut self, size: usize) -> Self {
+        self.max_queue_size = size;
+        self
+    }
+
+    pub fn with_max_export_batch_size(mut self, size: usize) -> Self {
+        self.max_export_batch_size = size;
+        self
+    }
+
+    pub fn with_scheduled_delay(mut self, delay: Duration) -> Self {
+        self.scheduled_delay = delay;
+        self
+    }
+
+    pub fn with_max_concurrent_exports(mut self, exports: usize) -> Self {
+        self.max_concurrent_exports = exports;
+        self
+    }
+
+    pub fn apply_defaults(&mut self, scheduled_delay: Duration) {
+        if self.max_queue_size == 0 {
+            self.max_queue_size = DEFAULT_MAX_QUEUE_SIZE;
+        }
+        if self.max_export_batch_size == 0 {
+            self.max_export_batch_size = DEFAULT_MAX_EXPORT_BATCH_SIZE.min(self.max_queue_size);
+        }
+        if self.scheduled_delay.is_zero() {
+            self.scheduled_delay = scheduled_delay;
+        }
+        if self.max_concurrent_exports == 0 {
+            self.max_concurrent_exports = DEFAULT_MAX_CONCURRENT_EXPORTS;
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), BatchError> {
+        if self.max_export_batch_size > self.max_queue_size {
+            return Err(BatchError::BatchExceedsQueue {
+                batch: self.max_export_batch_size,
+                queue: self.max_queue_size,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn log_config(
+        &self,
+        export_timeout: Duration,
+    ) -> opentelemetry_sdk::logs::BatchConfig {
+        opentelemetry_sdk::logs::BatchConfigBuilder::default()
+            .with_max_queue_size(self.max_queue_size)
+            .with_max_export_batch_size(self.max_export_batch_size)
+            .with_scheduled_delay(self.scheduled_delay)
+            .with_max_export_timeout(export_timeout)
+            .build()
+    }
+
+    pub(crate) fn span_config(
+        &self,
+        export_timeout: Duration,
+    ) -> opentelemetry_sdk::trace::BatchConfig {
+        opentelemetry_sdk::trace::BatchConfigBuilder::default()
+            .with_max_queue_size(self.max_queue_size)
+            .with_max_export_batch_size(self.max_export_batch_size)
+            .with_scheduled_delay(self.scheduled_delay)
+            .with_max_export_timeout(export_timeout)
+            .with_max_concurrent_exports(self.max_concurrent_exports)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_config_applies_defaults() {
+        let mut config = BatchConfig {
+            max_queue_size: 0,
+            max_export_batch_size: 0,
+            scheduled_delay: Duration::ZERO,
+            max_concurrent_exports: 0,
+        };
+
+        config.apply_defaults(Duration::from_secs(1));
+
+        assert_eq!(config.max_queue_size, DEFAULT_MAX_QUEUE_SIZE);
+        assert_eq!(config.max_export_batch_size, DEFAULT_MAX_EXPORT_BATCH_SIZE);
+        assert_eq!(config.scheduled_delay, Duration::from_secs(1));
+        assert_eq!(
+            config.max_concurrent_exports,
+            DEFAULT_MAX_CONCURRENT_EXPORTS
+        );
+    }
+
+    #[test]
+    fn test_batch_config_rejects_batch_larger_than_queue() {
+        let config = BatchConfig::default()
+            .with_max_queue_size(100)
+            .with_max_export_batch_size(200);
+
+        assert!(matches!(
+            config.validate(),
+            Err(BatchError::BatchExceedsQueue {
+                batch: 200,
+                queue: 100
+            })
+        ));
+    }
+}
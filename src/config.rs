@@ -2,9 +2,14 @@ use opentelemetry::KeyValue;
 use opentelemetry_sdk::resource::{Resource, ResourceDetector};
 use opentelemetry_semantic_conventions::resource;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::detectors::{
+    ContainerDetector, HostDetector, KubernetesDetector, OsDetector, ProcessDetector,
+};
 use crate::logger::LoggerConfig;
 use crate::meter::MeterConfig;
 use crate::profiler::ProfilerConfig;
@@ -12,6 +17,7 @@ use crate::tracer::TracerConfig;
 
 const DEFAULT_SERVICE_VERSION: &str = "0.1.0";
 const DEFAULT_SERVICE_NAMESPACE: &str = "default";
+const DEFAULT_DETECTOR_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -25,18 +31,54 @@ pub enum ConfigError {
     Meter(String),
     #[error("profiler configuration error: {0}")]
     Profiler(String),
+    #[error("invalid value {value:?} for {var}: {reason}")]
+    InvalidEnv {
+        var: String,
+        value: String,
+        reason: String,
+    },
+    #[error("failed to read config file {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse config file {}: {reason}", path.display())]
+    Parse { path: PathBuf, reason: String },
+    #[error("unsupported config file format {}, expected .toml, .yaml or .yml", .0.display())]
+    UnsupportedFormat(PathBuf),
+    #[error("environment variable {0} referenced in config file is not set")]
+    MissingEnvVar(String),
+    #[error("resource customizer {customizer} failed: {reason}")]
+    ResourceCustomizer { customizer: String, reason: String },
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Config {
     pub resource: ResourceConfig,
     pub logger: LoggerConfig,
     pub tracer: TracerConfig,
     pub meter: MeterConfig,
     pub profiler: ProfilerConfig,
+    /// Fail `Telemetry::new` when the tracing subscriber cannot be installed.
+    pub strict: bool,
+    /// Keep the components that started when others fail, reporting the
+    /// failures on `Telemetry::failures` instead of returning an error.
+    pub allow_partial: bool,
+    /// Install the global tracing subscriber. Turn off to add
+    /// `Telemetry::layer` to a subscriber the application builds itself.
+    pub install_subscriber: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub customizers: Vec<Arc<dyn ResourceCustomizer>>,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
 impl Config {
     pub fn new(service_name: impl Into<String>) -> Self {
         let service_name = service_name.into();
@@ -46,6 +88,9 @@ impl Config {
             tracer: TracerConfig::new(service_name.clone()),
             meter: MeterConfig::new(service_name.clone()),
             profiler: ProfilerConfig::new(service_name),
+            strict: false,
+            allow_partial: false,
+            install_subscriber: true,
             customizers: Vec::new(),
         }
     }
@@ -75,6 +120,41 @@ impl Config {
         self
     }
 
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn allow_partial(mut self, allow_partial: bool) -> Self {
+        self.allow_partial = allow_partial;
+        self
+    }
+
+    pub fn install_subscriber(mut self, install: bool) -> Self {
+        self.install_subscriber = install;
+        self
+    }
+
+    /// Appends a customizer that runs, in registration order, on the built resource.
+    pub fn with_customizer(mut self, customizer: impl ResourceCustomizer + 'static) -> Self {
+        self.customizers.push(Arc::new(customizer));
+        self
+    }
+
+    /// Builds the resource from [`ResourceConfig::build`] and passes it through each customizer.
+    pub fn build_resource(&self) -> Result<Resource, ConfigError> {
+        self.customizers
+            .iter()
+            .try_fold(self.resource.build(), |resource, customizer| {
+                customizer
+                    .customize(resource)
+                    .map_err(|err| ConfigError::ResourceCustomizer {
+                        customizer: format!("{customizer:?}"),
+                        reason: format!("{err:#}"),
+                    })
+            })
+    }
+
     pub fn apply_defaults(&mut self) {
         if self.resource.service_version.is_empty() {
             self.resource.service_version = DEFAULT_SERVICE_VERSION.to_string();
@@ -125,13 +205,17 @@ impl Config {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ResourceConfig {
     pub service_name: String,
     pub service_version: String,
     pub service_namespace: String,
     pub environment: String,
     pub attributes: HashMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub detectors: Vec<Arc<dyn ResourceDetector>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub override_factory: Option<Arc<ResourceFactory>>,
 }
 
@@ -143,10 +227,18 @@ impl std::fmt::Debug for ResourceConfig {
             .field("service_namespace", &self.service_namespace)
             .field("environment", &self.environment)
             .field("attributes", &self.attributes)
+            .field("detectors", &self.detectors.len())
+            .field("override_factory", &self.override_factory.is_some())
             .finish()
     }
 }
 
+impl Default for ResourceConfig {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
 impl ResourceConfig {
     pub fn new(service_name: impl Into<String>) -> Self {
         Self {
@@ -180,7 +272,47 @@ impl ResourceConfig {
         self
     }
 
+    /// Appends a detector whose attributes are merged under the configured ones.
+    /// Later detectors take precedence over earlier ones.
+    pub fn with_detector(mut self, detector: impl ResourceDetector + 'static) -> Self {
+        self.detectors.push(Arc::new(detector));
+        self
+    }
+
+    /// Registers the built-in host, OS, process, container and Kubernetes detectors.
+    pub fn with_default_detectors(self) -> Self {
+        self.with_detector(HostDetector)
+            .with_detector(OsDetector)
+            .with_detector(ProcessDetector::default())
+            .with_detector(ContainerDetector::default())
+            .with_detector(KubernetesDetector::default())
+    }
+
+    /// Replaces the resource built from the service fields and attributes.
+    /// Detector output is still merged underneath it.
+    pub fn with_override_factory(
+        mut self,
+        factory: impl Fn() -> Resource + Send + Sync + 'static,
+    ) -> Self {
+        self.override_factory = Some(Arc::new(factory));
+        self
+    }
+
     pub fn build(&self) -> Resource {
+        let base = match &self.override_factory {
+            Some(factory) => factory(),
+            None => self.base_resource(),
+        };
+
+        self.detectors
+            .iter()
+            .fold(Resource::empty(), |detected, detector| {
+                detected.merge(&detector.detect(DEFAULT_DETECTOR_TIMEOUT))
+            })
+            .merge(&base)
+    }
+
+    fn base_resource(&self) -> Resource {
         let mut attrs = vec![
             KeyValue::new(resource::SERVICE_NAME, self.service_name.clone()),
             KeyValue::new(resource::SERVICE_VERSION, self.service_version.clone()),
@@ -258,4 +390,110 @@ mod tests {
                 .any(|(k, v)| { k.as_str() == "service.name" && v.as_str() == "my-service" })
         );
     }
+
+    struct StaticDetector(Vec<KeyValue>);
+
+    impl ResourceDetector for StaticDetector {
+        fn detect(&self, _timeout: Duration) -> Resource {
+            Resource::new(self.0.clone())
+        }
+    }
+
+    #[derive(Debug)]
+    struct AppendAttribute(&'static str, &'static str);
+
+    impl ResourceCustomizer for AppendAttribute {
+        fn customize(&self, resource: Resource) -> Result<Resource, anyhow::Error> {
+            Ok(resource.merge(&Resource::new([KeyValue::new(self.0, self.1)])))
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingCustomizer;
+
+    impl ResourceCustomizer for FailingCustomizer {
+        fn customize(&self, _resource: Resource) -> Result<Resource, anyhow::Error> {
+            anyhow::bail!("no cloud metadata")
+        }
+    }
+
+    fn attribute(resource: &Resource, key: &'static str) -> Option<String> {
+        resource
+            .get(opentelemetry::Key::from_static_str(key))
+            .map(|value| value.to_string())
+    }
+
+    #[test]
+    fn test_resource_merges_detectors_under_configured_attributes() {
+        let resource = ResourceConfig::new("my-service")
+            .with_attribute("team", "payments")
+            .with_detector(StaticDetector(vec![
+                KeyValue::new("host.name", "first"),
+                KeyValue::new("team", "detected"),
+            ]))
+            .with_detector(StaticDetector(vec![KeyValue::new("host.name", "second")]))
+            .build();
+
+        assert_eq!(attribute(&resource, "host.name").as_deref(), Some("second"));
+        assert_eq!(attribute(&resource, "team").as_deref(), Some("payments"));
+        assert_eq!(
+            attribute(&resource, "service.name").as_deref(),
+            Some("my-service")
+        );
+    }
+
+    #[test]
+    fn test_resource_override_factory_replaces_base() {
+        let resource = ResourceConfig::new("my-service")
+            .with_detector(StaticDetector(vec![KeyValue::new("host.name", "box")]))
+            .with_override_factory(|| Resource::new([KeyValue::new("service.name", "custom")]))
+            .build();
+
+        assert_eq!(
+            attribute(&resource, "service.name").as_deref(),
+            Some("custom")
+        );
+        assert_eq!(attribute(&resource, "service.version"), None);
+        assert_eq!(attribute(&resource, "host.name").as_deref(), Some("box"));
+    }
+
+    #[test]
+    fn test_customizers_run_in_order() {
+        let resource = Config::new("my-service")
+            .with_customizer(AppendAttribute("stage", "first"))
+            .with_customizer(AppendAttribute("stage", "second"))
+            .build_resource()
+            .unwrap();
+
+        assert_eq!(attribute(&resource, "stage").as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn test_customizer_error_is_reported() {
+        let err = Config::new("my-service")
+            .with_customizer(AppendAttribute("stage", "first"))
+            .with_customizer(FailingCustomizer)
+            .build_resource()
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ConfigError::ResourceCustomizer { ref customizer, ref reason }
+                if customizer == "FailingCustomizer" && reason == "no cloud metadata"
+        ));
+    }
+
+    #[test]
+    fn test_default_detectors_keep_configured_service() {
+        let resource = ResourceConfig::new("my-service")
+            .with_default_detectors()
+            .build();
+
+        assert_eq!(
+            attribute(&resource, "service.name").as_deref(),
+            Some("my-service")
+        );
+        assert!(attribute(&resource, "process.pid").is_some());
+        assert!(attribute(&resource, "os.type").is_some());
+    }
 }
@@ -0,0 +1,400 @@
+//! Resource detectors for the environment a service runs in.
+//!
+//! Register them individually with [`ResourceConfig::with_detector`] or all at
+//! once with [`ResourceConfig::with_default_detectors`]. Attributes that cannot
+//! be read are left out rather than reported as errors.
+//!
+//! [`ResourceConfig::with_detector`]: crate::ResourceConfig::with_detector
+//! [`ResourceConfig::with_default_detectors`]: crate::ResourceConfig::with_default_detectors
+
+use opentelemetry::{Array, KeyValue, StringValue, Value};
+use opentelemetry_sdk::resource::{Resource, ResourceDetector};
+use opentelemetry_semantic_conventions::resource;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const HOSTNAME_PATHS: [&str; 2] = ["/proc/sys/kernel/hostname", "/etc/hostname"];
+const OS_RELEASE_PATH: &str = "/proc/sys/kernel/osrelease";
+const CGROUP_PATH: &str = "/proc/self/cgroup";
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const PODINFO_DIR: &str = "/etc/podinfo";
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+const CONTAINER_ID_LEN: usize = 64;
+
+/// Detects `host.name` and `host.arch`.
+#[derive(Clone, Debug, Default)]
+pub struct HostDetector;
+
+impl ResourceDetector for HostDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attrs = vec![KeyValue::new(resource::HOST_ARCH, host_arch())];
+
+        if let Some(name) = HOSTNAME_PATHS.iter().find_map(read_trimmed) {
+            attrs.push(KeyValue::new(resource::HOST_NAME, name));
+        }
+
+        Resource::new(attrs)
+    }
+}
+
+fn host_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "x86",
+        "arm" => "arm32",
+        "powerpc" => "ppc32",
+        "powerpc64" => "ppc64",
+        other => other,
+    }
+}
+
+/// Detects `os.type` and, on Linux, the kernel release as `os.version`.
+#[derive(Clone, Debug, Default)]
+pub struct OsDetector;
+
+impl ResourceDetector for OsDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let os_type = match std::env::consts::OS {
+            "macos" => "darwin",
+            other => other,
+        };
+        let mut attrs = vec![KeyValue::new(resource::OS_TYPE, os_type)];
+
+        if let Some(version) = read_trimmed(OS_RELEASE_PATH) {
+            attrs.push(KeyValue::new(resource::OS_VERSION, version));
+        }
+
+        Resource::new(attrs)
+    }
+}
+
+/// Detects the process id, executable and runtime, plus the command line when
+/// enabled with [`ProcessDetector::with_command_args`].
+#[derive(Clone, Debug, Default)]
+pub struct ProcessDetector {
+    command_args: bool,
+}
+
+impl ProcessDetector {
+    /// Also reports `process.command_args`. Off by default because flags often
+    /// carry secrets.
+    pub fn with_command_args(mut self, enabled: bool) -> Self {
+        self.command_args = enabled;
+        self
+    }
+}
+
+impl ResourceDetector for ProcessDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attrs = vec![
+            KeyValue::new(resource::PROCESS_PID, i64::from(std::process::id())),
+            KeyValue::new(resource::PROCESS_RUNTIME_NAME, "rust"),
+        ];
+
+        if self.command_args {
+            let args: Vec<StringValue> = std::env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned().into())
+                .collect();
+            attrs.push(KeyValue::new(
+                resource::PROCESS_COMMAND_ARGS,
+                Value::Array(Array::String(args)),
+            ));
+        }
+
+        if let Ok(path) = std::env::current_exe() {
+            if let Some(name) = path.file_name() {
+                attrs.push(KeyValue::new(
+                    resource::PROCESS_EXECUTABLE_NAME,
+                    name.to_string_lossy().into_owned(),
+                ));
+            }
+            attrs.push(KeyValue::new(
+                resource::PROCESS_EXECUTABLE_PATH,
+                path.to_string_lossy().into_owned(),
+            ));
+        }
+
+        Resource::new(attrs)
+    }
+}
+
+/// Detects `container.id` from `/proc/self/cgroup`, falling back to
+/// `/proc/self/mountinfo` on cgroup v2 hosts where the cgroup path is `/`.
+#[derive(Clone, Debug)]
+pub struct ContainerDetector {
+    cgroup_path: PathBuf,
+    mountinfo_path: PathBuf,
+}
+
+impl Default for ContainerDetector {
+    fn default() -> Self {
+        Self {
+            cgroup_path: PathBuf::from(CGROUP_PATH),
+            mountinfo_path: PathBuf::from(MOUNTINFO_PATH),
+        }
+    }
+}
+
+impl ContainerDetector {
+    pub fn with_cgroup_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cgroup_path = path.into();
+        self
+    }
+
+    pub fn with_mountinfo_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.mountinfo_path = path.into();
+        self
+    }
+}
+
+impl ResourceDetector for ContainerDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let id = fs::read_to_string(&self.cgroup_path)
+            .ok()
+            .and_then(|contents| container_id_from_cgroup(&contents))
+            .or_else(|| {
+                fs::read_to_string(&self.mountinfo_path)
+                    .ok()
+                    .and_then(|contents| container_id_from_mountinfo(&contents))
+            });
+
+        match id {
+            Some(id) => Resource::new([KeyValue::new(resource::CONTAINER_ID, id)]),
+            None => Resource::empty(),
+        }
+    }
+}
+
+/// Matches the last segment of each cgroup path, e.g. `/docker/<id>` or
+/// `/kubepods.slice/.../cri-containerd-<id>.scope`.
+fn container_id_from_cgroup(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let segment = line.rsplit('/').next()?;
+        let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+        let id = segment.rsplit('-').next()?;
+        is_container_id(id).then(|| id.to_string())
+    })
+}
+
+/// Matches mount roots such as `/var/lib/docker/containers/<id>/hostname`.
+fn container_id_from_mountinfo(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .flat_map(str::split_whitespace)
+        .find_map(|field| {
+            let segments: Vec<&str> = field.split('/').collect();
+            segments.windows(2).find_map(|pair| {
+                (matches!(pair[0], "containers" | "sandboxes") && is_container_id(pair[1]))
+                    .then(|| pair[1].to_string())
+            })
+        })
+}
+
+fn is_container_id(value: &str) -> bool {
+    value.len() == CONTAINER_ID_LEN && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Detects pod metadata exposed through the Kubernetes downward API.
+///
+/// Environment variables take precedence: `K8S_POD_NAME`/`POD_NAME`,
+/// `K8S_NAMESPACE_NAME`/`POD_NAMESPACE`, `K8S_POD_UID`/`POD_UID`,
+/// `K8S_NODE_NAME`/`NODE_NAME` and `K8S_CONTAINER_NAME`/`CONTAINER_NAME`.
+/// Otherwise the files `name`, `namespace`, `uid` and `node_name` are read from
+/// the downward API volume (`/etc/podinfo` by default), and the namespace falls
+/// back to the service account mount. Inside a cluster the pod name defaults
+/// to `HOSTNAME`.
+#[derive(Clone, Debug)]
+pub struct KubernetesDetector {
+    podinfo_dir: PathBuf,
+    service_account_dir: PathBuf,
+}
+
+impl Default for KubernetesDetector {
+    fn default() -> Self {
+        Self {
+            podinfo_dir: PathBuf::from(PODINFO_DIR),
+            service_account_dir: PathBuf::from(SERVICE_ACCOUNT_DIR),
+        }
+    }
+}
+
+impl KubernetesDetector {
+    pub fn with_podinfo_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.podinfo_dir = dir.into();
+        self
+    }
+
+    pub fn with_service_account_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.service_account_dir = dir.into();
+        self
+    }
+
+    fn detect_from(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Resource {
+        let env = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| lookup(name).filter(|value| !value.is_empty()))
+        };
+        let file = |dir: &Path, name: &str| read_trimmed(dir.join(name));
+        let in_cluster = lookup("KUBERNETES_SERVICE_HOST").is_some();
+
+        let pod_name = env(&["K8S_POD_NAME", "POD_NAME"])
+            .or_else(|| file(&self.podinfo_dir, "name"))
+            .or_else(|| in_cluster.then(|| env(&["HOSTNAME"])).flatten());
+        let namespace = env(&["K8S_NAMESPACE_NAME", "POD_NAMESPACE"])
+            .or_else(|| file(&self.podinfo_dir, "namespace"))
+            .or_else(|| file(&self.service_account_dir, "namespace"));
+        let pod_uid = env(&["K8S_POD_UID", "POD_UID"]).or_else(|| file(&self.podinfo_dir, "uid"));
+        let node_name =
+            env(&["K8S_NODE_NAME", "NODE_NAME"]).or_else(|| file(&self.podinfo_dir, "node_name"));
+        let container_name = env(&["K8S_CONTAINER_NAME", "CONTAINER_NAME"]);
+
+        Resource::new(
+            [
+                (resource::K8S_POD_NAME, pod_name),
+                (resource::K8S_NAMESPACE_NAME, namespace),
+                (resource::K8S_POD_UID, pod_uid),
+                (resource::K8S_NODE_NAME, node_name),
+                (resource::K8S_CONTAINER_NAME, container_name),
+            ]
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| KeyValue::new(key, value))),
+        )
+    }
+}
+
+impl ResourceDetector for KubernetesDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        self.detect_from(&|name| std::env::var(name).ok())
+    }
+}
+
+fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    let trimmed = contents.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const CONTAINER_ID: &str = "4f1c5e6d7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d";
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/detectors")
+            .join(name)
+    }
+
+    fn attribute(resource: &Resource, key: &'static str) -> Option<String> {
+        resource
+            .get(opentelemetry::Key::from_static_str(key))
+            .map(|value| value.to_string())
+    }
+
+    fn container_id(cgroup: &str, mountinfo: &str) -> Option<String> {
+        let detector = ContainerDetector::default()
+            .with_cgroup_path(fixture(cgroup))
+            .with_mountinfo_path(fixture(mountinfo));
+        attribute(&detector.detect(Duration::ZERO), resource::CONTAINER_ID)
+    }
+
+    #[test]
+    fn test_container_id_from_cgroup_v1() {
+        assert_eq!(
+            container_id("cgroup_v1", "missing").as_deref(),
+            Some(CONTAINER_ID)
+        );
+    }
+
+    #[test]
+    fn test_container_id_from_systemd_scope() {
+        assert_eq!(
+            container_id("cgroup_containerd", "missing").as_deref(),
+            Some(CONTAINER_ID)
+        );
+    }
+
+    #[test]
+    fn test_container_id_falls_back_to_mountinfo() {
+        assert_eq!(
+            container_id("cgroup_v2", "mountinfo").as_deref(),
+            Some(CONTAINER_ID)
+        );
+        assert_eq!(container_id("cgroup_v2", "missing"), None);
+    }
+
+    #[test]
+    fn test_kubernetes_env_takes_precedence_over_files() {
+        let env = HashMap::from([("K8S_POD_NAME", "checkout-7d9f"), ("NODE_NAME", "node-a")]);
+        let detector = KubernetesDetector {
+            podinfo_dir: fixture("podinfo"),
+            service_account_dir: fixture("missing"),
+        };
+
+        let resource = detector.detect_from(&|name| env.get(name).map(|v| v.to_string()));
+
+        assert_eq!(
+            attribute(&resource, resource::K8S_POD_NAME).as_deref(),
+            Some("checkout-7d9f")
+        );
+        assert_eq!(
+            attribute(&resource, resource::K8S_NAMESPACE_NAME).as_deref(),
+            Some("payments")
+        );
+        assert_eq!(
+            attribute(&resource, resource::K8S_POD_UID).as_deref(),
+            Some("0b6f2c3e-5a1d-4c1e-9f7a-2d8e4b6c1a90")
+        );
+        assert_eq!(
+            attribute(&resource, resource::K8S_NODE_NAME).as_deref(),
+            Some("node-a")
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_outside_cluster_is_empty() {
+        let detector = KubernetesDetector {
+            podinfo_dir: fixture("missing"),
+            service_account_dir: fixture("missing"),
+        };
+
+        let resource =
+            detector.detect_from(&|name| (name == "HOSTNAME").then(|| "laptop".to_string()));
+
+        assert_eq!(resource.len(), 0);
+    }
+
+    #[test]
+    fn test_process_detector() {
+        let resource = ProcessDetector::default().detect(Duration::ZERO);
+
+        assert_eq!(
+            attribute(&resource, resource::PROCESS_PID),
+            Some(std::process::id().to_string())
+        );
+        assert!(attribute(&resource, resource::PROCESS_EXECUTABLE_PATH).is_some());
+        assert!(attribute(&resource, resource::PROCESS_COMMAND_ARGS).is_none());
+
+        let resource = ProcessDetector::default()
+            .with_command_args(true)
+            .detect(Duration::ZERO);
+        assert!(attribute(&resource, resource::PROCESS_COMMAND_ARGS).is_some());
+    }
+
+    #[test]
+    fn test_host_and_os_detectors() {
+        let host = HostDetector.detect(Duration::ZERO);
+        let os = OsDetector.detect(Duration::ZERO);
+
+        assert_eq!(
+            attribute(&host, resource::HOST_ARCH).as_deref(),
+            Some(host_arch())
+        );
+        assert!(attribute(&os, resource::OS_TYPE).is_some());
+    }
+}
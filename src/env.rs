@@ -0,0 +1,1107 @@
+//! Maps the OpenTelemetry SDK environment variables, plus the Pyroscope agent
+//! variables, onto [`Config`].
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::auth::Credentials;
+use crate::batch::BatchConfig;
+use crate::config::{Config, ConfigError};
+use crate::local::LocalExporter;
+use crate::meter::{MeterExporter, ParseTemporalityError};
+use crate::otlp::Protocol;
+use crate::tracer::{ParsePropagatorError, Propagator, SamplerConfig, SpanLimitsConfig};
+
+struct SignalVars {
+    exporter: &'static str,
+    endpoint: &'static str,
+    headers: &'static str,
+    protocol: &'static str,
+    timeout: &'static str,
+    unsupported: [&'static str; 5],
+}
+
+const LOGS: SignalVars = SignalVars {
+    exporter: "OTEL_LOGS_EXPORTER",
+    endpoint: "OTEL_EXPORTER_OTLP_LOGS_ENDPOINT",
+    headers: "OTEL_EXPORTER_OTLP_LOGS_HEADERS",
+    protocol: "OTEL_EXPORTER_OTLP_LOGS_PROTOCOL",
+    timeout: "OTEL_EXPORTER_OTLP_LOGS_TIMEOUT",
+    unsupported: [
+        "OTEL_EXPORTER_OTLP_LOGS_INSECURE",
+        "OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY",
+        "OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_LOGS_COMPRESSION",
+    ],
+};
+
+const TRACES: SignalVars = SignalVars {
+    exporter: "OTEL_TRACES_EXPORTER",
+    endpoint: "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    headers: "OTEL_EXPORTER_OTLP_TRACES_HEADERS",
+    protocol: "OTEL_EXPORTER_OTLP_TRACES_PROTOCOL",
+    timeout: "OTEL_EXPORTER_OTLP_TRACES_TIMEOUT",
+    unsupported: [
+        "OTEL_EXPORTER_OTLP_TRACES_INSECURE",
+        "OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY",
+        "OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_TRACES_COMPRESSION",
+    ],
+};
+
+const METRICS: SignalVars = SignalVars {
+    exporter: "OTEL_METRICS_EXPORTER",
+    endpoint: "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+    headers: "OTEL_EXPORTER_OTLP_METRICS_HEADERS",
+    protocol: "OTEL_EXPORTER_OTLP_METRICS_PROTOCOL",
+    timeout: "OTEL_EXPORTER_OTLP_METRICS_TIMEOUT",
+    unsupported: [
+        "OTEL_EXPORTER_OTLP_METRICS_INSECURE",
+        "OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY",
+        "OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE",
+        "OTEL_EXPORTER_OTLP_METRICS_COMPRESSION",
+    ],
+};
+
+/// TLS and compression settings the exporters cannot honour; they are rejected
+/// rather than ignored so a misconfigured deployment fails loudly.
+const UNSUPPORTED: [&str; 5] = [
+    "OTEL_EXPORTER_OTLP_INSECURE",
+    "OTEL_EXPORTER_OTLP_CERTIFICATE",
+    "OTEL_EXPORTER_OTLP_CLIENT_KEY",
+    "OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE",
+    "OTEL_EXPORTER_OTLP_COMPRESSION",
+];
+
+/// Attribute value truncation, which the SDK does not implement.
+const UNSUPPORTED_LIMITS: [&str; 3] = [
+    "OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+    "OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+    "OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+];
+
+const UNKNOWN_SERVICE: &str = "unknown_service";
+const PROMETHEUS_HOST: &str = "localhost";
+const PROMETHEUS_PORT: u16 = 9464;
+
+/// A value of `OTEL_{LOGS,TRACES,METRICS}_EXPORTER`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SignalExporter {
+    Otlp,
+    Console,
+    /// Only accepted for metrics, which apply it themselves.
+    Prometheus(SocketAddr),
+    None,
+}
+
+/// Where an endpoint came from: signal-specific URLs are used as-is, while the
+/// generic `OTEL_EXPORTER_OTLP_ENDPOINT` gets the signal path appended.
+enum EnvEndpoint {
+    Signal(String),
+    Generic(String),
+}
+
+/// Exporter settings resolved for one signal, with signal-specific variables
+/// taking precedence over the generic `OTEL_EXPORTER_OTLP_*` ones.
+struct SignalOverrides {
+    exporter: Option<SignalExporter>,
+    endpoint: Option<EnvEndpoint>,
+    protocol: Option<Protocol>,
+    timeout: Option<Duration>,
+    headers: Vec<(&'static str, String, String)>,
+}
+
+/// The fields every component config shares for its OTLP exporter.
+struct SignalTarget<'a, E> {
+    enabled: &'a mut bool,
+    exporter: &'a mut E,
+    endpoint: &'a mut Option<String>,
+    exact_endpoint: &'a mut bool,
+    protocol: &'a mut Protocol,
+    timeout: &'a mut Duration,
+    credentials: &'a mut Credentials,
+}
+
+impl SignalOverrides {
+    /// `console` swaps the exporter for pretty lines on stdout; `otlp` restores
+    /// the OTLP exporter.
+    fn apply<E>(self, target: SignalTarget<'_, E>) -> Result<(), ConfigError>
+    where
+        E: Default + From<LocalExporter>,
+    {
+        match self.exporter {
+            Some(SignalExporter::Otlp) => {
+                *target.enabled = true;
+                *target.exporter = E::default();
+            }
+            Some(SignalExporter::Console) => {
+                *target.enabled = true;
+                *target.exporter = LocalExporter::stdout().into();
+            }
+            Some(SignalExporter::None) => *target.enabled = false,
+            Some(SignalExporter::Prometheus(_)) | None => {}
+        }
+        match self.endpoint {
+            Some(EnvEndpoint::Signal(endpoint)) => {
+                *target.endpoint = Some(endpoint);
+                *target.exact_endpoint = true;
+            }
+            Some(EnvEndpoint::Generic(endpoint)) => {
+                *target.endpoint = Some(endpoint);
+                *target.exact_endpoint = false;
+            }
+            None => {}
+        }
+        if let Some(protocol) = self.protocol {
+            *target.protocol = protocol;
+        }
+        if let Some(timeout) = self.timeout {
+            *target.timeout = timeout;
+        }
+        for (var, key, value) in self.headers {
+            apply_header(target.credentials, var, key, value)?;
+        }
+        Ok(())
+    }
+}
+
+struct Env<'a> {
+    lookup: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Env<'_> {
+    /// Empty values are treated as unset, as the specification requires.
+    fn get(&self, var: &str) -> Option<String> {
+        (self.lookup)(var).filter(|value| !value.trim().is_empty())
+    }
+
+    fn bool(&self, var: &str) -> bool {
+        self.get(var)
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"))
+    }
+
+    fn millis(&self, var: &str) -> Result<Option<Duration>, ConfigError> {
+        self.parse::<u64>(var, "expected milliseconds")
+            .map(|value| value.map(Duration::from_millis))
+    }
+
+    fn parse<T: std::str::FromStr>(
+        &self,
+        var: &str,
+        reason: &str,
+    ) -> Result<Option<T>, ConfigError> {
+        self.get(var)
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid(var, &value, reason))
+            })
+            .transpose()
+    }
+
+    fn protocol(&self, var: &str) -> Result<Option<Protocol>, ConfigError> {
+        self.get(var)
+            .map(|value| {
+                value.parse().map_err(|e: crate::otlp::ParseProtocolError| {
+                    invalid(var, &value, &e.to_string())
+                })
+            })
+            .transpose()
+    }
+
+    /// `none` yields an empty list, which leaves the global propagator alone.
+    fn propagators(&self, var: &str) -> Result<Option<Vec<Propagator>>, ConfigError> {
+        let Some(value) = self.get(var) else {
+            return Ok(None);
+        };
+
+        let mut propagators = Vec::new();
+        for name in value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            if name == "none" {
+                return Ok(Some(Vec::new()));
+            }
+            let propagator = name
+                .parse()
+                .map_err(|e: ParsePropagatorError| invalid(var, &value, &e.to_string()))?;
+            if !propagators.contains(&propagator) {
+                propagators.push(propagator);
+            }
+        }
+        Ok(Some(propagators))
+    }
+
+    fn key_values(&self, var: &str) -> Result<Vec<(String, String)>, ConfigError> {
+        match self.get(var) {
+            Some(value) => parse_key_values(var, &value),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn signal(&self, vars: &SignalVars) -> Result<SignalOverrides, ConfigError> {
+        let metrics = vars.exporter == METRICS.exporter;
+        let exporter = match self.get(vars.exporter) {
+            None => None,
+            Some(value) => match value.trim() {
+                "otlp" => Some(SignalExporter::Otlp),
+                "console" if !cfg!(feature = "local") => {
+                    return Err(invalid(
+                        vars.exporter,
+                        &value,
+                        "console requires the `local` cargo feature",
+                    ));
+                }
+                "console" => Some(SignalExporter::Console),
+                "prometheus" if metrics && !cfg!(feature = "prometheus") => {
+                    return Err(invalid(
+                        vars.exporter,
+                        &value,
+                        "prometheus requires the `prometheus` cargo feature",
+                    ));
+                }
+                "prometheus" if metrics => Some(SignalExporter::Prometheus(self.prometheus()?)),
+                "none" => Some(SignalExporter::None),
+                _ if metrics => {
+                    return Err(invalid(
+                        vars.exporter,
+                        &value,
+                        "expected otlp, prometheus, console or none",
+                    ));
+                }
+                _ => {
+                    return Err(invalid(
+                        vars.exporter,
+                        &value,
+                        "expected otlp, console or none",
+                    ));
+                }
+            },
+        };
+
+        for var in UNSUPPORTED.iter().chain(&vars.unsupported) {
+            self.reject_unsupported(var)?;
+        }
+
+        let mut headers = Vec::new();
+        for var in ["OTEL_EXPORTER_OTLP_HEADERS", vars.headers] {
+            for (key, value) in self.key_values(var)? {
+                headers.push((var, key, value));
+            }
+        }
+
+        Ok(SignalOverrides {
+            exporter,
+            endpoint: self
+                .get(vars.endpoint)
+                .map(EnvEndpoint::Signal)
+                .or_else(|| {
+                    self.get("OTEL_EXPORTER_OTLP_ENDPOINT")
+                        .map(EnvEndpoint::Generic)
+                }),
+            protocol: match self.protocol(vars.protocol)? {
+                Some(protocol) => Some(protocol),
+                None => self.protocol("OTEL_EXPORTER_OTLP_PROTOCOL")?,
+            },
+            timeout: match self.millis(vars.timeout)? {
+                Some(timeout) => Some(timeout),
+                None => self.millis("OTEL_EXPORTER_OTLP_TIMEOUT")?,
+            },
+            headers,
+        })
+    }
+
+    /// The scrape address from `OTEL_EXPORTER_PROMETHEUS_{HOST,PORT}`.
+    fn prometheus(&self) -> Result<SocketAddr, ConfigError> {
+        let host_var = "OTEL_EXPORTER_PROMETHEUS_HOST";
+        let host = self
+            .get(host_var)
+            .unwrap_or_else(|| PROMETHEUS_HOST.to_string());
+        let port = self
+            .parse("OTEL_EXPORTER_PROMETHEUS_PORT", "expected a port number")?
+            .unwrap_or(PROMETHEUS_PORT);
+        (host.trim(), port)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| invalid(host_var, &host, "expected a resolvable host"))
+    }
+
+    /// Attribute count limits for spans, where the span-, event- and
+    /// link-specific variables take precedence over `OTEL_ATTRIBUTE_COUNT_LIMIT`.
+    fn span_limits(&self, limits: &mut SpanLimitsConfig) -> Result<(), ConfigError> {
+        for var in UNSUPPORTED_LIMITS {
+            if let Some(value) = self.get(var) {
+                return Err(invalid(
+                    var,
+                    &value,
+                    "attribute value length limits are not supported",
+                ));
+            }
+        }
+
+        let count = "expected a non-negative integer";
+        let default = self.parse::<u32>("OTEL_ATTRIBUTE_COUNT_LIMIT", count)?;
+        for (var, limit) in [
+            (
+                "OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT",
+                &mut limits.max_attributes_per_span,
+            ),
+            (
+                "OTEL_EVENT_ATTRIBUTE_COUNT_LIMIT",
+                &mut limits.max_attributes_per_event,
+            ),
+            (
+                "OTEL_LINK_ATTRIBUTE_COUNT_LIMIT",
+                &mut limits.max_attributes_per_link,
+            ),
+        ] {
+            if let Some(value) = self.parse(var, count)?.or(default) {
+                *limit = value;
+            }
+        }
+        for (var, limit) in [
+            (
+                "OTEL_SPAN_EVENT_COUNT_LIMIT",
+                &mut limits.max_events_per_span,
+            ),
+            ("OTEL_SPAN_LINK_COUNT_LIMIT", &mut limits.max_links_per_span),
+        ] {
+            if let Some(value) = self.parse(var, count)? {
+                *limit = value;
+            }
+        }
+        Ok(())
+    }
+
+    /// Uncompressed export is the only mode, so `none` is still accepted.
+    fn reject_unsupported(&self, var: &str) -> Result<(), ConfigError> {
+        match self.get(var) {
+            Some(value) if var.ends_with("_COMPRESSION") && value.trim() == "none" => Ok(()),
+            Some(value) if var.ends_with("_COMPRESSION") => Err(invalid(
+                var,
+                &value,
+                "compression is not supported, expected none",
+            )),
+            Some(value) => Err(invalid(
+                var,
+                &value,
+                "TLS settings are not supported by the OTLP exporters",
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn batch(&self, prefix: &str, batch: &mut BatchConfig) -> Result<(), ConfigError> {
+        if let Some(delay) = self.millis(&format!("{prefix}_SCHEDULE_DELAY"))? {
+            batch.scheduled_delay = delay;
+        }
+        let count = "expected a non-negative integer";
+        if let Some(size) = self.parse(&format!("{prefix}_MAX_QUEUE_SIZE"), count)? {
+            batch.max_queue_size = size;
+        }
+        if let Some(size) = self.parse(&format!("{prefix}_MAX_EXPORT_BATCH_SIZE"), count)? {
+            batch.max_export_batch_size = size;
+        }
+        if let Some(exports) = self.parse(&format!("{prefix}_MAX_CONCURRENT_EXPORTS"), count)? {
+            batch.max_concurrent_exports = exports;
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Builds a configuration purely from the process environment.
+    ///
+    /// Signals default to `http/protobuf` and, without an endpoint, to the
+    /// specification's default collector address for their protocol. The
+    /// service is named `unknown_service` unless `OTEL_SERVICE_NAME` or
+    /// `service.name` says otherwise, and the profiler is only enabled when
+    /// `PYROSCOPE_SERVER_ADDRESS` is set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(&|var| std::env::var(var).ok())
+    }
+
+    /// Overlays any OpenTelemetry or Pyroscope environment variables that are
+    /// set onto a configuration assembled with the builders.
+    pub fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
+        self.apply_overrides_from(&|var| std::env::var(var).ok())
+    }
+
+    fn from_lookup(lookup: &dyn Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut config = Config::new("");
+        config.logger.protocol = Protocol::HttpProtobuf;
+        config.tracer.protocol = Protocol::HttpProtobuf;
+        config.meter.protocol = Protocol::HttpProtobuf;
+        config.apply_overrides_from(lookup)?;
+
+        if config.resource.service_name.is_empty() {
+            config.set_service_name(UNKNOWN_SERVICE.to_string());
+        }
+
+        config.profiler.enabled = config.profiler.server_url.is_some();
+        for (endpoint, protocol) in [
+            (&mut config.logger.endpoint, config.logger.protocol),
+            (&mut config.tracer.endpoint, config.tracer.protocol),
+            (&mut config.meter.endpoint, config.meter.protocol),
+        ] {
+            endpoint.get_or_insert_with(|| protocol.default_endpoint().to_string());
+        }
+
+        Ok(config)
+    }
+
+    fn apply_overrides_from(
+        &mut self,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let env = Env { lookup };
+
+        self.apply_resource_env(&env)?;
+
+        let logger = &mut self.logger;
+        env.signal(&LOGS)?.apply(SignalTarget {
+            enabled: &mut logger.enabled,
+            exporter: &mut logger.exporter,
+            endpoint: &mut logger.endpoint,
+            exact_endpoint: &mut logger.exact_endpoint,
+            protocol: &mut logger.protocol,
+            timeout: &mut logger.timeout,
+            credentials: &mut logger.credentials,
+        })?;
+        env.batch("OTEL_BLRP", &mut logger.batch)?;
+        if let Some(timeout) = env.millis("OTEL_BLRP_EXPORT_TIMEOUT")? {
+            logger.timeout = timeout;
+        }
+
+        let tracer = &mut self.tracer;
+        env.signal(&TRACES)?.apply(SignalTarget {
+            enabled: &mut tracer.enabled,
+            exporter: &mut tracer.exporter,
+            endpoint: &mut tracer.endpoint,
+            exact_endpoint: &mut tracer.exact_endpoint,
+            protocol: &mut tracer.protocol,
+            timeout: &mut tracer.export_timeout,
+            credentials: &mut tracer.credentials,
+        })?;
+        env.batch("OTEL_BSP", &mut tracer.batch)?;
+        env.span_limits(&mut tracer.span_limits)?;
+        if let Some(timeout) = env.millis("OTEL_BSP_EXPORT_TIMEOUT")? {
+            tracer.export_timeout = timeout;
+        }
+        self.apply_sampler_env(&env)?;
+        if let Some(propagators) = env.propagators("OTEL_PROPAGATORS")? {
+            self.tracer.propagators = propagators;
+        }
+
+        let meter = &mut self.meter;
+        let metrics = env.signal(&METRICS)?;
+        if let Some(SignalExporter::Prometheus(bind_addr)) = metrics.exporter {
+            meter.enabled = true;
+            meter.exporter = MeterExporter::prometheus(bind_addr);
+        }
+        metrics.apply(SignalTarget {
+            enabled: &mut meter.enabled,
+            exporter: &mut meter.exporter,
+            endpoint: &mut meter.endpoint,
+            exact_endpoint: &mut meter.exact_endpoint,
+            protocol: &mut meter.protocol,
+            timeout: &mut meter.export_timeout,
+            credentials: &mut meter.credentials,
+        })?;
+        if let Some(interval) = env.millis("OTEL_METRIC_EXPORT_INTERVAL")? {
+            meter.export_interval = interval;
+        }
+        if let Some(timeout) = env.millis("OTEL_METRIC_EXPORT_TIMEOUT")? {
+            meter.export_timeout = timeout;
+        }
+        let var = "OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE";
+        if let Some(value) = env.get(var) {
+            meter.temporality = value
+                .parse()
+                .map_err(|e: ParseTemporalityError| invalid(var, &value, &e.to_string()))?;
+        }
+
+        if env.bool("OTEL_SDK_DISABLED") {
+            self.logger.enabled = false;
+            self.tracer.enabled = false;
+            self.meter.enabled = false;
+        }
+
+        self.apply_pyroscope_env(&env)
+    }
+
+    fn apply_resource_env(&mut self, env: &Env<'_>) -> Result<(), ConfigError> {
+        for (key, value) in env.key_values("OTEL_RESOURCE_ATTRIBUTES")? {
+            match key.as_str() {
+                "service.name" => self.set_service_name(value),
+                "service.version" => self.resource.service_version = value,
+                "service.namespace" => self.resource.service_namespace = value,
+                "deployment.environment" | "deployment.environment.name" => {
+                    self.logger.environment = value.clone();
+                    self.resource.environment = value;
+                }
+                _ => {
+                    self.resource.attributes.insert(key, value);
+                }
+            }
+        }
+
+        if let Some(name) = env.get("OTEL_SERVICE_NAME") {
+            self.set_service_name(name);
+        }
+
+        Ok(())
+    }
+
+    fn apply_sampler_env(&mut self, env: &Env<'_>) -> Result<(), ConfigError> {
+        let var = "OTEL_TRACES_SAMPLER";
+        let Some(sampler) = env.get(var) else {
+            return Ok(());
+        };
+
+        let ratio = || -> Result<f64, ConfigError> {
+            let arg = "OTEL_TRACES_SAMPLER_ARG";
+            let ratio = env
+                .parse::<f64>(arg, "expected a ratio between 0 and 1")?
+                .unwrap_or(1.0);
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(invalid(
+                    arg,
+                    &ratio.to_string(),
+                    "expected a ratio between 0 and 1",
+                ));
+            }
+            Ok(ratio)
+        };
+
+        self.tracer.sampler = Some(match sampler.trim() {
+            "always_on" => SamplerConfig::AlwaysOn,
+            "always_off" => SamplerConfig::AlwaysOff,
+            "traceidratio" => SamplerConfig::ratio(ratio()?),
+            "parentbased_always_on" => SamplerConfig::parent_based(SamplerConfig::AlwaysOn),
+            "parentbased_always_off" => SamplerConfig::parent_based(SamplerConfig::AlwaysOff),
+            "parentbased_traceidratio" => {
+                SamplerConfig::parent_based(SamplerConfig::ratio(ratio()?))
+            }
+            _ => {
+                return Err(invalid(
+                    var,
+                    &sampler,
+                    "expected always_on, always_off, traceidratio or their parentbased_ variants",
+                ));
+            }
+        });
+
+        Ok(())
+    }
+
+    fn apply_pyroscope_env(&mut self, env: &Env<'_>) -> Result<(), ConfigError> {
+        let profiler = &mut self.profiler;
+
+        if let Some(url) = env.get("PYROSCOPE_SERVER_ADDRESS") {
+            profiler.server_url = Some(url);
+        }
+        if let Some(name) = env.get("PYROSCOPE_APPLICATION_NAME") {
+            profiler.service_name = name;
+        }
+        if let Some(tenant) = env.get("PYROSCOPE_TENANT_ID") {
+            profiler.tenant_id = Some(tenant);
+        }
+        if let Some(token) = env.get("PYROSCOPE_AUTH_TOKEN") {
+            profiler.credentials.bearer_token = Some(token);
+        }
+        if let Some(user) = env.get("PYROSCOPE_BASIC_AUTH_USER") {
+            profiler.credentials.basic_username = Some(user);
+        }
+        if let Some(password) = env.get("PYROSCOPE_BASIC_AUTH_PASSWORD") {
+            profiler.credentials.basic_password = Some(password);
+        }
+        for (key, value) in env.key_values("PYROSCOPE_TAGS")? {
+            profiler.tags.insert(key, value);
+        }
+
+        Ok(())
+    }
+
+    fn set_service_name(&mut self, name: String) {
+        self.logger.service_name = name.clone();
+        self.tracer.service_name = name.clone();
+        self.meter.service_name = name.clone();
+        self.profiler.service_name = name.clone();
+        self.profiler
+            .tags
+            .insert("service".to_string(), name.clone());
+        self.profiler
+            .tags
+            .insert("service_name".to_string(), name.clone());
+        self.resource.service_name = name;
+    }
+}
+
+/// `Authorization` is routed to the typed credential fields because
+/// [`Credentials::header_map`] never forwards it from the raw header list.
+fn apply_header(
+    credentials: &mut Credentials,
+    var: &str,
+    key: String,
+    value: String,
+) -> Result<(), ConfigError> {
+    if !key.eq_ignore_ascii_case("authorization") {
+        credentials.headers.insert(key, value);
+        return Ok(());
+    }
+
+    if let Some(token) = value.strip_prefix("Bearer ") {
+        credentials.bearer_token = Some(token.trim().to_string());
+        return Ok(());
+    }
+
+    let basic = value
+        .strip_prefix("Basic ")
+        .and_then(|encoded| BASE64.decode(encoded.trim()).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|decoded| {
+            decoded
+                .split_once(':')
+                .map(|(user, pass)| (user.to_string(), pass.to_string()))
+        });
+
+    match basic {
+        Some((username, password)) => {
+            credentials.basic_username = Some(username);
+            credentials.basic_password = Some(password);
+            Ok(())
+        }
+        None => Err(invalid(
+            var,
+            &value,
+            "authorization header must be `Bearer <token>` or `Basic <base64 user:pass>`",
+        )),
+    }
+}
+
+/// Parses the `key=value,key=value` lists used by `OTEL_RESOURCE_ATTRIBUTES`
+/// and `OTEL_EXPORTER_OTLP_HEADERS`, percent-decoding both sides.
+fn parse_key_values(var: &str, raw: &str) -> Result<Vec<(String, String)>, ConfigError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| invalid(var, raw, "expected comma-separated key=value pairs"))?;
+            let key = percent_decode(var, raw, key.trim())?;
+            if key.is_empty() {
+                return Err(invalid(var, raw, "keys must not be empty"));
+            }
+            Ok((key, percent_decode(var, raw, value.trim())?))
+        })
+        .collect()
+}
+
+fn percent_decode(var: &str, raw: &str, input: &str) -> Result<String, ConfigError> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| invalid(var, raw, "malformed percent-encoding"))?;
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| invalid(var, raw, "percent-encoding is not UTF-8"))
+}
+
+fn invalid(var: &str, value: &str, reason: &str) -> ConfigError {
+    ConfigError::InvalidEnv {
+        var: var.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::LoggerExporter;
+    use crate::meter::Temporality;
+    use crate::tracer::TracerExporter;
+    use std::collections::HashMap;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Config::from_lookup(&|var| vars.get(var).cloned())
+    }
+
+    #[test]
+    fn test_from_env_defaults() {
+        let config = from_vars(&[("OTEL_SERVICE_NAME", "checkout")]).unwrap();
+
+        assert_eq!(config.resource.service_name, "checkout");
+        assert_eq!(config.tracer.service_name, "checkout");
+        assert_eq!(config.profiler.tags.get("service").unwrap(), "checkout");
+        assert_eq!(config.logger.endpoint.unwrap(), "http://localhost:4318");
+        assert_eq!(config.tracer.protocol, Protocol::HttpProtobuf);
+        assert_eq!(config.tracer.endpoint.unwrap(), "http://localhost:4318");
+        assert!(!config.profiler.enabled);
+    }
+
+    #[test]
+    fn test_from_env_bare_environment() {
+        let config = from_vars(&[]).unwrap();
+
+        assert_eq!(config.resource.service_name, UNKNOWN_SERVICE);
+        assert_eq!(config.meter.service_name, UNKNOWN_SERVICE);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_generic_endpoint_keeps_http_protocol() {
+        let config =
+            from_vars(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318")]).unwrap();
+
+        assert_eq!(config.tracer.protocol, Protocol::HttpProtobuf);
+        assert_eq!(config.tracer.endpoint.unwrap(), "http://collector:4318");
+        assert!(!config.tracer.exact_endpoint);
+    }
+
+    #[test]
+    fn test_signal_endpoint_is_used_as_is() {
+        let config = from_vars(&[
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+            ("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", "http://x/custom"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.tracer.endpoint.unwrap(), "http://x/custom");
+        assert!(config.tracer.exact_endpoint);
+        assert!(!config.logger.exact_endpoint);
+    }
+
+    #[test]
+    fn test_unsupported_tls_and_compression() {
+        for (var, value) in [
+            ("OTEL_EXPORTER_OTLP_INSECURE", "true"),
+            ("OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE", "/etc/ca.pem"),
+            ("OTEL_EXPORTER_OTLP_COMPRESSION", "gzip"),
+            ("OTEL_EXPORTER_OTLP_LOGS_COMPRESSION", "gzip"),
+        ] {
+            assert!(
+                matches!(from_vars(&[(var, value)]), Err(ConfigError::InvalidEnv { var: ref v, .. }) if v == var),
+                "{var} was not rejected"
+            );
+        }
+
+        assert!(from_vars(&[("OTEL_EXPORTER_OTLP_COMPRESSION", "none")]).is_ok());
+    }
+
+    #[test]
+    fn test_resource_attributes() {
+        let config = from_vars(&[
+            (
+                "OTEL_RESOURCE_ATTRIBUTES",
+                "service.name=from-attrs,service.version=2.0.0,deployment.environment=prod,team=pay%2Cments",
+            ),
+            ("OTEL_SERVICE_NAME", "from-name"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.resource.service_name, "from-name");
+        assert_eq!(config.resource.service_version, "2.0.0");
+        assert_eq!(config.resource.environment, "prod");
+        assert_eq!(config.resource.attributes.get("team").unwrap(), "pay,ments");
+    }
+
+    #[test]
+    fn test_signal_specific_overrides_generic() {
+        let config = from_vars(&[
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+            ("OTEL_EXPORTER_OTLP_PROTOCOL", "http/protobuf"),
+            ("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", "http://tempo:4317"),
+            ("OTEL_EXPORTER_OTLP_TRACES_PROTOCOL", "grpc"),
+            ("OTEL_EXPORTER_OTLP_TIMEOUT", "2500"),
+            ("OTEL_METRICS_EXPORTER", "none"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.logger.endpoint.unwrap(), "http://collector:4318");
+        assert_eq!(config.logger.timeout, Duration::from_millis(2500));
+        assert_eq!(config.tracer.endpoint.unwrap(), "http://tempo:4317");
+        assert_eq!(config.tracer.protocol, Protocol::Grpc);
+        assert!(!config.meter.enabled);
+    }
+
+    #[test]
+    fn test_console_exporter() {
+        if !cfg!(feature = "local") {
+            assert!(matches!(
+                from_vars(&[("OTEL_TRACES_EXPORTER", "console")]),
+                Err(ConfigError::InvalidEnv { ref var, ref reason, .. })
+                    if var == "OTEL_TRACES_EXPORTER" && reason.contains("`local`")
+            ));
+            return;
+        }
+        let config = from_vars(&[
+            ("OTEL_TRACES_EXPORTER", "console"),
+            ("OTEL_LOGS_EXPORTER", "console"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.tracer.exporter,
+            TracerExporter::Local(LocalExporter::stdout())
+        );
+        assert_eq!(
+            config.logger.exporter,
+            LoggerExporter::Local(LocalExporter::stdout())
+        );
+        assert_eq!(config.meter.exporter, MeterExporter::Otlp);
+    }
+
+    #[test]
+    fn test_prometheus_exporter() {
+        let result = from_vars(&[
+            ("OTEL_METRICS_EXPORTER", "prometheus"),
+            ("OTEL_EXPORTER_PROMETHEUS_HOST", "0.0.0.0"),
+            ("OTEL_EXPORTER_PROMETHEUS_PORT", "9100"),
+        ]);
+
+        if cfg!(feature = "prometheus") {
+            assert_eq!(
+                result.unwrap().meter.exporter,
+                MeterExporter::prometheus(([0, 0, 0, 0], 9100))
+            );
+        } else {
+            assert!(matches!(
+                result,
+                Err(ConfigError::InvalidEnv { ref var, ref reason, .. })
+                    if var == "OTEL_METRICS_EXPORTER" && reason.contains("`prometheus`")
+            ));
+        }
+
+        assert!(matches!(
+            from_vars(&[("OTEL_TRACES_EXPORTER", "prometheus")]),
+            Err(ConfigError::InvalidEnv { ref var, .. }) if var == "OTEL_TRACES_EXPORTER"
+        ));
+    }
+
+    #[test]
+    fn test_span_limits() {
+        let config = from_vars(&[
+            ("OTEL_ATTRIBUTE_COUNT_LIMIT", "64"),
+            ("OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT", "32"),
+            ("OTEL_SPAN_EVENT_COUNT_LIMIT", "16"),
+            ("OTEL_SPAN_LINK_COUNT_LIMIT", "8"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.tracer.span_limits,
+            SpanLimitsConfig {
+                max_attributes_per_span: 32,
+                max_events_per_span: 16,
+                max_links_per_span: 8,
+                max_attributes_per_event: 64,
+                max_attributes_per_link: 64,
+            }
+        );
+
+        for var in UNSUPPORTED_LIMITS {
+            assert!(
+                matches!(from_vars(&[(var, "256")]), Err(ConfigError::InvalidEnv { var: ref v, .. }) if v == var),
+                "{var} was not rejected"
+            );
+        }
+        assert!(from_vars(&[("OTEL_SPAN_EVENT_COUNT_LIMIT", "-1")]).is_err());
+    }
+
+    #[test]
+    fn test_headers_map_onto_credentials() {
+        let config = from_vars(&[
+            (
+                "OTEL_EXPORTER_OTLP_HEADERS",
+                "Authorization=Basic%20dXNlcjpwYXNz,X-Scope-OrgID=tenant",
+            ),
+            (
+                "OTEL_EXPORTER_OTLP_LOGS_HEADERS",
+                "Authorization=Bearer token",
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.tracer.credentials.basic_auth().unwrap(),
+            ("user".to_string(), "pass".to_string())
+        );
+        assert_eq!(
+            config
+                .tracer
+                .credentials
+                .headers
+                .get("X-Scope-OrgID")
+                .unwrap(),
+            "tenant"
+        );
+        assert_eq!(config.logger.credentials.bearer().unwrap(), "token");
+    }
+
+    #[test]
+    fn test_batch_and_metric_intervals() {
+        let config = from_vars(&[
+            ("OTEL_BSP_SCHEDULE_DELAY", "250"),
+            ("OTEL_BSP_MAX_QUEUE_SIZE", "4096"),
+            ("OTEL_BLRP_MAX_EXPORT_BATCH_SIZE", "64"),
+            ("OTEL_METRIC_EXPORT_INTERVAL", "15000"),
+            ("OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE", "Delta"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            config.tracer.batch.scheduled_delay,
+            Duration::from_millis(250)
+        );
+        assert_eq!(config.tracer.batch.max_queue_size, 4096);
+        assert_eq!(config.logger.batch.max_export_batch_size, 64);
+        assert_eq!(config.meter.export_interval, Duration::from_secs(15));
+        assert_eq!(config.meter.temporality, Temporality::Delta);
+    }
+
+    #[test]
+    fn test_sampler() {
+        let config = from_vars(&[
+            ("OTEL_TRACES_SAMPLER", "parentbased_traceidratio"),
+            ("OTEL_TRACES_SAMPLER_ARG", "0.25"),
+        ])
+        .unwrap();
+        assert_eq!(
+            config.tracer.sampler,
+            Some(SamplerConfig::parent_based(SamplerConfig::ratio(0.25)))
+        );
+
+        let config = from_vars(&[("OTEL_TRACES_SAMPLER", "always_off")]).unwrap();
+        assert!(config.tracer.enabled);
+        assert_eq!(config.tracer.sampler, Some(SamplerConfig::AlwaysOff));
+    }
+
+    #[test]
+    fn test_propagators() {
+        let config =
+            from_vars(&[("OTEL_PROPAGATORS", "tracecontext, b3multi,xray,b3multi")]).unwrap();
+        assert_eq!(
+            config.tracer.propagators,
+            vec![
+                Propagator::TraceContext,
+                Propagator::B3Multi,
+                Propagator::XRay
+            ]
+        );
+
+        let config = from_vars(&[("OTEL_PROPAGATORS", "none")]).unwrap();
+        assert!(config.tracer.propagators.is_empty());
+
+        assert!(matches!(
+            from_vars(&[("OTEL_PROPAGATORS", "ottrace")]),
+            Err(ConfigError::InvalidEnv { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sdk_disabled() {
+        let config = from_vars(&[("OTEL_SDK_DISABLED", "TRUE")]).unwrap();
+
+        assert!(!config.logger.enabled);
+        assert!(!config.tracer.enabled);
+        assert!(!config.meter.enabled);
+    }
+
+    #[test]
+    fn test_pyroscope() {
+        let config = from_vars(&[
+            ("OTEL_SERVICE_NAME", "checkout"),
+            ("PYROSCOPE_SERVER_ADDRESS", "http://pyroscope:4040"),
+            ("PYROSCOPE_TENANT_ID", "team-a"),
+            ("PYROSCOPE_BASIC_AUTH_USER", "user"),
+            ("PYROSCOPE_BASIC_AUTH_PASSWORD", "pass"),
+            ("PYROSCOPE_TAGS", "region=eu"),
+        ])
+        .unwrap();
+
+        assert!(config.profiler.enabled);
+        assert_eq!(config.profiler.server_url.unwrap(), "http://pyroscope:4040");
+        assert_eq!(config.profiler.tenant_id.unwrap(), "team-a");
+        assert!(config.profiler.credentials.basic_auth().is_some());
+        assert_eq!(config.profiler.tags.get("region").unwrap(), "eu");
+    }
+
+    #[test]
+    fn test_malformed_values() {
+        for (var, value) in [
+            ("OTEL_EXPORTER_OTLP_PROTOCOL", "thrift"),
+            ("OTEL_EXPORTER_OTLP_TIMEOUT", "5s"),
+            ("OTEL_TRACES_EXPORTER", "zipkin"),
+            ("OTEL_TRACES_SAMPLER", "sometimes"),
+            ("OTEL_RESOURCE_ATTRIBUTES", "missing-equals"),
+            ("OTEL_EXPORTER_OTLP_HEADERS", "Authorization=Digest abc"),
+            ("OTEL_BSP_MAX_QUEUE_SIZE", "-1"),
+            (
+                "OTEL_EXPORTER_OTLP_METRICS_TEMPORALITY_PREFERENCE",
+                "sometimes",
+            ),
+        ] {
+            let err = from_vars(&[(var, value)]).unwrap_err();
+            assert!(
+                matches!(err, ConfigError::InvalidEnv { var: ref v, .. } if v == var),
+                "unexpected error for {var}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sampler_arg_out_of_range() {
+        let err = from_vars(&[
+            ("OTEL_TRACES_SAMPLER", "traceidratio"),
+            ("OTEL_TRACES_SAMPLER_ARG", "1.5"),
+        ])
+        .unwrap_err();
+
+        assert!(
+            matches!(err, ConfigError::InvalidEnv { ref var, .. } if var == "OTEL_TRACES_SAMPLER_ARG")
+        );
+    }
+
+    #[test]
+    fn test_overrides_keep_builder_values() {
+        let mut config = Config::new("builder-service");
+        config.tracer.endpoint = Some("http://builder:4317".to_string());
+
+        config
+            .apply_overrides_from(&|var| {
+                (var == "OTEL_EXPORTER_OTLP_LOGS_ENDPOINT").then(|| "http://env:4318".to_string())
+            })
+            .unwrap();
+
+        assert_eq!(config.resource.service_name, "builder-service");
+        assert_eq!(config.tracer.endpoint.unwrap(), "http://builder:4317");
+        assert_eq!(config.logger.endpoint.unwrap(), "http://env:4318");
+    }
+}
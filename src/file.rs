@@ -0,0 +1,594 @@
+//! Loads [`Config`] from TOML or YAML files, expanding `${VAR}` references in
+//! string values against the process environment after parsing.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, ConfigError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            #[cfg(feature = "toml")]
+            Some("toml") => Ok(Format::Toml),
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => Ok(Format::Yaml),
+            _ => Err(ConfigError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+}
+
+impl Config {
+    /// Reads a configuration file, choosing the format from its extension
+    /// (`.toml` with the `toml` feature, `.yaml`/`.yml` with the `yaml` feature).
+    ///
+    /// In string values, `${VAR}` is replaced with the value of the environment
+    /// variable `VAR`, `${VAR:-default}` falls back to `default` when it is unset
+    /// or empty, and `$$` produces a literal `$`. Expansion happens after the
+    /// file is parsed, so comments are left alone and a value can never change
+    /// the document's structure. If the file does not load with every expanded
+    /// value as a string, it is read again with each value that is exactly one
+    /// reference and expands to a number or boolean, such as
+    /// `sample_ratio = "${SAMPLE_RATIO:-0.1}"`, taking that type instead.
+    /// Fields missing from the file keep the values of [`Config::default`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let format = Format::from_path(path)?;
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        parse(&contents, format, path, &|name| std::env::var(name).ok())
+    }
+}
+
+fn parse(
+    contents: &str,
+    format: Format,
+    path: &Path,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<Config, ConfigError> {
+    let parse_error = |reason: String| ConfigError::Parse {
+        path: PathBuf::from(path),
+        reason,
+    };
+
+    match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => {
+            let value = toml::Value::Table(
+                toml::from_str(contents)
+                    .map_err(|e: toml::de::Error| parse_error(e.to_string()))?,
+            );
+            let decode = |typed| {
+                let mut value = value.clone();
+                interpolate_toml(&mut value, lookup, typed)?;
+                value.try_into().map_err(|e| parse_error(e.to_string()))
+            };
+            decode(false).or_else(|err| decode(true).map_err(|_| err))
+        }
+        #[cfg(feature = "yaml")]
+        Format::Yaml => {
+            let value: serde_yaml::Value =
+                serde_yaml::from_str(contents).map_err(|e| parse_error(e.to_string()))?;
+            let decode = |typed| {
+                let mut value = value.clone();
+                interpolate_yaml(&mut value, lookup, typed)?;
+                serde_yaml::from_value(value).map_err(|e| parse_error(e.to_string()))
+            };
+            decode(false).or_else(|err| decode(true).map_err(|_| err))
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+fn interpolate_toml(
+    value: &mut toml::Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    typed: bool,
+) -> Result<(), ConfigError> {
+    match value {
+        toml::Value::String(s) => {
+            let expanded = interpolate(s, lookup)?;
+            *value = match typed
+                .then(|| Scalar::from_reference(s, &expanded))
+                .flatten()
+            {
+                Some(Scalar::Bool(b)) => toml::Value::Boolean(b),
+                Some(Scalar::Integer(i)) => toml::Value::Integer(i),
+                Some(Scalar::Float(f)) => toml::Value::Float(f),
+                None => toml::Value::String(expanded),
+            };
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                interpolate_toml(item, lookup, typed)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                interpolate_toml(item, lookup, typed)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(feature = "yaml")]
+fn interpolate_yaml(
+    value: &mut serde_yaml::Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    typed: bool,
+) -> Result<(), ConfigError> {
+    match value {
+        serde_yaml::Value::String(s) => {
+            let expanded = interpolate(s, lookup)?;
+            *value = match typed
+                .then(|| Scalar::from_reference(s, &expanded))
+                .flatten()
+            {
+                Some(Scalar::Bool(b)) => serde_yaml::Value::Bool(b),
+                Some(Scalar::Integer(i)) => serde_yaml::Value::Number(i.into()),
+                Some(Scalar::Float(f)) => serde_yaml::Value::Number(f.into()),
+                None => serde_yaml::Value::String(expanded),
+            };
+        }
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                interpolate_yaml(item, lookup, typed)?;
+            }
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            for (_, item) in mapping.iter_mut() {
+                interpolate_yaml(item, lookup, typed)?;
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => interpolate_yaml(&mut tagged.value, lookup, typed)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// A number or boolean read from a value that was exactly one `${...}` reference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scalar {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+}
+
+impl Scalar {
+    fn from_reference(raw: &str, expanded: &str) -> Option<Self> {
+        raw.strip_prefix("${")
+            .and_then(|body| body.strip_suffix('}'))
+            .filter(|reference| !reference.contains('}'))?;
+
+        match expanded {
+            "true" => Some(Scalar::Bool(true)),
+            "false" => Some(Scalar::Bool(false)),
+            _ => expanded.parse().map(Scalar::Integer).ok().or_else(|| {
+                expanded
+                    .parse()
+                    .ok()
+                    .filter(|f: &f64| f.is_finite())
+                    .map(Scalar::Float)
+            }),
+        }
+    }
+}
+
+fn interpolate(
+    input: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, ConfigError> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            output.push('$');
+            rest = after;
+            continue;
+        }
+
+        let Some(end) = rest.strip_prefix("${").and_then(|body| body.find('}')) else {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let reference = &rest[2..2 + end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+
+        match (lookup(name).filter(|value| !value.is_empty()), default) {
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => return Err(ConfigError::MissingEnvVar(name.to_string())),
+        }
+        rest = &rest[2 + end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "yaml")]
+    use crate::local::{LocalExporter, LocalFormat};
+    #[cfg(feature = "yaml")]
+    use crate::logger::{ConsoleLogConfig, ConsoleTimestamp, LogFilterConfig};
+    #[cfg(feature = "toml")]
+    use crate::meter::{InstrumentType, Temporality, ViewAggregation, ViewConfig};
+    use crate::otlp::Protocol;
+    #[cfg(feature = "toml")]
+    use crate::tracer::SamplerConfig;
+    #[cfg(feature = "yaml")]
+    use crate::tracer::TracerExporter;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_interpolate_env_references() {
+        let vars = lookup(&[("TOKEN", "secret"), ("EMPTY", "")]);
+
+        assert_eq!(
+            interpolate("Bearer ${TOKEN}", &vars).unwrap(),
+            "Bearer secret"
+        );
+        assert_eq!(
+            interpolate("${HOST:-localhost}:${EMPTY:-4317}", &vars).unwrap(),
+            "localhost:4317"
+        );
+        assert_eq!(
+            interpolate("cost $$5 $HOME", &vars).unwrap(),
+            "cost $5 $HOME"
+        );
+        assert!(matches!(
+            interpolate("${MISSING}", &vars),
+            Err(ConfigError::MissingEnvVar(name)) if name == "MISSING"
+        ));
+    }
+
+    #[test]
+    fn test_scalar_from_reference() {
+        assert_eq!(
+            Scalar::from_reference("${RATIO}", "0.5"),
+            Some(Scalar::Float(0.5))
+        );
+        assert_eq!(
+            Scalar::from_reference("${PORT:-4317}", "4317"),
+            Some(Scalar::Integer(4317))
+        );
+        assert_eq!(
+            Scalar::from_reference("${ENABLED}", "true"),
+            Some(Scalar::Bool(true))
+        );
+        assert_eq!(Scalar::from_reference("port ${PORT}", "port 1"), None);
+        assert_eq!(Scalar::from_reference("${A}${B}", "12"), None);
+        assert_eq!(Scalar::from_reference("${RATIO}", "NaN"), None);
+        assert_eq!(Scalar::from_reference("${HOST}", "localhost"), None);
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        assert!(matches!(
+            Format::from_path(Path::new("o11y.json")),
+            Err(ConfigError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_parse_toml() {
+        let contents = r#"
+            [resource]
+            service_name = "billing"
+            environment = "${ENV:-dev}"
+
+            [tracer]
+            endpoint = "http://collector:4317"
+            sample_ratio = 0.5
+            export_timeout = "2s"
+
+            [tracer.credentials]
+            bearer_token = "${TOKEN}"
+
+            [tracer.sampler]
+            type = "parent_based"
+            root = { type = "trace_id_ratio", ratio = 0.2 }
+
+            [meter]
+            endpoint = "http://mimir:4318"
+            protocol = "http/json"
+            export_interval = "500ms"
+            temporality = "delta"
+
+            [[meter.views]]
+            instrument_name = "http.*"
+            instrument_kind = "histogram"
+            allowed_attributes = ["http.route"]
+            aggregation = { explicit_bucket_histogram = { boundaries = [0.1, 1.0], record_min_max = false } }
+
+            [logger]
+            endpoint = "http://loki:3100/otlp"
+
+            [profiler]
+            enabled = false
+
+            [logger.batch]
+            max_export_batch_size = 64
+        "#;
+        let vars = lookup(&[("TOKEN", "secret")]);
+
+        let mut config = parse(contents, Format::Toml, Path::new("o11y.toml"), &vars).unwrap();
+        config.apply_defaults();
+
+        assert_eq!(config.resource.service_name, "billing");
+        assert_eq!(config.resource.environment, "dev");
+        assert_eq!(config.tracer.service_name, "billing");
+        assert_eq!(config.tracer.sample_ratio, 0.5);
+        assert_eq!(config.tracer.export_timeout, Duration::from_secs(2));
+        assert_eq!(
+            config.tracer.credentials.bearer_token.as_deref(),
+            Some("secret")
+        );
+        assert_eq!(
+            config.tracer.sampler,
+            Some(SamplerConfig::parent_based(SamplerConfig::ratio(0.2)))
+        );
+        assert_eq!(config.meter.protocol, Protocol::HttpJson);
+        assert_eq!(config.meter.export_interval, Duration::from_millis(500));
+        assert_eq!(config.meter.temporality, Temporality::Delta);
+        assert_eq!(
+            config.meter.views,
+            vec![
+                ViewConfig::default()
+                    .with_instrument_name("http.*")
+                    .with_instrument_kind(InstrumentType::Histogram)
+                    .with_allowed_attributes(["http.route"])
+                    .with_aggregation(ViewAggregation::ExplicitBucketHistogram {
+                        boundaries: vec![0.1, 1.0],
+                        record_min_max: false,
+                    })
+            ]
+        );
+        assert_eq!(config.logger.batch.max_export_batch_size, 64);
+        assert_eq!(
+            config.profiler.tags.get("service").map(String::as_str),
+            Some("billing")
+        );
+        config.validate().unwrap();
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_parse_yaml() {
+        let contents = r#"
+resource:
+  service_name: billing
+  attributes:
+    team: payments
+logger:
+  endpoint: http://loki:3100/otlp
+  timeout: 3s
+  credentials:
+    basic_username: ${LOKI_USER}
+    basic_password: ${LOKI_PASSWORD}
+  console:
+    format: json
+    timestamp: unix
+    flatten_fields: true
+    trace_ids: true
+  filter:
+    directives: info,sqlx=warn
+    env_var: BILLING_LOG
+meter:
+  enabled: false
+tracer:
+  exporter:
+    type: local
+    format: otlp_json
+    output:
+      file:
+        path: target/traces.jsonl
+        max_files: 2
+"#;
+        let vars = lookup(&[("LOKI_USER", "admin"), ("LOKI_PASSWORD", "hunter2")]);
+
+        let config = parse(contents, Format::Yaml, Path::new("o11y.yaml"), &vars).unwrap();
+
+        assert_eq!(
+            config.resource.attributes.get("team").map(String::as_str),
+            Some("payments")
+        );
+        assert_eq!(
+            config.logger.endpoint.as_deref(),
+            Some("http://loki:3100/otlp")
+        );
+        assert_eq!(config.logger.timeout, Duration::from_secs(3));
+        assert_eq!(
+            config.logger.credentials.basic_username.as_deref(),
+            Some("admin")
+        );
+        assert_eq!(
+            config.logger.credentials.basic_password.as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(
+            config.logger.console,
+            ConsoleLogConfig::json()
+                .with_timestamp(ConsoleTimestamp::Unix)
+                .flatten_fields(true)
+                .trace_ids(true)
+        );
+        assert_eq!(
+            config.logger.filter,
+            LogFilterConfig::new("info,sqlx=warn").with_env_var("BILLING_LOG")
+        );
+        assert!(!config.meter.enabled);
+        assert_eq!(config.tracer.protocol, Protocol::Grpc);
+        assert_eq!(
+            config.tracer.exporter,
+            TracerExporter::Local(
+                LocalExporter::file("target/traces.jsonl")
+                    .with_format(LocalFormat::OtlpJson)
+                    .with_rotation(10 * 1024 * 1024, 2)
+            )
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_values_cannot_inject_keys() {
+        let contents = r#"
+            # Set ${UNSET_IN_COMMENT} to override.
+            [tracer]
+            endpoint = "http://collector:4317"
+
+            [tracer.credentials]
+            bearer_token = "${TOKEN}"
+        "#;
+        let vars = lookup(&[("TOKEN", "abc\"\nendpoint = \"http://evil:4317")]);
+
+        let config = parse(contents, Format::Toml, Path::new("o11y.toml"), &vars).unwrap();
+
+        assert_eq!(
+            config.tracer.endpoint.as_deref(),
+            Some("http://collector:4317")
+        );
+        assert_eq!(
+            config.tracer.credentials.bearer_token.as_deref(),
+            Some("abc\"\nendpoint = \"http://evil:4317")
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_values_cannot_inject_keys() {
+        let contents = r#"
+# Set ${UNSET_IN_COMMENT} to override.
+logger:
+  endpoint: http://loki:3100/otlp
+  credentials:
+    bearer_token: ${TOKEN}
+"#;
+        let vars = lookup(&[("TOKEN", "abc\nendpoint: http://evil # x")]);
+
+        let config = parse(contents, Format::Yaml, Path::new("o11y.yaml"), &vars).unwrap();
+
+        assert_eq!(
+            config.logger.endpoint.as_deref(),
+            Some("http://loki:3100/otlp")
+        );
+        assert_eq!(
+            config.logger.credentials.bearer_token.as_deref(),
+            Some("abc\nendpoint: http://evil # x")
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_interpolated_numbers() {
+        let contents = r#"
+            [tracer]
+            sample_ratio = "${SAMPLE_RATIO:-0.1}"
+
+            [logger]
+            enabled = "${LOGS_ENABLED}"
+
+            [logger.batch]
+            max_export_batch_size = "${BATCH_SIZE}"
+        "#;
+        let vars = lookup(&[("LOGS_ENABLED", "false"), ("BATCH_SIZE", "256")]);
+
+        let config = parse(contents, Format::Toml, Path::new("o11y.toml"), &vars).unwrap();
+
+        assert_eq!(config.tracer.sample_ratio, 0.1);
+        assert!(!config.logger.enabled);
+        assert_eq!(config.logger.batch.max_export_batch_size, 256);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_interpolated_numbers_keep_numeric_strings() {
+        let numbers = r#"
+tracer:
+  sample_ratio: ${SAMPLE_RATIO}
+"#;
+        let strings = r#"
+logger:
+  credentials:
+    basic_password: ${PASSWORD}
+"#;
+        let vars = lookup(&[("SAMPLE_RATIO", "0.25"), ("PASSWORD", "1234")]);
+
+        let config = parse(numbers, Format::Yaml, Path::new("o11y.yaml"), &vars).unwrap();
+        assert_eq!(config.tracer.sample_ratio, 0.25);
+
+        let config = parse(strings, Format::Yaml, Path::new("o11y.yaml"), &vars).unwrap();
+        assert_eq!(
+            config.logger.credentials.basic_password.as_deref(),
+            Some("1234")
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_view_deny_list_is_rejected() {
+        let contents = r#"
+            [[meter.views]]
+            instrument_name = "http.*"
+            denied_attributes = ["user.id"]
+        "#;
+
+        assert!(matches!(
+            parse(contents, Format::Toml, Path::new("o11y.toml"), &lookup(&[])),
+            Err(ConfigError::Parse { .. })
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_round_trip() {
+        let config = Config::new("billing");
+
+        let encoded = toml::to_string(&config).unwrap();
+        let decoded = parse(&encoded, Format::Toml, Path::new("o11y.toml"), &lookup(&[])).unwrap();
+
+        assert_eq!(decoded.resource.service_name, "billing");
+        assert_eq!(decoded.logger.timeout, config.logger.timeout);
+        assert_eq!(decoded.tracer.batch, config.tracer.batch);
+        assert_eq!(decoded.meter.export_interval, config.meter.export_interval);
+        assert_eq!(decoded.profiler.tags, config.profiler.tags);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::telemetry::Component;
+
+/// Reported for a running profiler, which uploads on the Pyroscope agent's
+/// own schedule and cannot be flushed without restarting the agent.
+#[derive(Debug, Error)]
+#[error("flush is not supported, profiles are uploaded on the agent's schedule")]
+pub struct FlushUnsupported;
+
+/// Outcome of [`Telemetry::force_flush`] and [`Telemetry::force_flush_async`].
+/// A signal is `None` when its component is not running.
+///
+/// [`Telemetry::force_flush`]: crate::Telemetry::force_flush
+/// [`Telemetry::force_flush_async`]: crate::Telemetry::force_flush_async
+#[derive(Debug, Default)]
+pub struct FlushReport {
+    pub logger: Option<Result<()>>,
+    pub tracer: Option<Result<()>>,
+    pub meter: Option<Result<()>>,
+    pub profiler: Option<Result<()>>,
+}
+
+impl FlushReport {
+    /// Whether every component that supports flushing flushed successfully.
+    pub fn is_success(&self) -> bool {
+        self.results().all(|(_, result)| match result {
+            Ok(()) => true,
+            Err(err) => err.is::<FlushUnsupported>(),
+        })
+    }
+
+    pub fn result(&self, component: Component) -> Option<&Result<()>> {
+        match component {
+            Component::Logger => self.logger.as_ref(),
+            Component::Tracer => self.tracer.as_ref(),
+            Component::Meter => self.meter.as_ref(),
+            Component::Profiler => self.profiler.as_ref(),
+        }
+    }
+
+    /// Results of the running components, in logger, tracer, meter, profiler order.
+    pub fn results(&self) -> impl Iterator<Item = (Component, &Result<()>)> {
+        [
+            Component::Logger,
+            Component::Tracer,
+            Component::Meter,
+            Component::Profiler,
+        ]
+        .into_iter()
+        .filter_map(|component| Some((component, self.result(component)?)))
+    }
+}
+
+impl fmt::Display for FlushReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut results = self.results().peekable();
+        if results.peek().is_none() {
+            return f.write_str("no components running");
+        }
+
+        for (i, (component, result)) in results.enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match result {
+                Ok(()) => write!(f, "{component}: ok")?,
+                Err(err) => write!(f, "{component}: {err:#}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_flush_is_not_a_failure() {
+        let report = FlushReport {
+            logger: Some(Ok(())),
+            profiler: Some(Err(FlushUnsupported.into())),
+            ..FlushReport::default()
+        };
+        assert!(report.is_success(), "{report}");
+        assert_eq!(
+            report.to_string(),
+            "logger: ok, profiler: flush is not supported, profiles are uploaded on the agent's schedule"
+        );
+
+        let report = FlushReport {
+            logger: Some(Err(anyhow::anyhow!("collector unreachable"))),
+            ..FlushReport::default()
+        };
+        assert!(!report.is_success());
+    }
+}
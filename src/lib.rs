@@ -1,14 +1,33 @@
+#[cfg(not(any(feature = "grpc", feature = "http-proto", feature = "http-json")))]
+compile_error!("enable at least one OTLP transport feature: `grpc`, `http-proto` or `http-json`");
+
 pub mod auth;
+pub mod batch;
 pub mod config;
+pub mod detectors;
+mod env;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+mod file;
+pub mod flush;
+pub mod local;
 pub mod logger;
 pub mod meter;
+pub mod otlp;
 pub mod profiler;
+pub mod shutdown;
+#[cfg(feature = "signal")]
+pub mod signal;
 pub mod telemetry;
 pub mod tracer;
 
 pub use auth::Credentials;
+pub use batch::BatchConfig;
 pub use config::{Config, ResourceConfig};
-pub use telemetry::{Telemetry, TraceContextInfo, current_trace_context};
+pub use flush::{FlushReport, FlushUnsupported};
+pub use local::LocalExporter;
+pub use otlp::{ExporterTarget, Protocol};
+pub use shutdown::{ShutdownReport, TelemetryGuard};
+pub use telemetry::{Telemetry, TelemetryError, TraceContextInfo, current_trace_context};
 
 pub use logger::LoggerProvider;
 pub use meter::MeterProvider;
@@ -0,0 +1,169 @@
+//! Exporters that write telemetry to stdout or a file instead of a collector,
+//! for local development and CI artifacts.
+
+#[cfg(feature = "local")]
+pub(crate) mod text;
+#[cfg(feature = "local")]
+mod writer;
+
+#[cfg(feature = "local")]
+pub(crate) use writer::LocalWriter;
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 5;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LocalError {
+    #[error("the local exporter requires the `local` cargo feature")]
+    Unsupported,
+    #[error("the local exporter needs a file path")]
+    FilePathRequired,
+    #[error("the local exporter needs a positive max_bytes to rotate files")]
+    InvalidMaxBytes,
+}
+
+/// Writes a signal as text lines to stdout or a rotating file. Requires the
+/// `local` feature.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LocalExporter {
+    pub format: LocalFormat,
+    pub output: LocalOutput,
+}
+
+impl LocalExporter {
+    pub fn stdout() -> Self {
+        Self::default()
+    }
+
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            format: LocalFormat::default(),
+            output: LocalOutput::File(FileOutput::new(path)),
+        }
+    }
+
+    pub fn with_format(mut self, format: LocalFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Rolls the file over once it grows past `max_bytes`, keeping `max_files`
+    /// older ones. Has no effect on stdout.
+    pub fn with_rotation(mut self, max_bytes: u64, max_files: usize) -> Self {
+        if let LocalOutput::File(file) = &mut self.output {
+            file.max_bytes = max_bytes;
+            file.max_files = max_files;
+        }
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), LocalError> {
+        if !cfg!(feature = "local") {
+            return Err(LocalError::Unsupported);
+        }
+        if let LocalOutput::File(file) = &self.output {
+            if file.path.as_os_str().is_empty() {
+                return Err(LocalError::FilePathRequired);
+            }
+            if file.max_bytes == 0 {
+                return Err(LocalError::InvalidMaxBytes);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LocalFormat {
+    /// One human-readable line per span, log record or data point.
+    #[default]
+    Pretty,
+    /// One OTLP/JSON export request per line, as the `http-json` protocol
+    /// would send it.
+    OtlpJson,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LocalOutput {
+    #[default]
+    Stdout,
+    File(FileOutput),
+}
+
+/// Appends to `path`; once it grows past `max_bytes` it is renamed to
+/// `path.1`, shifting older files up to `path.{max_files}`. Signals writing to
+/// the same path share one file and must use the same rotation settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FileOutput {
+    pub path: PathBuf,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl Default for FileOutput {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl FileOutput {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let stdout = LocalExporter::stdout().with_format(LocalFormat::OtlpJson);
+        let file = LocalExporter::file("telemetry.log").with_rotation(1024, 2);
+        let unsupported = Err(LocalError::Unsupported);
+
+        if cfg!(feature = "local") {
+            assert_eq!(stdout.validate(), Ok(()));
+            assert_eq!(file.validate(), Ok(()));
+            assert_eq!(
+                LocalExporter::file("").validate(),
+                Err(LocalError::FilePathRequired)
+            );
+            assert_eq!(
+                file.with_rotation(0, 2).validate(),
+                Err(LocalError::InvalidMaxBytes)
+            );
+        } else {
+            assert_eq!(stdout.validate(), unsupported);
+        }
+    }
+
+    #[test]
+    fn test_rotation_only_applies_to_files() {
+        assert_eq!(
+            LocalExporter::stdout().with_rotation(1, 1),
+            LocalExporter::stdout()
+        );
+
+        let LocalOutput::File(file) = LocalExporter::file("out.log").with_rotation(1, 7).output
+        else {
+            panic!("expected a file output");
+        };
+        assert_eq!((file.max_bytes, file.max_files), (1, 7));
+    }
+}
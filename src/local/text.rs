@@ -0,0 +1,84 @@
+//! Pieces shared by the pretty formats of the local exporters.
+
+use opentelemetry::{Key, Value};
+use opentelemetry_sdk::Resource;
+use std::fmt::Write as _;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// RFC 3339 in UTC with millisecond precision.
+pub(crate) fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let seconds_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Howard Hinnant's days-to-date conversion for the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub(crate) fn service_name(resource: &Resource) -> String {
+    resource
+        .get(Key::from_static_str("service.name"))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| "unknown_service".to_string())
+}
+
+/// Appends ` key=value`, quoting values that would be ambiguous bare.
+pub(crate) fn field(out: &mut String, key: &str, value: &str) {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        let _ = write!(out, " {key}={value:?}");
+    } else {
+        let _ = write!(out, " {key}={value}");
+    }
+}
+
+pub(crate) fn attributes<'a>(
+    out: &mut String,
+    attributes: impl IntoIterator<Item = (&'a Key, &'a Value)>,
+) {
+    for (key, value) in attributes {
+        field(out, key.as_str(), &value.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_millis(1_709_210_096_789)),
+            "2024-02-29T12:34:56.789Z"
+        );
+    }
+
+    #[test]
+    fn test_field_quoting() {
+        let mut out = String::new();
+        field(&mut out, "method", "GET");
+        field(&mut out, "path", "/a b");
+        field(&mut out, "empty", "");
+        assert_eq!(out, r#" method=GET path="/a b" empty="""#);
+    }
+}
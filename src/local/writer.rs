@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+
+use super::{FileOutput, LocalOutput};
+
+/// Open files by path, so signals pointed at the same file append to, and
+/// rotate, one handle. Opening a path again with different rotation settings
+/// fails instead of silently using the first ones.
+static FILES: LazyLock<Mutex<HashMap<PathBuf, Weak<Mutex<RotatingFile>>>>> =
+    LazyLock::new(Default::default);
+
+/// Writes whole chunks of lines, so concurrent exports never interleave
+/// within a line.
+#[derive(Clone, Debug)]
+pub(crate) enum LocalWriter {
+    Stdout,
+    File(Arc<Mutex<RotatingFile>>),
+}
+
+impl LocalWriter {
+    pub(crate) fn open(output: &LocalOutput) -> io::Result<Self> {
+        let config = match output {
+            LocalOutput::Stdout => return Ok(LocalWriter::Stdout),
+            LocalOutput::File(config) => config,
+        };
+
+        let mut files = FILES.lock().unwrap_or_else(|e| e.into_inner());
+        files.retain(|_, file| file.strong_count() > 0);
+        if let Some(file) = files.get(&config.path).and_then(Weak::upgrade) {
+            file.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .check_rotation(config)?;
+            return Ok(LocalWriter::File(file));
+        }
+        let file = Arc::new(Mutex::new(RotatingFile::open(config)?));
+        files.insert(config.path.clone(), Arc::downgrade(&file));
+        Ok(LocalWriter::File(file))
+    }
+
+    pub(crate) fn write(&self, text: &str) -> io::Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        match self {
+            LocalWriter::Stdout => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(text.as_bytes())?;
+                stdout.flush()
+            }
+            LocalWriter::File(file) => file
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .write(text.as_bytes()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    fn open(config: &FileOutput) -> io::Result<Self> {
+        if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = append(&config.path)?;
+        Ok(Self {
+            path: config.path.clone(),
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+            len: file.metadata()?.len(),
+            file,
+        })
+    }
+
+    fn check_rotation(&self, config: &FileOutput) -> io::Result<()> {
+        if (self.max_bytes, self.max_files) == (config.max_bytes, config.max_files) {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} is already open with max_bytes {} and max_files {}",
+                self.path.display(),
+                self.max_bytes,
+                self.max_files
+            ),
+        ))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.len > 0 && self.len + bytes.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(bytes)?;
+        self.len += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated(index);
+                if from.exists() {
+                    fs::rename(from, self.rotated(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+            self.file = append(&self.path)?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{index}"));
+        path.into()
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("o11y-{name}-{nanos}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_output(path: &Path, max_bytes: u64, max_files: usize) -> LocalOutput {
+        LocalOutput::File(FileOutput {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_files,
+        })
+    }
+
+    #[test]
+    fn test_rotates_and_keeps_max_files() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("telemetry.log");
+        let writer = LocalWriter::open(&file_output(&path, 10, 2)).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write(line).unwrap();
+        }
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert_eq!(read(path.clone()), "fourth\n");
+        assert_eq!(read(dir.join("telemetry.log.1")), "third\n");
+        assert_eq!(read(dir.join("telemetry.log.2")), "second\n");
+        assert!(!dir.join("telemetry.log.3").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_same_path_shares_a_file() {
+        let dir = temp_dir("shared");
+        let path = dir.join("nested/telemetry.log");
+        let traces = LocalWriter::open(&file_output(&path, 1024, 1)).unwrap();
+        let logs = LocalWriter::open(&file_output(&path, 1024, 1)).unwrap();
+
+        traces.write("span\n").unwrap();
+        logs.write("log\n").unwrap();
+
+        assert!(
+            matches!((&traces, &logs), (LocalWriter::File(a), LocalWriter::File(b)) if Arc::ptr_eq(a, b))
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "span\nlog\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_same_path_rejects_other_rotation() {
+        let dir = temp_dir("mismatch");
+        let path = dir.join("telemetry.log");
+        let _traces = LocalWriter::open(&file_output(&path, 1024, 1)).unwrap();
+
+        let err = LocalWriter::open(&file_output(&path, 2048, 1)).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
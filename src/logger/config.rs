@@ -1,7 +1,12 @@
+use super::{ConsoleLogConfig, FilterError, LogFilterConfig};
 use crate::auth::Credentials;
+use crate::batch::{BatchConfig, BatchError};
+use crate::local::{LocalError, LocalExporter};
+use crate::otlp::{ExporterTarget, Protocol, TargetError};
 use std::time::Duration;
 use thiserror::Error;
 
+const DEFAULT_SCHEDULED_DELAY: Duration = Duration::from_secs(1);
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Error)]
@@ -10,27 +15,83 @@ pub enum LoggerError {
     ServiceNameRequired,
     #[error("logger endpoint is required when enabled")]
     EndpointRequired,
+    #[error("logger protocol {0} requires the `{feature}` cargo feature", feature = .0.feature())]
+    UnsupportedProtocol(Protocol),
+    #[error("logger local exporter: {0}")]
+    Local(#[from] LocalError),
+    #[error("logger target {index}: {source}")]
+    Target { index: usize, source: TargetError },
+    #[error("logger {0}")]
+    Filter(#[from] FilterError),
+    #[error("logger batch configuration error: {0}")]
+    Batch(#[from] BatchError),
+}
+
+/// Where log records go. OTLP sends them to `LoggerConfig::endpoint`;
+/// `Local` writes each one as it is emitted to stdout or a file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum LoggerExporter {
+    #[default]
+    Otlp,
+    Local(LocalExporter),
+}
+
+impl From<LocalExporter> for LoggerExporter {
+    fn from(local: LocalExporter) -> Self {
+        LoggerExporter::Local(local)
+    }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct LoggerConfig {
     pub enabled: bool,
+    pub exporter: LoggerExporter,
     pub endpoint: Option<String>,
+    /// Uses `endpoint` as the full URL instead of appending the signal path,
+    /// as `OTEL_EXPORTER_OTLP_LOGS_ENDPOINT` requires.
+    pub exact_endpoint: bool,
+    pub protocol: Protocol,
     pub service_name: String,
     pub environment: String,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
     pub timeout: Duration,
+    pub batch: BatchConfig,
     pub credentials: Credentials,
+    /// Additional [`ExporterTarget`]s.
+    pub targets: Vec<ExporterTarget>,
+    /// What `Telemetry` prints to stdout for each event, whether or not
+    /// logs are exported.
+    pub console: ConsoleLogConfig,
+    /// Which events and spans the installed subscriber lets through.
+    pub filter: LogFilterConfig,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self::new("")
+    }
 }
 
 impl LoggerConfig {
     pub fn new(service_name: impl Into<String>) -> Self {
         Self {
             enabled: true,
+            exporter: LoggerExporter::default(),
             endpoint: None,
+            exact_endpoint: false,
+            protocol: Protocol::HttpProtobuf,
             service_name: service_name.into(),
             environment: "development".to_string(),
             timeout: DEFAULT_TIMEOUT,
+            batch: BatchConfig::default().with_scheduled_delay(DEFAULT_SCHEDULED_DELAY),
             credentials: Credentials::new(),
+            targets: Vec::new(),
+            console: ConsoleLogConfig::default(),
+            filter: LogFilterConfig::default(),
         }
     }
 
@@ -39,11 +100,28 @@ impl LoggerConfig {
         self
     }
 
+    pub fn with_exporter(mut self, exporter: impl Into<LoggerExporter>) -> Self {
+        self.exporter = exporter.into();
+        self
+    }
+
     pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
         self.endpoint = Some(endpoint.into());
         self
     }
 
+    /// Sets a full signal URL that is dialled without appending a path.
+    pub fn with_signal_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self.exact_endpoint = true;
+        self
+    }
+
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
     pub fn with_environment(mut self, environment: impl Into<String>) -> Self {
         self.environment = environment.into();
         self
@@ -54,30 +132,75 @@ impl LoggerConfig {
         self
     }
 
+    pub fn with_batch(mut self, batch: BatchConfig) -> Self {
+        self.batch = batch;
+        self
+    }
+
     pub fn with_credentials(mut self, credentials: Credentials) -> Self {
         self.credentials = credentials;
         self
     }
 
+    /// Adds an [`ExporterTarget`].
+    pub fn with_target(mut self, target: ExporterTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    pub fn with_console(mut self, console: ConsoleLogConfig) -> Self {
+        self.console = console;
+        self
+    }
+
+    /// Replaces the default `EnvFilter` directives shared by every layer.
+    pub fn with_filter(mut self, directives: impl Into<String>) -> Self {
+        self.filter.directives = Some(directives.into());
+        self
+    }
+
+    pub fn with_filter_config(mut self, filter: LogFilterConfig) -> Self {
+        self.filter = filter;
+        self
+    }
+
     pub fn apply_defaults(&mut self) {
-        if self.timeout.as_secs() == 0 {
+        if self.timeout.is_zero() {
             self.timeout = DEFAULT_TIMEOUT;
         }
         if self.environment.is_empty() {
             self.environment = "development".to_string();
         }
+        self.batch.apply_defaults(DEFAULT_SCHEDULED_DELAY);
+        self.filter.apply_defaults();
     }
 
     pub fn validate(&self) -> Result<(), LoggerError> {
+        // The subscriber is installed whether or not logs are exported.
+        self.filter.validate()?;
         if !self.enabled {
             return Ok(());
         }
         if self.service_name.is_empty() {
             return Err(LoggerError::ServiceNameRequired);
         }
-        if self.endpoint.is_none() {
-            return Err(LoggerError::EndpointRequired);
+        match &self.exporter {
+            LoggerExporter::Otlp => {
+                if self.endpoint.is_none() {
+                    return Err(LoggerError::EndpointRequired);
+                }
+                if !self.protocol.is_supported() {
+                    return Err(LoggerError::UnsupportedProtocol(self.protocol));
+                }
+            }
+            LoggerExporter::Local(local) => local.validate()?,
         }
+        for (index, target) in self.targets.iter().enumerate() {
+            target
+                .validate(self.protocol)
+                .map_err(|source| LoggerError::Target { index, source })?;
+        }
+        self.batch.validate()?;
         Ok(())
     }
 }
@@ -101,6 +224,41 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_logger_config_local_exporter_needs_no_endpoint() {
+        let config = LoggerConfig::new("test").with_exporter(LocalExporter::stdout());
+
+        if cfg!(feature = "local") {
+            assert!(config.validate().is_ok());
+        } else {
+            assert!(matches!(
+                config.validate(),
+                Err(LoggerError::Local(LocalError::Unsupported))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_logger_config_validates_filter_when_disabled() {
+        let config = LoggerConfig::new("test")
+            .enabled(false)
+            .with_filter("info,hyper=chatty");
+
+        assert!(matches!(config.validate(), Err(LoggerError::Filter(_))));
+    }
+
+    #[test]
+    fn test_logger_config_applies_defaults() {
+        let mut config = LoggerConfig::new("test");
+        config.timeout = Duration::ZERO;
+        config.batch.scheduled_delay = Duration::ZERO;
+
+        config.apply_defaults();
+
+        assert_eq!(config.timeout, DEFAULT_TIMEOUT);
+        assert_eq!(config.batch.scheduled_delay, DEFAULT_SCHEDULED_DELAY);
+    }
+
     #[test]
     fn test_logger_config_builder() {
         let config = LoggerConfig::new("my-service")
@@ -0,0 +1,752 @@
+//! The human- or machine-readable log lines `Telemetry` writes to stdout.
+
+use opentelemetry_sdk::trace::Tracer;
+use std::borrow::Cow;
+use std::fmt::{self, Write as _};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::Record;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::{self, Writer};
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::fmt::{
+    self as tracing_fmt, FmtContext, FormatEvent, FormatFields, FormattedFields,
+};
+use tracing_subscriber::registry::LookupSpan;
+
+use super::context;
+use crate::telemetry::TraceContextInfo;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ConsoleFormat {
+    /// No console output; logs only reach the OTLP exporter.
+    Off,
+    /// Multi-line, with each field and the source location on its own line.
+    Pretty,
+    /// One short line, with span fields appended after the event's.
+    Compact,
+    /// One line with the span context, target, file and line.
+    #[default]
+    Full,
+    /// One JSON object per line, with the current span's name and fields under
+    /// `span` and, inside a traced span, `trace_id` and `span_id`.
+    Json,
+    /// `key=value` pairs, carrying `trace_id` and `span_id` inside a traced span.
+    Logfmt,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ConsoleTimestamp {
+    None,
+    /// Wall-clock time in UTC, e.g. `2024-05-01T12:00:00.123456Z`.
+    #[default]
+    Rfc3339,
+    /// Seconds since the Unix epoch with millisecond precision.
+    Unix,
+    /// Time elapsed since the subscriber was installed.
+    Uptime,
+}
+
+/// How log events are printed to stdout, independently of OTLP export.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ConsoleLogConfig {
+    pub format: ConsoleFormat,
+    pub timestamp: ConsoleTimestamp,
+    /// Colours levels and field names in the pretty, compact and full formats.
+    pub ansi: bool,
+    /// Puts event fields at the top level of JSON lines instead of under
+    /// `fields`. Fields named like a built-in key, e.g. `level`, are written
+    /// as `fields.level` so no key appears twice.
+    pub flatten_fields: bool,
+    /// Appends `trace_id` and `span_id` to pretty, compact and full lines
+    /// inside a traced span; JSON and logfmt lines always carry them.
+    pub trace_ids: bool,
+}
+
+impl ConsoleLogConfig {
+    pub fn off() -> Self {
+        Self::default().with_format(ConsoleFormat::Off)
+    }
+
+    pub fn json() -> Self {
+        Self::default().with_format(ConsoleFormat::Json)
+    }
+
+    pub fn with_format(mut self, format: ConsoleFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: ConsoleTimestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    pub fn flatten_fields(mut self, flatten: bool) -> Self {
+        self.flatten_fields = flatten;
+        self
+    }
+
+    pub fn trace_ids(mut self, trace_ids: bool) -> Self {
+        self.trace_ids = trace_ids;
+        self
+    }
+}
+
+/// The stdout layer for `config`, or `None` when the console is off. Trace
+/// ids are read through `tracer`, so they match the exported spans.
+pub(crate) fn layer<S>(
+    config: &ConsoleLogConfig,
+    tracer: Option<Tracer>,
+) -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let timer = Timer::new(config.timestamp);
+    let base = tracing_fmt::layer().with_ansi(config.ansi);
+    let format = tracing_fmt::format().with_ansi(config.ansi);
+    let trace_ids = tracer.clone().filter(|_| config.trace_ids);
+
+    macro_rules! builtin {
+        ($layer:expr, $format:expr) => {
+            match timer {
+                Some(timer) => $layer
+                    .event_format(WithTraceIds {
+                        format: $format.with_timer(timer),
+                        tracer: trace_ids,
+                    })
+                    .boxed(),
+                None => $layer
+                    .event_format(WithTraceIds {
+                        format: $format.without_time(),
+                        tracer: trace_ids,
+                    })
+                    .boxed(),
+            }
+        };
+    }
+
+    let layer = match config.format {
+        ConsoleFormat::Off => return None,
+        ConsoleFormat::Pretty => {
+            builtin!(base.fmt_fields(format::Pretty::default()), format.pretty())
+        }
+        ConsoleFormat::Compact => builtin!(base, format.compact()),
+        ConsoleFormat::Full => builtin!(base, format.with_file(true).with_line_number(true)),
+        ConsoleFormat::Json => base
+            .with_ansi(false)
+            .fmt_fields(JsonFields)
+            .event_format(JsonFormat {
+                timer,
+                flatten: config.flatten_fields,
+                tracer,
+            })
+            .boxed(),
+        ConsoleFormat::Logfmt => base
+            .with_ansi(false)
+            .event_format(LogfmtFormat { timer, tracer })
+            .boxed(),
+    };
+    Some(layer)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Timer {
+    Rfc3339,
+    Unix,
+    Uptime(Instant),
+}
+
+impl Timer {
+    fn new(timestamp: ConsoleTimestamp) -> Option<Self> {
+        match timestamp {
+            ConsoleTimestamp::None => None,
+            ConsoleTimestamp::Rfc3339 => Some(Timer::Rfc3339),
+            ConsoleTimestamp::Unix => Some(Timer::Unix),
+            ConsoleTimestamp::Uptime => Some(Timer::Uptime(Instant::now())),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = self.format_time(&mut Writer::new(&mut out));
+        out
+    }
+}
+
+impl FormatTime for Timer {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        match self {
+            Timer::Rfc3339 => tracing_fmt::time::SystemTime.format_time(w),
+            Timer::Unix => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                write!(w, "{}.{:03}", now.as_secs(), now.subsec_millis())
+            }
+            Timer::Uptime(start) => {
+                let elapsed = start.elapsed();
+                write!(w, "{:4}.{:09}s", elapsed.as_secs(), elapsed.subsec_nanos())
+            }
+        }
+    }
+}
+
+/// A recorded field value; `Raw` values are numbers or booleans.
+enum FieldValue {
+    Raw(String),
+    Text(String),
+}
+
+#[derive(Default)]
+struct Fields(Vec<(&'static str, FieldValue)>);
+
+impl Visit for Fields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        let value = if value.is_finite() {
+            FieldValue::Raw(value.to_string())
+        } else {
+            FieldValue::Text(value.to_string())
+        };
+        self.0.push((field.name(), value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0
+            .push((field.name(), FieldValue::Raw(value.to_string())));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0
+            .push((field.name(), FieldValue::Raw(value.to_string())));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0
+            .push((field.name(), FieldValue::Raw(value.to_string())));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0
+            .push((field.name(), FieldValue::Text(value.to_string())));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .push((field.name(), FieldValue::Text(format!("{value:?}"))));
+    }
+}
+
+impl Fields {
+    /// The fields as encoded JSON members, renaming any in `reserved`.
+    fn json_members(&self, reserved: &[&str]) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    FieldValue::Raw(raw) => raw.clone(),
+                    FieldValue::Text(text) => json_string(text),
+                };
+                json_member(&field_key(name, reserved), &value)
+            })
+            .collect()
+    }
+}
+
+/// `name`, or `fields.{name}` when it would collide with a key in `reserved`.
+fn field_key<'a>(name: &'a str, reserved: &[&str]) -> Cow<'a, str> {
+    if reserved.contains(&name) {
+        Cow::Owned(format!("fields.{name}"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Keys `JsonFormat` writes itself at the top level of a line.
+const JSON_KEYS: [&str; 7] = [
+    "timestamp",
+    "level",
+    "target",
+    "fields",
+    "span",
+    "trace_id",
+    "span_id",
+];
+
+/// Keys `JsonFormat` writes itself inside the `span` object.
+const JSON_SPAN_KEYS: [&str; 1] = ["name"];
+
+/// Stores span fields as JSON members, one per line, so `JsonFormat` can put
+/// them under `span`. Encoded members never contain a newline.
+struct JsonFields;
+
+impl<'writer> FormatFields<'writer> for JsonFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = Fields::default();
+        fields.record(&mut visitor);
+        writer.write_str(&visitor.json_members(&JSON_SPAN_KEYS).join("\n"))
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut FormattedFields<Self>,
+        fields: &Record<'_>,
+    ) -> fmt::Result {
+        let mut visitor = Fields::default();
+        fields.record(&mut visitor);
+        let added = visitor.json_members(&JSON_SPAN_KEYS);
+        if added.is_empty() {
+            return Ok(());
+        }
+        // A recorded value replaces the one the span was created with.
+        let mut members: Vec<&str> = current
+            .fields
+            .lines()
+            .filter(|member| {
+                !added
+                    .iter()
+                    .any(|new| member_key(new) == member_key(member))
+            })
+            .collect();
+        members.extend(added.iter().map(String::as_str));
+        current.fields = members.join("\n");
+        Ok(())
+    }
+}
+
+/// The trace context of the span an event was recorded in, if it is traced.
+fn trace_context<S, N>(
+    ctx: &FmtContext<'_, S, N>,
+    tracer: Option<&Tracer>,
+) -> Option<TraceContextInfo>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    let span = ctx.lookup_current()?;
+    let context = context::span_context(&span, tracer?)?;
+    TraceContextInfo::from_context(&context)
+}
+
+/// Appends ` trace_id=… span_id=…` to the lines of events in a traced span.
+struct WithTraceIds<F> {
+    format: F,
+    tracer: Option<Tracer>,
+}
+
+impl<S, N, F> FormatEvent<S, N> for WithTraceIds<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let Some(trace) = trace_context(ctx, self.tracer.as_ref()) else {
+            return self.format.format_event(ctx, writer, event);
+        };
+        // The built-in formats set their own ANSI mode, so buffering keeps colours.
+        let mut line = String::new();
+        self.format
+            .format_event(ctx, Writer::new(&mut line), event)?;
+        let body = line.trim_end_matches('\n');
+        let mut writer = writer;
+        write!(
+            writer,
+            "{body} trace_id={} span_id={}{}",
+            trace.trace_id,
+            trace.span_id,
+            &line[body.len()..]
+        )
+    }
+}
+
+struct JsonFormat {
+    timer: Option<Timer>,
+    flatten: bool,
+    tracer: Option<Tracer>,
+}
+
+impl<S> FormatEvent<S, JsonFields> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, JsonFields>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let mut members = Vec::new();
+        if let Some(timer) = &self.timer {
+            members.push(json_member("timestamp", &json_string(&timer.render())));
+        }
+        members.push(json_member(
+            "level",
+            &json_string(metadata.level().as_str()),
+        ));
+        members.push(json_member("target", &json_string(metadata.target())));
+        if self.flatten {
+            members.extend(fields.json_members(&JSON_KEYS));
+        } else {
+            let nested = fields.json_members(&[]).join(",");
+            members.push(json_member("fields", &format!("{{{nested}}}")));
+        }
+        if let Some(span) = ctx.lookup_current() {
+            let mut object = vec![json_member("name", &json_string(span.name()))];
+            if let Some(formatted) = span.extensions().get::<FormattedFields<JsonFields>>() {
+                object.extend(formatted.fields.lines().map(String::from));
+            }
+            members.push(json_member("span", &format!("{{{}}}", object.join(","))));
+        }
+        if let Some(trace) = trace_context(ctx, self.tracer.as_ref()) {
+            members.push(json_member("trace_id", &json_string(&trace.trace_id)));
+            members.push(json_member("span_id", &json_string(&trace.span_id)));
+        }
+
+        writeln!(writer, "{{{}}}", members.join(","))
+    }
+}
+
+fn json_member(key: &str, value: &str) -> String {
+    format!("{}:{value}", json_string(key))
+}
+
+/// The encoded key of a member built by `json_member`.
+fn member_key(member: &str) -> &str {
+    let mut escaped = false;
+    for (index, c) in member.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return &member[..=index],
+            _ => {}
+        }
+    }
+    member
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct LogfmtFormat {
+    timer: Option<Timer>,
+    tracer: Option<Tracer>,
+}
+
+impl<S, N> FormatEvent<S, N> for LogfmtFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let mut pairs: Vec<(&str, String)> = Vec::new();
+        if let Some(timer) = &self.timer {
+            pairs.push(("time", timer.render()));
+        }
+        pairs.push(("level", metadata.level().as_str().to_ascii_lowercase()));
+        pairs.push(("target", metadata.target().to_string()));
+        for (name, value) in fields.0 {
+            let name = if name == "message" { "msg" } else { name };
+            match value {
+                FieldValue::Raw(value) | FieldValue::Text(value) => pairs.push((name, value)),
+            }
+        }
+        if let Some(span) = ctx.lookup_current() {
+            pairs.push(("span", span.name().to_string()));
+        }
+        if let Some(trace) = trace_context(ctx, self.tracer.as_ref()) {
+            pairs.push(("trace_id", trace.trace_id));
+            pairs.push(("span_id", trace.span_id));
+        }
+
+        let line: Vec<String> = pairs
+            .iter()
+            .map(|(key, value)| format!("{key}={}", logfmt_value(value)))
+            .collect();
+        writeln!(writer, "{}", line.join(" "))
+    }
+}
+
+fn logfmt_value(value: &str) -> String {
+    let bare = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '"' || c == '=');
+    if bare {
+        value.to_string()
+    } else {
+        json_string(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::Registry;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Captured {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    /// Formats one event inside a traced `checkout` span, returning the line and trace id.
+    fn capture<E>(format: impl FnOnce(Option<Tracer>) -> E) -> (String, String)
+    where
+        E: FormatEvent<Registry, tracing_fmt::format::DefaultFields> + Send + Sync + 'static,
+    {
+        capture_with(tracing_fmt::format::DefaultFields::new(), format, || {
+            tracing::info!(
+                order = 7,
+                paid = true,
+                note = "two words",
+                "order \"placed\""
+            );
+        })
+    }
+
+    fn capture_json(flatten: bool, emit: impl FnOnce()) -> (String, String) {
+        capture_with(
+            JsonFields,
+            |tracer| JsonFormat {
+                timer: None,
+                flatten,
+                tracer,
+            },
+            emit,
+        )
+    }
+
+    fn capture_with<N, E>(
+        fields: N,
+        format: impl FnOnce(Option<Tracer>) -> E,
+        emit: impl FnOnce(),
+    ) -> (String, String)
+    where
+        N: for<'w> FormatFields<'w> + Send + Sync + 'static,
+        E: FormatEvent<Registry, N> + Send + Sync + 'static,
+    {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let tracer = TracerProvider::builder().build().tracer("test_tracer");
+        let subscriber = Registry::default()
+            .with(
+                tracing_fmt::layer()
+                    .with_writer(move || writer.clone())
+                    .fmt_fields(fields)
+                    .event_format(format(Some(tracer.clone()))),
+            )
+            .with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        let mut trace_id = String::new();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("checkout");
+            let _entered = span.enter();
+            emit();
+            trace_id = crate::telemetry::current_trace_context()
+                .map(|context| context.trace_id)
+                .unwrap_or_default();
+        });
+        (captured.text(), trace_id)
+    }
+
+    fn emit_order() {
+        tracing::info!(
+            order = 7,
+            paid = true,
+            note = "two words",
+            "order \"placed\""
+        );
+    }
+
+    #[test]
+    fn test_json_format() {
+        let (line, trace_id) = capture_json(false, emit_order);
+
+        assert!(
+            line.starts_with(
+                r#"{"level":"INFO","target":"o11y::logger::console::tests","fields":{"message":"order \"placed\"","order":7,"paid":true,"note":"two words"},"span":{"name":"checkout"},"trace_id":""#
+            ),
+            "{line}"
+        );
+        assert!(line.contains(&trace_id) && trace_id.len() == 32, "{line}");
+        assert!(line.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_json_flattened_fields() {
+        let (line, _) = capture_with(
+            JsonFields,
+            |tracer| JsonFormat {
+                timer: Some(Timer::Unix),
+                flatten: true,
+                tracer,
+            },
+            emit_order,
+        );
+
+        assert!(line.starts_with(r#"{"timestamp":""#), "{line}");
+        assert!(
+            line.contains(
+                r#""target":"o11y::logger::console::tests","message":"order \"placed\"","order":7,"#
+            ),
+            "{line}"
+        );
+        assert!(!line.contains(r#""fields""#));
+    }
+
+    #[test]
+    fn test_json_flattened_fields_do_not_repeat_keys() {
+        let (line, _) = capture_json(true, || {
+            tracing::info!(level = "custom", span_id = 3, "collides");
+        });
+
+        for key in ["level", "span_id"] {
+            assert_eq!(line.matches(&format!(r#""{key}":"#)).count(), 1, "{line}");
+        }
+        assert!(
+            line.contains(r#""fields.level":"custom","fields.span_id":3"#),
+            "{line}"
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["level"], "INFO");
+        assert_eq!(parsed["span_id"].as_str().map(str::len), Some(16));
+    }
+
+    #[test]
+    fn test_json_span_fields() {
+        let (line, _) = capture_json(false, || {
+            let span = tracing::info_span!("request", user_id = 42, name = "alice");
+            let _entered = span.enter();
+            span.record("user_id", 43);
+            tracing::info!("handled");
+        });
+
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["span"]["name"], "request", "{line}");
+        assert_eq!(parsed["span"]["fields.name"], "alice", "{line}");
+        assert_eq!(line.matches(r#""user_id":"#).count(), 1, "{line}");
+        assert_eq!(parsed["span"]["user_id"], 43, "{line}");
+    }
+
+    #[test]
+    fn test_logfmt_format() {
+        let (line, trace_id) = capture(|tracer| LogfmtFormat {
+            timer: None,
+            tracer,
+        });
+
+        assert_eq!(
+            line,
+            format!(
+                "level=info target=o11y::logger::console::tests msg=\"order \\\"placed\\\"\" order=7 paid=true note=\"two words\" span=checkout trace_id={trace_id} span_id={}\n",
+                &line[line.len() - 17..line.len() - 1]
+            )
+        );
+    }
+
+    #[test]
+    fn test_compact_trace_ids() {
+        let (line, trace_id) = capture(|tracer| WithTraceIds {
+            format: tracing_fmt::format()
+                .compact()
+                .with_ansi(false)
+                .without_time(),
+            tracer,
+        });
+
+        assert!(
+            line.starts_with(" INFO checkout: o11y::logger::console::tests: order"),
+            "{line}"
+        );
+        let suffix = format!(" trace_id={trace_id} span_id=");
+        let span_id = line.trim_end().rsplit_once(&suffix).map(|(_, id)| id);
+        assert_eq!(span_id.map(str::len), Some(16), "{line}");
+        assert!(line.ends_with('\n') && !line.ends_with("\n\n"));
+    }
+
+    #[test]
+    fn test_off_has_no_layer() {
+        assert!(layer::<Registry>(&ConsoleLogConfig::off(), None).is_none());
+        assert!(layer::<Registry>(&ConsoleLogConfig::json(), None).is_some());
+        assert!(layer::<Registry>(&ConsoleLogConfig::default().trace_ids(true), None).is_some());
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+        assert_eq!(logfmt_value(""), r#""""#);
+        assert_eq!(logfmt_value("plain"), "plain");
+    }
+}
@@ -0,0 +1,120 @@
+//! Trace context for code running inside a subscriber layer, where
+//! `Span::current` cannot be used because the dispatcher is busy delivering
+//! the event.
+
+use opentelemetry::Context;
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry_sdk::logs::Logger;
+use opentelemetry_sdk::trace::Tracer;
+use tracing::{Event, Subscriber};
+use tracing_opentelemetry::{OtelData, PreSampledTracer};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context as LayerContext;
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+
+use super::LoggerProvider;
+
+/// The OpenTelemetry context of `span`, with the sampling decision its
+/// exported span will carry.
+pub(crate) fn span_context<S>(span: &SpanRef<'_, S>, tracer: &Tracer) -> Option<Context>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    let mut extensions = span.extensions_mut();
+    let data = extensions.get_mut::<OtelData>()?;
+    Some(tracer.sampled_context(data))
+}
+
+/// The OTLP log bridge, run with the event's span attached as the current
+/// OpenTelemetry context so the SDK stamps its trace and span ids onto the
+/// exported record.
+pub(crate) struct TraceContextBridge {
+    bridge: OpenTelemetryTracingBridge<LoggerProvider, Logger>,
+    tracer: Option<Tracer>,
+}
+
+impl TraceContextBridge {
+    pub(crate) fn new(provider: &LoggerProvider, tracer: Option<Tracer>) -> Self {
+        Self {
+            bridge: OpenTelemetryTracingBridge::new(provider),
+            tracer,
+        }
+    }
+}
+
+impl<S> Layer<S> for TraceContextBridge
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let _attached = self
+            .tracer
+            .as_ref()
+            .zip(ctx.event_span(event))
+            .and_then(|(tracer, span)| span_context(&span, tracer))
+            .map(Context::attach);
+        self.bridge.on_event(event, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::InstrumentationScope;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::logs::{LogProcessor, LogRecord, LogResult, TraceContext};
+    use opentelemetry_sdk::trace::TracerProvider;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::Registry;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Keeps the trace context of every emitted record.
+    #[derive(Clone, Debug, Default)]
+    struct Captured(Arc<Mutex<Vec<Option<TraceContext>>>>);
+
+    impl LogProcessor for Captured {
+        fn emit(&self, record: &mut LogRecord, _scope: &InstrumentationScope) {
+            self.0.lock().unwrap().push(record.trace_context.clone());
+        }
+
+        fn force_flush(&self) -> LogResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) -> LogResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_bridge_stamps_span_context() {
+        let captured = Captured::default();
+        let logger = LoggerProvider::builder()
+            .with_log_processor(captured.clone())
+            .build();
+        let tracer = TracerProvider::builder().build().tracer("test_tracer");
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer.clone()))
+            .with(TraceContextBridge::new(&logger, Some(tracer)));
+
+        let expected = tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside");
+            let span = tracing::info_span!("checkout");
+            let _entered = span.enter();
+            tracing::info!("inside");
+            crate::telemetry::current_trace_context()
+        })
+        .unwrap();
+
+        let records = captured.0.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_none());
+        let stamped = records[1].as_ref().unwrap();
+        assert_eq!(stamped.trace_id.to_string(), expected.trace_id);
+        assert_eq!(stamped.span_id.to_string(), expected.span_id);
+        assert_eq!(
+            stamped.trace_flags.map(|flags| flags.is_sampled()),
+            Some(expected.sampled)
+        );
+    }
+}
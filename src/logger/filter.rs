@@ -0,0 +1,224 @@
+//! Which events and spans reach each layer of the subscriber `Telemetry` installs.
+
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::Subscriber;
+use tracing_subscriber::filter::ParseError;
+use tracing_subscriber::{EnvFilter, reload};
+
+use super::level::{LogLevelHandle, ReloadableFilter};
+
+const DEFAULT_ENV_VAR: &str = "RUST_LOG";
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_DIRECTIVES_SUFFIX: &str = "otel::tracing=trace,axum_tracing_opentelemetry=trace";
+
+#[derive(Debug, Error)]
+pub enum FilterError {
+    #[error("{layer} directives are invalid: {source}")]
+    InvalidDirectives {
+        layer: &'static str,
+        source: ParseError,
+    },
+}
+
+/// `EnvFilter` directives, e.g. `info,my_crate=debug,hyper=warn`, shared by
+/// the console, OTLP log bridge and span layers unless a layer sets its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LogFilterConfig {
+    /// Defaults to `info,{service}=debug` plus the OpenTelemetry crates at `trace`.
+    pub directives: Option<String>,
+    /// Read before `directives`, so each binary in a pod can be tuned on its own.
+    pub env_var: String,
+    pub console: Option<String>,
+    /// Events forwarded to the OTLP log exporter.
+    pub export: Option<String>,
+    /// Spans recorded by the OpenTelemetry tracing layer.
+    pub spans: Option<String>,
+    /// A file whose directives replace the filters while it is non-empty;
+    /// see [`LogLevelHandle::watch_file`].
+    pub watch_file: Option<PathBuf>,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub watch_interval: Duration,
+}
+
+impl Default for LogFilterConfig {
+    fn default() -> Self {
+        Self {
+            directives: None,
+            env_var: DEFAULT_ENV_VAR.to_string(),
+            console: None,
+            export: None,
+            spans: None,
+            watch_file: None,
+            watch_interval: DEFAULT_WATCH_INTERVAL,
+        }
+    }
+}
+
+/// Hands out one reloadable filter per layer, since an `EnvFilter` can only
+/// be attached once, and collects them into a [`LogLevelHandle`].
+pub(crate) struct LayerFilters<'a> {
+    config: &'a LogFilterConfig,
+    shared: String,
+    reloadable: Vec<ReloadableFilter>,
+}
+
+impl LayerFilters<'_> {
+    pub(crate) fn console<S: Subscriber>(&mut self) -> reload::Layer<EnvFilter, S> {
+        self.filter(self.config.console.clone())
+    }
+
+    pub(crate) fn export<S: Subscriber>(&mut self) -> reload::Layer<EnvFilter, S> {
+        self.filter(self.config.export.clone())
+    }
+
+    pub(crate) fn spans<S: Subscriber>(&mut self) -> reload::Layer<EnvFilter, S> {
+        self.filter(self.config.spans.clone())
+    }
+
+    fn filter<S: Subscriber>(&mut self, configured: Option<String>) -> reload::Layer<EnvFilter, S> {
+        let directives = configured.as_deref().unwrap_or(&self.shared);
+        let (filter, handle) = reload::Layer::new(EnvFilter::builder().parse_lossy(directives));
+        self.reloadable.push(ReloadableFilter {
+            configured,
+            reload: Box::new(move |filter| handle.reload(filter)),
+        });
+        filter
+    }
+
+    pub(crate) fn into_handle(self) -> LogLevelHandle {
+        LogLevelHandle::new(self.shared, self.reloadable)
+    }
+}
+
+impl LogFilterConfig {
+    pub fn new(directives: impl Into<String>) -> Self {
+        Self::default().with_directives(directives)
+    }
+
+    pub fn with_directives(mut self, directives: impl Into<String>) -> Self {
+        self.directives = Some(directives.into());
+        self
+    }
+
+    pub fn with_env_var(mut self, env_var: impl Into<String>) -> Self {
+        self.env_var = env_var.into();
+        self
+    }
+
+    pub fn with_console(mut self, directives: impl Into<String>) -> Self {
+        self.console = Some(directives.into());
+        self
+    }
+
+    pub fn with_export(mut self, directives: impl Into<String>) -> Self {
+        self.export = Some(directives.into());
+        self
+    }
+
+    pub fn with_spans(mut self, directives: impl Into<String>) -> Self {
+        self.spans = Some(directives.into());
+        self
+    }
+
+    pub fn with_watch_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.watch_file = Some(path.into());
+        self
+    }
+
+    pub fn with_watch_interval(mut self, interval: Duration) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+
+    pub fn apply_defaults(&mut self) {
+        if self.env_var.is_empty() {
+            self.env_var = DEFAULT_ENV_VAR.to_string();
+        }
+        if self.watch_interval.is_zero() {
+            self.watch_interval = DEFAULT_WATCH_INTERVAL;
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), FilterError> {
+        let layers = [
+            ("filter", &self.directives),
+            ("console filter", &self.console),
+            ("export filter", &self.export),
+            ("span filter", &self.spans),
+        ];
+        for (layer, directives) in layers {
+            if let Some(directives) = directives {
+                parse(directives)
+                    .map_err(|source| FilterError::InvalidDirectives { layer, source })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The directives every layer without its own falls back to. An unset or
+    /// unparsable `env_var` is ignored, as `RUST_LOG` always has been.
+    pub(crate) fn shared_directives(
+        &self,
+        service_name: &str,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> String {
+        lookup(&self.env_var)
+            .filter(|value| !value.trim().is_empty() && parse(value).is_ok())
+            .or_else(|| self.directives.clone())
+            .unwrap_or_else(|| format!("info,{service_name}=debug,{DEFAULT_DIRECTIVES_SUFFIX}"))
+    }
+
+    pub(crate) fn build(&self, service_name: &str) -> LayerFilters<'_> {
+        LayerFilters {
+            config: self,
+            shared: self.shared_directives(service_name, &|var| std::env::var(var).ok()),
+            reloadable: Vec::new(),
+        }
+    }
+}
+
+fn parse(directives: &str) -> Result<EnvFilter, ParseError> {
+    EnvFilter::builder().parse(directives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_directives_precedence() {
+        let vars = |value: &'static str| {
+            move |var: &str| (var == "BILLING_LOG").then(|| value.to_string())
+        };
+        let config = LogFilterConfig::new("warn").with_env_var("BILLING_LOG");
+
+        assert_eq!(config.shared_directives("billing", &vars("debug")), "debug");
+        assert_eq!(
+            config.shared_directives("billing", &vars("=bogus=")),
+            "warn"
+        );
+        assert_eq!(
+            LogFilterConfig::default().shared_directives("billing", &vars("debug")),
+            "info,billing=debug,otel::tracing=trace,axum_tracing_opentelemetry=trace"
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_directives() {
+        assert!(LogFilterConfig::new("info,hyper=warn").validate().is_ok());
+
+        let err = LogFilterConfig::default()
+            .with_export("hyper=loud")
+            .validate()
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("export filter directives are invalid"),
+            "{err}"
+        );
+    }
+}
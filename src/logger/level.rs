@@ -0,0 +1,215 @@
+//! Changes the filters of the installed subscriber without a restart.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::ParseError;
+use tracing_subscriber::reload;
+
+#[derive(Debug, Error)]
+pub enum LogLevelError {
+    #[error("invalid log filter directives: {0}")]
+    InvalidDirectives(#[from] ParseError),
+    #[error("failed to reload log filter: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+/// Replaces a layer's filter; fails once the subscriber has been dropped.
+type Reload = Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>;
+
+pub(crate) struct ReloadableFilter {
+    /// The layer's own directives from `LogFilterConfig`, restored by `reset`.
+    pub(crate) configured: Option<String>,
+    pub(crate) reload: Reload,
+}
+
+/// Reads and replaces the filter directives of the subscriber `Telemetry`
+/// installed, e.g. to bump a service to `debug` during an incident.
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// The shared directives from configuration or the filter env var.
+    configured: String,
+    current: Mutex<Option<String>>,
+    filters: Vec<ReloadableFilter>,
+}
+
+impl fmt::Debug for LogLevelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogLevelHandle")
+            .field("directives", &self.directives())
+            .field("filters", &self.inner.filters.len())
+            .finish()
+    }
+}
+
+impl LogLevelHandle {
+    pub(crate) fn new(configured: String, filters: Vec<ReloadableFilter>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                configured,
+                current: Mutex::new(None),
+                filters,
+            }),
+        }
+    }
+
+    /// The directives last set, or the configured shared directives.
+    pub fn directives(&self) -> String {
+        self.lock()
+            .clone()
+            .unwrap_or_else(|| self.inner.configured.clone())
+    }
+
+    /// Applies `directives` to every layer, including those configured with
+    /// their own, until the next call or [`LogLevelHandle::reset`].
+    pub fn set_directives(&self, directives: &str) -> Result<(), LogLevelError> {
+        EnvFilter::builder().parse(directives)?;
+        let mut current = self.lock();
+        for filter in &self.inner.filters {
+            (filter.reload)(EnvFilter::builder().parse_lossy(directives))?;
+        }
+        *current = Some(directives.to_string());
+        Ok(())
+    }
+
+    /// Restores the filters the subscriber was installed with.
+    pub fn reset(&self) -> Result<(), LogLevelError> {
+        let mut current = self.lock();
+        for filter in &self.inner.filters {
+            let directives = filter
+                .configured
+                .as_deref()
+                .unwrap_or(&self.inner.configured);
+            (filter.reload)(EnvFilter::builder().parse_lossy(directives))?;
+        }
+        *current = None;
+        Ok(())
+    }
+
+    /// Polls `path` every `interval` and applies its directives whenever the
+    /// file changes; lines starting with `#` are ignored. Emptying or removing
+    /// the file resets the filters. The watcher stops once every handle is dropped.
+    pub fn watch_file(&self, path: impl Into<PathBuf>, interval: Duration) -> std::io::Result<()> {
+        let path = path.into();
+        let inner = Arc::downgrade(&self.inner);
+        thread::Builder::new()
+            .name("o11y-log-filter".to_string())
+            .spawn(move || watch(&inner, &path, interval))?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.inner.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn watch(inner: &Weak<Inner>, path: &Path, interval: Duration) {
+    let mut applied = None;
+    loop {
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        let handle = LogLevelHandle { inner };
+        let directives = read_directives(path);
+        if directives != applied {
+            let result = match &directives {
+                Some(directives) => handle.set_directives(directives),
+                None => handle.reset(),
+            };
+            match result {
+                Ok(()) => applied = directives,
+                Err(LogLevelError::Reload(_)) => return,
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), error = %err, "ignoring log filter file");
+                    applied = directives;
+                }
+            }
+        }
+        drop(handle);
+        thread::sleep(interval);
+    }
+}
+
+fn read_directives(path: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let directives: Vec<&str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    (!directives.is_empty()).then(|| directives.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A handle over one layer, and the directives each reload applied to it.
+    fn handle(configured: &str, own: Option<&str>) -> (LogLevelHandle, Arc<Mutex<Vec<String>>>) {
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        let record = applied.clone();
+        let handle = LogLevelHandle::new(
+            configured.to_string(),
+            vec![ReloadableFilter {
+                configured: own.map(str::to_string),
+                reload: Box::new(move |filter| {
+                    record.lock().unwrap().push(filter.to_string());
+                    Ok(())
+                }),
+            }],
+        );
+        (handle, applied)
+    }
+
+    #[test]
+    fn test_set_and_reset_directives() {
+        let (handle, applied) = handle("info", Some("warn"));
+        assert_eq!(handle.directives(), "info");
+
+        handle.set_directives("debug").unwrap();
+        assert_eq!(handle.directives(), "debug");
+
+        handle.reset().unwrap();
+        assert_eq!(handle.directives(), "info");
+        assert_eq!(*applied.lock().unwrap(), ["debug", "warn"]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_directives() {
+        let (handle, applied) = handle("info", None);
+
+        assert!(matches!(
+            handle.set_directives("info,hyper=chatty"),
+            Err(LogLevelError::InvalidDirectives(_))
+        ));
+        assert_eq!(handle.directives(), "info");
+        assert!(applied.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_directives() {
+        let path = std::env::temp_dir().join(format!(
+            "o11y-log-filter-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        assert_eq!(read_directives(&path), None);
+
+        std::fs::write(&path, "# incident 42\ninfo\n\nsqlx=debug\n").unwrap();
+        assert_eq!(read_directives(&path).as_deref(), Some("info,sqlx=debug"));
+
+        std::fs::write(&path, "# nothing\n").unwrap();
+        assert_eq!(read_directives(&path), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
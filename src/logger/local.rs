@@ -0,0 +1,181 @@
+//! Writes log records to stdout or a file.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use opentelemetry::logs::AnyValue;
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
+use opentelemetry_proto::transform::logs::tonic::group_logs_by_resource_and_scope;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::export::logs::{LogBatch, LogExporter};
+use opentelemetry_sdk::logs::{LogError, LogResult};
+use std::fmt::Write as _;
+use std::time::SystemTime;
+
+use crate::local::{LocalExporter, LocalFormat, LocalWriter, text};
+
+#[derive(Debug)]
+pub(crate) struct LocalLogExporter {
+    format: LocalFormat,
+    writer: LocalWriter,
+    resource: Resource,
+}
+
+impl LocalLogExporter {
+    pub(crate) fn new(config: &LocalExporter) -> Result<Self> {
+        Ok(Self {
+            format: config.format,
+            writer: LocalWriter::open(&config.output)
+                .context("failed to open the local log exporter output")?,
+            resource: Resource::empty(),
+        })
+    }
+
+    fn encode(&self, batch: LogBatch<'_>) -> LogResult<String> {
+        match self.format {
+            LocalFormat::Pretty => Ok(self.pretty(&batch)),
+            LocalFormat::OtlpJson => {
+                let resource = ResourceAttributesWithSchema::from(&self.resource);
+                let request = ExportLogsServiceRequest {
+                    resource_logs: group_logs_by_resource_and_scope(batch, &resource),
+                };
+                let mut json =
+                    serde_json::to_string(&request).map_err(|e| LogError::Other(Box::new(e)))?;
+                json.push('\n');
+                Ok(json)
+            }
+        }
+    }
+
+    fn pretty(&self, batch: &LogBatch<'_>) -> String {
+        let service = text::service_name(&self.resource);
+        let mut out = String::new();
+        for (record, scope) in batch.iter() {
+            let time = record
+                .timestamp
+                .or(record.observed_timestamp)
+                .unwrap_or_else(SystemTime::now);
+            let level = record
+                .severity_text
+                .or(record.severity_number.map(|severity| severity.name()))
+                .unwrap_or("-");
+            let target = record.target.as_deref().unwrap_or(scope.name());
+            let _ = write!(
+                out,
+                "{} {level:<5} [{service}] {target}:",
+                text::timestamp(time)
+            );
+            if let Some(body) = &record.body {
+                out.push(' ');
+                out.push_str(&any_value(body));
+            }
+            for (key, value) in record.attributes_iter() {
+                text::field(&mut out, key.as_str(), &any_value(value));
+            }
+            if let Some(context) = &record.trace_context {
+                text::field(&mut out, "trace_id", &context.trace_id.to_string());
+                text::field(&mut out, "span_id", &context.span_id.to_string());
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn any_value(value: &AnyValue) -> String {
+    match value {
+        AnyValue::Int(value) => value.to_string(),
+        AnyValue::Double(value) => value.to_string(),
+        AnyValue::String(value) => value.as_str().to_string(),
+        AnyValue::Boolean(value) => value.to_string(),
+        AnyValue::Bytes(bytes) => format!("{bytes:?}"),
+        AnyValue::ListAny(values) => {
+            let values: Vec<String> = values.iter().map(any_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+        AnyValue::Map(map) => {
+            let mut entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{key}: {}", any_value(value)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        other => format!("{other:?}"),
+    }
+}
+
+#[async_trait]
+impl LogExporter for LocalLogExporter {
+    async fn export(&mut self, batch: LogBatch<'_>) -> LogResult<()> {
+        let text = self.encode(batch)?;
+        self.writer
+            .write(&text)
+            .map_err(|e| LogError::Other(Box::new(e)))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::KeyValue;
+    use opentelemetry::logs::{LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+    use opentelemetry_sdk::logs::LoggerProvider;
+    use std::time::UNIX_EPOCH;
+
+    fn logs_in(format: LocalFormat) -> String {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("o11y-logs-{nanos}.log"));
+        let config = LocalExporter::file(&path).with_format(format);
+        let provider = LoggerProvider::builder()
+            .with_resource(Resource::new([KeyValue::new("service.name", "checkout")]))
+            .with_simple_exporter(LocalLogExporter::new(&config).unwrap())
+            .build();
+
+        let logger = provider.logger("test_logger");
+        let mut record = logger.create_log_record();
+        record.set_severity_number(Severity::Warn);
+        record.set_target("payments");
+        record.set_body(AnyValue::from("card declined"));
+        record.add_attribute("attempt", 2);
+        logger.emit(record);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        written
+    }
+
+    #[test]
+    fn test_pretty_logs() {
+        let written = logs_in(LocalFormat::Pretty);
+
+        assert!(
+            written.ends_with(" WARN  [checkout] payments: card declined attempt=2\n"),
+            "{written}"
+        );
+    }
+
+    #[test]
+    fn test_otlp_json_logs() {
+        let written = logs_in(LocalFormat::OtlpJson);
+        let request: serde_json::Value = serde_json::from_str(written.trim_end()).unwrap();
+
+        let record = &request["resourceLogs"][0]["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(record["body"]["stringValue"], "card declined");
+        assert_eq!(record["severityNumber"], 13);
+    }
+
+    #[test]
+    fn test_any_value() {
+        let list = AnyValue::ListAny(Box::new(vec![AnyValue::Int(1), AnyValue::Boolean(true)]));
+        assert_eq!(any_value(&list), "[1, true]");
+        assert_eq!(any_value(&AnyValue::Double(1.5)), "1.5");
+    }
+}
@@ -1,14 +1,35 @@
 mod config;
-
-pub use config::{LoggerConfig, LoggerError};
-
-use anyhow::Result;
+pub(crate) mod console;
+mod context;
+mod filter;
+mod level;
+#[cfg(feature = "local")]
+mod local;
+
+pub use config::{LoggerConfig, LoggerError, LoggerExporter};
+pub use console::{ConsoleFormat, ConsoleLogConfig, ConsoleTimestamp};
+pub(crate) use context::TraceContextBridge;
+pub(crate) use filter::LayerFilters;
+pub use filter::{FilterError, LogFilterConfig};
+pub use level::{LogLevelError, LogLevelHandle};
+
+use anyhow::{Context, Result};
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
 use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "grpc")]
+use opentelemetry_otlp::WithTonicConfig;
 use opentelemetry_sdk::{
     logs::{BatchLogProcessor, LoggerProvider as SdkLoggerProvider},
     resource::Resource,
+    runtime::Tokio,
 };
 
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
+use crate::otlp::Protocol;
+use crate::otlp::{self, Destination};
+
+const LOGS_PATH: &str = "/v1/logs";
+
 pub type LoggerProvider = SdkLoggerProvider;
 
 pub fn setup(config: &LoggerConfig, resource: &Resource) -> Result<Option<LoggerProvider>> {
@@ -16,30 +37,85 @@ pub fn setup(config: &LoggerConfig, resource: &Resource) -> Result<Option<Logger
         return Ok(None);
     }
 
-    let endpoint = config
-        .endpoint
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("logger endpoint is required when enabled"))?;
-
-    let normalized_endpoint = if endpoint.ends_with("/v1/logs") {
-        endpoint.clone()
-    } else {
-        format!("{}/v1/logs", endpoint.trim_end_matches('/'))
+    let mut builder = SdkLoggerProvider::builder().with_resource(resource.clone());
+
+    let primary = match &config.exporter {
+        LoggerExporter::Otlp => {
+            let endpoint = config
+                .endpoint
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("logger endpoint is required when enabled"))?;
+            Some(Destination::new(
+                endpoint,
+                config.protocol,
+                config.timeout,
+                &config.credentials,
+                (!config.exact_endpoint).then_some(LOGS_PATH),
+            ))
+        }
+        #[cfg(feature = "local")]
+        LoggerExporter::Local(local) => {
+            builder = builder.with_simple_exporter(local::LocalLogExporter::new(local)?);
+            None
+        }
+        #[cfg(not(feature = "local"))]
+        LoggerExporter::Local(_) => {
+            anyhow::bail!("logger local exporter requires the `local` cargo feature")
+        }
     };
+    let targets = config
+        .targets
+        .iter()
+        .map(|target| target.destination(config.protocol, config.timeout, LOGS_PATH));
 
-    let exporter_builder = opentelemetry_otlp::LogExporter::builder()
-        .with_http()
-        .with_endpoint(normalized_endpoint);
+    for destination in primary.into_iter().chain(targets) {
+        builder = builder.with_log_processor(otlp_processor(config, &destination)?);
+    }
+    let provider = builder.build();
 
-    let exporter = exporter_builder.build()?;
-    let processor = BatchLogProcessor::builder(exporter, opentelemetry_sdk::runtime::Tokio).build();
+    Ok(Some(provider))
+}
 
-    let provider = SdkLoggerProvider::builder()
-        .with_resource(resource.clone())
-        .with_log_processor(processor)
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
+fn otlp_processor(
+    config: &LoggerConfig,
+    destination: &Destination<'_>,
+) -> Result<BatchLogProcessor<Tokio>> {
+    let exporter = match destination.protocol {
+        #[cfg(feature = "grpc")]
+        Protocol::Grpc => opentelemetry_otlp::LogExporter::builder()
+            .with_tonic()
+            .with_endpoint(&destination.endpoint)
+            .with_timeout(destination.timeout)
+            .with_metadata(destination.credentials.metadata_map()?)
+            .build(),
+        #[cfg(any(feature = "http-proto", feature = "http-json"))]
+        Protocol::HttpProtobuf | Protocol::HttpJson => {
+            let exporter_builder = opentelemetry_otlp::LogExporter::builder()
+                .with_http()
+                .with_endpoint(&destination.endpoint)
+                .with_timeout(destination.timeout)
+                .with_protocol(destination.protocol.into());
+            otlp::with_http_headers(exporter_builder, destination.credentials).build()
+        }
+        #[allow(unreachable_patterns)]
+        protocol => return Err(otlp::unsupported(protocol)),
+    }
+    .with_context(|| format!("failed to build log exporter for {}", destination.endpoint))?;
+
+    let processor = BatchLogProcessor::builder(exporter, Tokio)
+        .with_batch_config(config.batch.log_config(destination.timeout))
         .build();
 
-    Ok(Some(provider))
+    Ok(processor)
+}
+
+#[cfg(not(any(feature = "grpc", feature = "http-proto", feature = "http-json")))]
+fn otlp_processor(
+    _: &LoggerConfig,
+    destination: &Destination<'_>,
+) -> Result<BatchLogProcessor<Tokio>> {
+    Err(otlp::unsupported(destination.protocol))
 }
 
 pub fn shutdown(provider: LoggerProvider) {
@@ -48,6 +124,25 @@ pub fn shutdown(provider: LoggerProvider) {
     }
 }
 
+/// Flushes buffered log records and shuts the provider down, reporting the first failure.
+pub fn try_shutdown(provider: LoggerProvider) -> Result<()> {
+    let flushed = try_flush(&provider);
+    provider
+        .shutdown()
+        .context("failed to shut down logger provider")?;
+    flushed
+}
+
+/// Flushes buffered log records without shutting the provider down.
+pub fn try_flush(provider: &LoggerProvider) -> Result<()> {
+    provider
+        .force_flush()
+        .into_iter()
+        .collect::<Result<Vec<()>, _>>()
+        .context("failed to flush logger provider")?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
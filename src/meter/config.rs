@@ -1,8 +1,16 @@
 use crate::auth::Credentials;
+use crate::local::{LocalError, LocalExporter};
+use crate::otlp::{ExporterTarget, Protocol, TargetError};
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
 use std::time::Duration;
 use thiserror::Error;
 
+use super::ViewConfig;
+
 const DEFAULT_EXPORT_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Error)]
 pub enum MeterError {
@@ -10,28 +18,157 @@ pub enum MeterError {
     ServiceNameRequired,
     #[error("meter endpoint is required when enabled")]
     EndpointRequired,
+    #[error("meter protocol {0} requires the `{feature}` cargo feature", feature = .0.feature())]
+    UnsupportedProtocol(Protocol),
+    #[error("meter target {index}: {source}")]
+    Target { index: usize, source: TargetError },
+    #[error("meter exporter prometheus requires the `prometheus` cargo feature")]
+    PrometheusUnsupported,
+    #[error("meter local exporter: {0}")]
+    Local(#[from] LocalError),
+    #[error("meter view {index} is invalid: {reason}")]
+    InvalidView { index: usize, reason: String },
+}
+
+/// Where metrics go. OTLP pushes to `MeterConfig::endpoint` every export
+/// interval and `Local` writes them to stdout or a file just as often;
+/// Prometheus serves them for scraping instead.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum MeterExporter {
+    #[default]
+    Otlp,
+    /// Serves `/metrics` on `bind_addr` in the Prometheus text and OpenMetrics
+    /// formats, always with cumulative temporality. Requires the `prometheus`
+    /// feature.
+    Prometheus {
+        bind_addr: SocketAddr,
+    },
+    Local(LocalExporter),
+}
+
+impl From<LocalExporter> for MeterExporter {
+    fn from(local: LocalExporter) -> Self {
+        MeterExporter::Local(local)
+    }
+}
+
+impl MeterExporter {
+    pub fn prometheus(bind_addr: impl Into<SocketAddr>) -> Self {
+        MeterExporter::Prometheus {
+            bind_addr: bind_addr.into(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown temporality {0:?}, expected cumulative, delta or lowmemory")]
+pub struct ParseTemporalityError(String);
+
+/// Temporality the OTLP exporter asks for. `Delta` and `LowMemory` still
+/// report up-down counters cumulatively, as the specification requires.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Temporality {
+    #[default]
+    Cumulative,
+    Delta,
+    /// Delta for synchronous counters and histograms, cumulative otherwise.
+    LowMemory,
+}
+
+impl Temporality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Temporality::Cumulative => "cumulative",
+            Temporality::Delta => "delta",
+            Temporality::LowMemory => "lowmemory",
+        }
+    }
+}
+
+impl FromStr for Temporality {
+    type Err = ParseTemporalityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cumulative" => Ok(Temporality::Cumulative),
+            "delta" => Ok(Temporality::Delta),
+            "lowmemory" => Ok(Temporality::LowMemory),
+            _ => Err(ParseTemporalityError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Temporality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<Temporality> for opentelemetry_sdk::metrics::Temporality {
+    fn from(temporality: Temporality) -> Self {
+        match temporality {
+            Temporality::Cumulative => opentelemetry_sdk::metrics::Temporality::Cumulative,
+            Temporality::Delta => opentelemetry_sdk::metrics::Temporality::Delta,
+            Temporality::LowMemory => opentelemetry_sdk::metrics::Temporality::LowMemory,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct MeterConfig {
     pub enabled: bool,
+    pub exporter: MeterExporter,
     pub endpoint: Option<String>,
+    /// Uses `endpoint` as the full URL instead of appending the signal path,
+    /// as `OTEL_EXPORTER_OTLP_METRICS_ENDPOINT` requires.
+    pub exact_endpoint: bool,
+    pub protocol: Protocol,
     pub service_name: String,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
     pub export_interval: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub export_timeout: Duration,
+    pub temporality: Temporality,
+    /// Applied in order; an instrument matched by several views gets a stream
+    /// from each.
+    pub views: Vec<ViewConfig>,
     pub runtime: RuntimeConfig,
+    pub system: SystemMetricsConfig,
     pub credentials: Credentials,
+    /// Additional [`ExporterTarget`]s.
+    pub targets: Vec<ExporterTarget>,
     pub use_global: bool,
 }
 
+impl Default for MeterConfig {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
 impl MeterConfig {
     pub fn new(service_name: impl Into<String>) -> Self {
         Self {
             enabled: true,
+            exporter: MeterExporter::default(),
             endpoint: None,
+            exact_endpoint: false,
+            protocol: Protocol::HttpProtobuf,
             service_name: service_name.into(),
             export_interval: DEFAULT_EXPORT_INTERVAL,
+            export_timeout: DEFAULT_EXPORT_TIMEOUT,
+            temporality: Temporality::default(),
+            views: Vec::new(),
             runtime: RuntimeConfig::default(),
+            system: SystemMetricsConfig::default(),
             credentials: Credentials::new(),
+            targets: Vec::new(),
             use_global: false,
         }
     }
@@ -41,35 +178,81 @@ impl MeterConfig {
         self
     }
 
+    pub fn with_exporter(mut self, exporter: impl Into<MeterExporter>) -> Self {
+        self.exporter = exporter.into();
+        self
+    }
+
     pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
         self.endpoint = Some(endpoint.into());
         self
     }
 
+    /// Sets a full signal URL that is dialled without appending a path.
+    pub fn with_signal_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self.exact_endpoint = true;
+        self
+    }
+
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
     pub fn with_export_interval(mut self, interval: Duration) -> Self {
         self.export_interval = interval;
         self
     }
 
+    pub fn with_export_timeout(mut self, timeout: Duration) -> Self {
+        self.export_timeout = timeout;
+        self
+    }
+
+    pub fn with_temporality(mut self, temporality: Temporality) -> Self {
+        self.temporality = temporality;
+        self
+    }
+
+    pub fn with_view(mut self, view: ViewConfig) -> Self {
+        self.views.push(view);
+        self
+    }
+
     pub fn with_runtime(mut self, runtime: RuntimeConfig) -> Self {
         self.runtime = runtime;
         self
     }
 
+    pub fn with_system_metrics(mut self, system: SystemMetricsConfig) -> Self {
+        self.system = system;
+        self
+    }
+
     pub fn with_credentials(mut self, credentials: Credentials) -> Self {
         self.credentials = credentials;
         self
     }
 
+    /// Adds an [`ExporterTarget`].
+    pub fn with_target(mut self, target: ExporterTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
     pub fn use_global(mut self, use_global: bool) -> Self {
         self.use_global = use_global;
         self
     }
 
     pub fn apply_defaults(&mut self) {
-        if self.export_interval.as_secs() == 0 {
+        if self.export_interval.is_zero() {
             self.export_interval = DEFAULT_EXPORT_INTERVAL;
         }
+        if self.export_timeout.is_zero() {
+            self.export_timeout = DEFAULT_EXPORT_TIMEOUT;
+        }
     }
 
     pub fn validate(&self) -> Result<(), MeterError> {
@@ -79,16 +262,74 @@ impl MeterConfig {
         if self.service_name.is_empty() {
             return Err(MeterError::ServiceNameRequired);
         }
-        if self.endpoint.is_none() {
-            return Err(MeterError::EndpointRequired);
+        match &self.exporter {
+            MeterExporter::Otlp => {
+                if self.endpoint.is_none() {
+                    return Err(MeterError::EndpointRequired);
+                }
+                if !self.protocol.is_supported() {
+                    return Err(MeterError::UnsupportedProtocol(self.protocol));
+                }
+            }
+            MeterExporter::Prometheus { .. } => {
+                if !cfg!(feature = "prometheus") {
+                    return Err(MeterError::PrometheusUnsupported);
+                }
+            }
+            MeterExporter::Local(local) => local.validate()?,
+        }
+        for (index, target) in self.targets.iter().enumerate() {
+            target
+                .validate(self.protocol)
+                .map_err(|source| MeterError::Target { index, source })?;
+        }
+        for (index, view) in self.views.iter().enumerate() {
+            if let Some(reason) = view.problem() {
+                return Err(MeterError::InvalidView { index, reason });
+            }
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Default)]
+/// Tokio runtime instruments, registered on the meter provider when `enabled`
+/// is set. Every instrument is on by default and can be switched off
+/// individually.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct RuntimeConfig {
     pub enabled: bool,
+    pub workers: bool,
+    pub alive_tasks: bool,
+    pub global_queue_depth: bool,
+    pub worker_busy_duration: bool,
+    pub worker_park_count: bool,
+    // The instruments below need the `tokio-unstable` feature and a build with
+    // `RUSTFLAGS="--cfg tokio_unstable"`; otherwise they are never registered.
+    pub blocking_threads: bool,
+    pub worker_steal_count: bool,
+    pub worker_poll_count: bool,
+    pub budget_forced_yields: bool,
+    pub io_driver_ready: bool,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            workers: true,
+            alive_tasks: true,
+            global_queue_depth: true,
+            worker_busy_duration: true,
+            worker_park_count: true,
+            blocking_threads: true,
+            worker_steal_count: true,
+            worker_poll_count: true,
+            budget_forced_yields: true,
+            io_driver_ready: true,
+        }
+    }
 }
 
 impl RuntimeConfig {
@@ -96,6 +337,152 @@ impl RuntimeConfig {
         self.enabled = enabled;
         self
     }
+
+    pub fn workers(mut self, enabled: bool) -> Self {
+        self.workers = enabled;
+        self
+    }
+
+    pub fn alive_tasks(mut self, enabled: bool) -> Self {
+        self.alive_tasks = enabled;
+        self
+    }
+
+    pub fn global_queue_depth(mut self, enabled: bool) -> Self {
+        self.global_queue_depth = enabled;
+        self
+    }
+
+    pub fn worker_busy_duration(mut self, enabled: bool) -> Self {
+        self.worker_busy_duration = enabled;
+        self
+    }
+
+    pub fn worker_park_count(mut self, enabled: bool) -> Self {
+        self.worker_park_count = enabled;
+        self
+    }
+
+    pub fn blocking_threads(mut self, enabled: bool) -> Self {
+        self.blocking_threads = enabled;
+        self
+    }
+
+    pub fn worker_steal_count(mut self, enabled: bool) -> Self {
+        self.worker_steal_count = enabled;
+        self
+    }
+
+    pub fn worker_poll_count(mut self, enabled: bool) -> Self {
+        self.worker_poll_count = enabled;
+        self
+    }
+
+    pub fn budget_forced_yields(mut self, enabled: bool) -> Self {
+        self.budget_forced_yields = enabled;
+        self
+    }
+
+    pub fn io_driver_ready(mut self, enabled: bool) -> Self {
+        self.io_driver_ready = enabled;
+        self
+    }
+}
+
+/// Process and host instruments read from `/proc`, following the `process.*`
+/// and `system.*` semantic conventions. Registered on the meter provider when
+/// `enabled` is set; every instrument is on by default. Nothing is observed on
+/// platforms without procfs.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SystemMetricsConfig {
+    pub enabled: bool,
+    pub process_cpu: bool,
+    pub process_memory: bool,
+    pub process_open_fds: bool,
+    pub process_threads: bool,
+    pub process_context_switches: bool,
+    pub host_cpu: bool,
+    pub host_memory: bool,
+    pub host_load: bool,
+    pub host_network: bool,
+    pub host_disk: bool,
+}
+
+impl Default for SystemMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            process_cpu: true,
+            process_memory: true,
+            process_open_fds: true,
+            process_threads: true,
+            process_context_switches: true,
+            host_cpu: true,
+            host_memory: true,
+            host_load: true,
+            host_network: true,
+            host_disk: true,
+        }
+    }
+}
+
+impl SystemMetricsConfig {
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn process_cpu(mut self, enabled: bool) -> Self {
+        self.process_cpu = enabled;
+        self
+    }
+
+    pub fn process_memory(mut self, enabled: bool) -> Self {
+        self.process_memory = enabled;
+        self
+    }
+
+    pub fn process_open_fds(mut self, enabled: bool) -> Self {
+        self.process_open_fds = enabled;
+        self
+    }
+
+    pub fn process_threads(mut self, enabled: bool) -> Self {
+        self.process_threads = enabled;
+        self
+    }
+
+    pub fn process_context_switches(mut self, enabled: bool) -> Self {
+        self.process_context_switches = enabled;
+        self
+    }
+
+    pub fn host_cpu(mut self, enabled: bool) -> Self {
+        self.host_cpu = enabled;
+        self
+    }
+
+    pub fn host_memory(mut self, enabled: bool) -> Self {
+        self.host_memory = enabled;
+        self
+    }
+
+    pub fn host_load(mut self, enabled: bool) -> Self {
+        self.host_load = enabled;
+        self
+    }
+
+    pub fn host_network(mut self, enabled: bool) -> Self {
+        self.host_network = enabled;
+        self
+    }
+
+    pub fn host_disk(mut self, enabled: bool) -> Self {
+        self.host_disk = enabled;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -121,10 +508,12 @@ mod tests {
     fn test_meter_config_applies_defaults() {
         let mut config = MeterConfig::new("test");
         config.export_interval = Duration::from_secs(0);
+        config.export_timeout = Duration::from_secs(0);
 
         config.apply_defaults();
 
         assert_eq!(config.export_interval, DEFAULT_EXPORT_INTERVAL);
+        assert_eq!(config.export_timeout, DEFAULT_EXPORT_TIMEOUT);
     }
 
     #[test]
@@ -138,6 +527,57 @@ mod tests {
         assert_eq!(config.export_interval, Duration::from_secs(30));
     }
 
+    #[test]
+    fn test_meter_config_rejects_invalid_view() {
+        let config = MeterConfig::new("test")
+            .with_endpoint("http://localhost:9009")
+            .with_view(ViewConfig::default().with_instrument_name("latency"))
+            .with_view(ViewConfig::default().with_rename("orphan"));
+
+        assert!(matches!(
+            config.validate(),
+            Err(MeterError::InvalidView { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_prometheus_exporter_needs_no_endpoint() {
+        let config = MeterConfig::new("test")
+            .with_exporter(MeterExporter::prometheus(([127, 0, 0, 1], 9464)));
+
+        if cfg!(feature = "prometheus") {
+            assert!(config.validate().is_ok());
+        } else {
+            assert!(matches!(
+                config.validate(),
+                Err(MeterError::PrometheusUnsupported)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_local_exporter_needs_no_endpoint() {
+        let config = MeterConfig::new("test").with_exporter(LocalExporter::stdout());
+
+        if cfg!(feature = "local") {
+            assert!(config.validate().is_ok());
+        } else {
+            assert!(matches!(
+                config.validate(),
+                Err(MeterError::Local(LocalError::Unsupported))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_temporality() {
+        assert_eq!(
+            "LowMemory".parse::<Temporality>().unwrap(),
+            Temporality::LowMemory
+        );
+        assert!("sometimes".parse::<Temporality>().is_err());
+    }
+
     #[test]
     fn test_runtime_config_builder() {
         let runtime = RuntimeConfig::default();
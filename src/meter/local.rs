@@ -0,0 +1,215 @@
+//! Writes collected metrics to stdout or a file every export interval.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_sdk::metrics::data::{
+    ExponentialHistogram, Gauge, Histogram, Metric, ResourceMetrics, Sum,
+};
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::metrics::{MetricError, MetricResult, Temporality};
+use std::fmt::Write as _;
+use std::time::SystemTime;
+
+use crate::local::{LocalExporter, LocalFormat, LocalWriter, text};
+
+pub(crate) struct LocalMetricExporter {
+    format: LocalFormat,
+    writer: LocalWriter,
+    temporality: Temporality,
+}
+
+impl LocalMetricExporter {
+    pub(crate) fn new(config: &LocalExporter, temporality: Temporality) -> Result<Self> {
+        Ok(Self {
+            format: config.format,
+            writer: LocalWriter::open(&config.output)
+                .context("failed to open the local metric exporter output")?,
+            temporality,
+        })
+    }
+
+    fn encode(&self, metrics: &ResourceMetrics) -> MetricResult<String> {
+        match self.format {
+            LocalFormat::Pretty => Ok(pretty(metrics)),
+            LocalFormat::OtlpJson => {
+                let request = ExportMetricsServiceRequest::from(metrics);
+                let mut json = serde_json::to_string(&request)
+                    .map_err(|e| MetricError::Other(e.to_string()))?;
+                json.push('\n');
+                Ok(json)
+            }
+        }
+    }
+}
+
+fn pretty(metrics: &ResourceMetrics) -> String {
+    let prefix = format!(
+        "{} METRIC [{}]",
+        text::timestamp(SystemTime::now()),
+        text::service_name(&metrics.resource)
+    );
+    let mut out = String::new();
+    for scope in &metrics.scope_metrics {
+        for metric in &scope.metrics {
+            write_points(&mut out, &prefix, metric);
+        }
+    }
+    out
+}
+
+/// One line per data point; the unit, when set, follows the value.
+fn write_points(out: &mut String, prefix: &str, metric: &Metric) {
+    let data = metric.data.as_any();
+    let name = &metric.name;
+    let unit = if metric.unit.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", metric.unit)
+    };
+
+    macro_rules! points {
+        ($($kind:ident<$ty:ty>),*) => {
+            $(if let Some(agg) = data.downcast_ref::<$kind<$ty>>() {
+                for point in &agg.data_points {
+                    let _ = write!(out, "{prefix} {name} {}{unit}", point.value);
+                    text::attributes(out, point.attributes.iter().map(|kv| (&kv.key, &kv.value)));
+                    out.push('\n');
+                }
+                return;
+            })*
+        };
+    }
+    points!(
+        Sum<u64>,
+        Sum<i64>,
+        Sum<f64>,
+        Gauge<u64>,
+        Gauge<i64>,
+        Gauge<f64>
+    );
+
+    macro_rules! histograms {
+        ($($ty:ty),*) => {
+            $(if let Some(histogram) = data.downcast_ref::<Histogram<$ty>>() {
+                for point in &histogram.data_points {
+                    let _ = write!(out, "{prefix} {name} count={} sum={}{unit}", point.count, point.sum);
+                    if let (Some(min), Some(max)) = (point.min, point.max) {
+                        let _ = write!(out, " min={min} max={max}");
+                    }
+                    let buckets: Vec<String> = point
+                        .bucket_counts
+                        .iter()
+                        .enumerate()
+                        .map(|(index, count)| match point.bounds.get(index) {
+                            Some(bound) => format!("{bound}:{count}"),
+                            None => format!("+Inf:{count}"),
+                        })
+                        .collect();
+                    text::field(out, "buckets", &buckets.join(" "));
+                    text::attributes(out, point.attributes.iter().map(|kv| (&kv.key, &kv.value)));
+                    out.push('\n');
+                }
+                return;
+            })*
+            $(if let Some(histogram) = data.downcast_ref::<ExponentialHistogram<$ty>>() {
+                for point in &histogram.data_points {
+                    let _ = write!(
+                        out,
+                        "{prefix} {name} count={} sum={}{unit} scale={} zero_count={}",
+                        point.count, point.sum, point.scale, point.zero_count
+                    );
+                    text::attributes(out, point.attributes.iter().map(|kv| (&kv.key, &kv.value)));
+                    out.push('\n');
+                }
+                return;
+            })*
+        };
+    }
+    histograms!(u64, i64, f64);
+}
+
+#[async_trait]
+impl PushMetricExporter for LocalMetricExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> MetricResult<()> {
+        let text = self.encode(metrics)?;
+        self.writer
+            .write(&text)
+            .map_err(|e| MetricError::Other(e.to_string()))
+    }
+
+    async fn force_flush(&self) -> MetricResult<()> {
+        Ok(())
+    }
+
+    fn shutdown(&self) -> MetricResult<()> {
+        Ok(())
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.temporality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meter::testing::TestReader;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::MeterProvider as _;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+
+    fn collect() -> ResourceMetrics {
+        let reader = TestReader::new();
+        let provider = SdkMeterProvider::builder()
+            .with_resource(Resource::new([KeyValue::new("service.name", "checkout")]))
+            .with_reader(reader.clone())
+            .build();
+        let meter = provider.meter("test_meter");
+        meter
+            .u64_counter("orders")
+            .build()
+            .add(2, &[KeyValue::new("region", "eu")]);
+        meter
+            .f64_histogram("latency")
+            .with_unit("s")
+            .with_boundaries(vec![0.1, 1.0])
+            .build()
+            .record(0.5, &[]);
+        reader.collect()
+    }
+
+    #[test]
+    fn test_pretty_metrics() {
+        let written = pretty(&collect());
+        let lines: Vec<&str> = written.lines().collect();
+
+        assert_eq!(lines.len(), 2, "{written}");
+        assert!(
+            lines[0].contains(" METRIC [checkout] orders 2 region=eu"),
+            "{written}"
+        );
+        assert!(
+            lines[1].ends_with(
+                " latency count=1 sum=0.5 s min=0.5 max=0.5 buckets=\"0.1:0 1:1 +Inf:0\""
+            ),
+            "{written}"
+        );
+    }
+
+    #[test]
+    fn test_otlp_json_metrics() {
+        let exporter = LocalMetricExporter {
+            format: LocalFormat::OtlpJson,
+            writer: LocalWriter::Stdout,
+            temporality: Temporality::Cumulative,
+        };
+        let written = exporter.encode(&collect()).unwrap();
+        let request: serde_json::Value = serde_json::from_str(written.trim_end()).unwrap();
+
+        let metrics = &request["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics[0]["name"], "orders");
+        assert_eq!(metrics[1]["name"], "latency");
+    }
+}
@@ -1,50 +1,151 @@
 mod config;
+#[cfg(feature = "local")]
+mod local;
+#[cfg(feature = "prometheus")]
+mod prometheus;
 mod runtime;
-
-pub use config::{MeterConfig, MeterError, RuntimeConfig};
-pub use runtime::register_runtime_metrics;
-
-use anyhow::Result;
-use opentelemetry::global;
+mod system;
+#[cfg(test)]
+mod testing;
+mod view;
+
+pub use config::{
+    MeterConfig, MeterError, MeterExporter, ParseTemporalityError, RuntimeConfig,
+    SystemMetricsConfig, Temporality,
+};
+pub use runtime::{RuntimeMetricsHandle, register_runtime_metrics};
+pub use system::register_system_metrics;
+pub use view::{InstrumentType, ViewAggregation, ViewConfig};
+
+use anyhow::{Context, Result};
+use opentelemetry::metrics::{Meter, MeterProvider as _};
+use opentelemetry::{InstrumentationScope, global};
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
 use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{metrics::SdkMeterProvider, resource::Resource};
+#[cfg(feature = "grpc")]
+use opentelemetry_otlp::WithTonicConfig;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::resource::Resource;
+
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
+use crate::otlp::Protocol;
+use crate::otlp::{self, Destination};
 
 pub type MeterProvider = SdkMeterProvider;
 
+const METRICS_PATH: &str = "/v1/metrics";
+
+/// The meter for instruments this crate registers itself, scoped to its name and version.
+fn scoped_meter(provider: &MeterProvider) -> Meter {
+    let scope = InstrumentationScope::builder(env!("CARGO_PKG_NAME"))
+        .with_version(env!("CARGO_PKG_VERSION"))
+        .build();
+    provider.meter_with_scope(scope)
+}
+
 pub fn setup(config: &MeterConfig, resource: &Resource) -> Result<Option<MeterProvider>> {
     if !config.enabled {
         return Ok(None);
     }
 
-    let endpoint = config
-        .endpoint
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("meter endpoint is required when enabled"))?;
-
-    let normalized_endpoint = if endpoint.ends_with("/v1/metrics") {
-        endpoint.clone()
-    } else {
-        format!("{}/v1/metrics", endpoint.trim_end_matches('/'))
+    let builder = SdkMeterProvider::builder().with_resource(resource.clone());
+    let mut builder = match &config.exporter {
+        MeterExporter::Otlp => {
+            let endpoint = config
+                .endpoint
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("meter endpoint is required when enabled"))?;
+            builder.with_reader(otlp_reader(
+                config,
+                &Destination::new(
+                    endpoint,
+                    config.protocol,
+                    config.export_timeout,
+                    &config.credentials,
+                    (!config.exact_endpoint).then_some(METRICS_PATH),
+                ),
+            )?)
+        }
+        #[cfg(feature = "prometheus")]
+        MeterExporter::Prometheus { bind_addr } => {
+            builder.with_reader(prometheus::PrometheusReader::serve(*bind_addr)?)
+        }
+        #[cfg(not(feature = "prometheus"))]
+        MeterExporter::Prometheus { .. } => {
+            anyhow::bail!("meter exporter prometheus requires the `prometheus` cargo feature")
+        }
+        #[cfg(feature = "local")]
+        MeterExporter::Local(local) => {
+            let exporter = local::LocalMetricExporter::new(local, config.temporality.into())?;
+            builder.with_reader(
+                PeriodicReader::builder(exporter, opentelemetry_sdk::runtime::Tokio)
+                    .with_interval(config.export_interval)
+                    .with_timeout(config.export_timeout)
+                    .build(),
+            )
+        }
+        #[cfg(not(feature = "local"))]
+        MeterExporter::Local(_) => {
+            anyhow::bail!("meter local exporter requires the `local` cargo feature")
+        }
     };
+    for target in &config.targets {
+        let destination = target.destination(config.protocol, config.export_timeout, METRICS_PATH);
+        builder = builder.with_reader(otlp_reader(config, &destination)?);
+    }
+    for (index, view) in config.views.iter().enumerate() {
+        let view = view
+            .to_view()
+            .with_context(|| format!("failed to build meter view {index}"))?;
+        builder = builder.with_view(view);
+    }
+    let provider = builder.build();
 
-    let exporter_builder = opentelemetry_otlp::MetricExporter::builder()
-        .with_http()
-        .with_endpoint(normalized_endpoint);
-
-    let exporter = exporter_builder.build()?;
-
-    let reader = opentelemetry_sdk::metrics::PeriodicReader::builder(
-        exporter,
-        opentelemetry_sdk::runtime::Tokio,
-    )
-    .build();
+    Ok(Some(provider))
+}
 
-    let provider = SdkMeterProvider::builder()
-        .with_resource(resource.clone())
-        .with_reader(reader)
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
+fn otlp_reader(config: &MeterConfig, destination: &Destination<'_>) -> Result<PeriodicReader> {
+    let exporter = match destination.protocol {
+        #[cfg(feature = "grpc")]
+        Protocol::Grpc => opentelemetry_otlp::MetricExporter::builder()
+            .with_temporality(config.temporality.into())
+            .with_tonic()
+            .with_endpoint(&destination.endpoint)
+            .with_timeout(destination.timeout)
+            .with_metadata(destination.credentials.metadata_map()?)
+            .build(),
+        #[cfg(any(feature = "http-proto", feature = "http-json"))]
+        Protocol::HttpProtobuf | Protocol::HttpJson => {
+            let exporter_builder = opentelemetry_otlp::MetricExporter::builder()
+                .with_temporality(config.temporality.into())
+                .with_http()
+                .with_endpoint(&destination.endpoint)
+                .with_timeout(destination.timeout)
+                .with_protocol(destination.protocol.into());
+            otlp::with_http_headers(exporter_builder, destination.credentials).build()
+        }
+        #[allow(unreachable_patterns)]
+        protocol => return Err(otlp::unsupported(protocol)),
+    }
+    .with_context(|| {
+        format!(
+            "failed to build metric exporter for {}",
+            destination.endpoint
+        )
+    })?;
+
+    let reader = PeriodicReader::builder(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_interval(config.export_interval)
+        .with_timeout(destination.timeout)
         .build();
 
-    Ok(Some(provider))
+    Ok(reader)
+}
+
+#[cfg(not(any(feature = "grpc", feature = "http-proto", feature = "http-json")))]
+fn otlp_reader(_: &MeterConfig, destination: &Destination<'_>) -> Result<PeriodicReader> {
+    Err(otlp::unsupported(destination.protocol))
 }
 
 pub fn init(config: &MeterConfig, resource: &Resource) -> Result<Option<MeterProvider>> {
@@ -65,6 +166,22 @@ pub fn shutdown(provider: MeterProvider) {
     }
 }
 
+/// Exports collected metrics and shuts the provider down, reporting the first failure.
+pub fn try_shutdown(provider: MeterProvider) -> Result<()> {
+    let flushed = try_flush(&provider);
+    provider
+        .shutdown()
+        .context("failed to shut down meter provider")?;
+    flushed
+}
+
+/// Exports collected metrics without shutting the provider down.
+pub fn try_flush(provider: &MeterProvider) -> Result<()> {
+    provider
+        .force_flush()
+        .context("failed to flush meter provider")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,607 @@
+//! Serves the meter provider's metrics for Prometheus to scrape.
+
+use anyhow::{Context, Result};
+use hyper::header::{ACCEPT, CONTENT_TYPE, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use opentelemetry::{KeyValue, Value};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::data::{Gauge, Histogram, Metric, ResourceMetrics, Sum};
+use opentelemetry_sdk::metrics::reader::MetricReader;
+use opentelemetry_sdk::metrics::{
+    InstrumentKind, ManualReader, MetricResult, Pipeline, Temporality,
+};
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tokio::sync::Notify;
+
+const METRICS_PATH: &str = "/metrics";
+const TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Text,
+    OpenMetrics,
+}
+
+impl Format {
+    /// OpenMetrics when the scraper asks for it, the classic text format otherwise.
+    fn negotiate(accept: Option<&HeaderValue>) -> Self {
+        match accept.and_then(|value| value.to_str().ok()) {
+            Some(accept) if accept.contains("application/openmetrics-text") => Format::OpenMetrics,
+            _ => Format::Text,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Format::Text => TEXT_CONTENT_TYPE,
+            Format::OpenMetrics => OPENMETRICS_CONTENT_TYPE,
+        }
+    }
+}
+
+/// A pull reader whose metrics are collected on every scrape of the server it
+/// starts. Shutting the provider down stops the server.
+#[derive(Clone, Debug)]
+pub(crate) struct PrometheusReader {
+    reader: Arc<ManualReader>,
+    stop: Arc<Notify>,
+}
+
+impl PrometheusReader {
+    /// Binds `addr` right away, so a taken port fails setup, then serves from
+    /// the current Tokio runtime.
+    pub(crate) fn serve(addr: SocketAddr) -> Result<Self> {
+        let runtime =
+            Handle::try_current().context("the prometheus exporter needs a Tokio runtime")?;
+        let listener = std::net::TcpListener::bind(addr)
+            .with_context(|| format!("failed to bind prometheus exporter to {addr}"))?;
+        listener.set_nonblocking(true)?;
+        let listener = {
+            let _entered = runtime.enter();
+            TcpListener::from_std(listener)?
+        };
+
+        let reader = Self {
+            reader: Arc::new(ManualReader::builder().build()),
+            stop: Arc::new(Notify::new()),
+        };
+        runtime.spawn(reader.clone().accept(listener));
+        Ok(reader)
+    }
+
+    async fn accept(self, listener: TcpListener) {
+        loop {
+            let stream = tokio::select! {
+                _ = self.stop.notified() => return,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(_) => continue,
+                },
+            };
+
+            let reader = self.clone();
+            tokio::spawn(async move {
+                let service = service_fn(|request| {
+                    let reader = reader.clone();
+                    async move { Ok::<_, Infallible>(reader.respond(&request)) }
+                });
+                // Scrapers that hang up mid-response are not worth reporting.
+                let _ = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    }
+
+    fn respond<B>(&self, request: &Request<B>) -> Response<String> {
+        if request.uri().path() != METRICS_PATH {
+            return plain(StatusCode::NOT_FOUND, "not found\n".to_string());
+        }
+        if request.method() != Method::GET {
+            return plain(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "method not allowed\n".to_string(),
+            );
+        }
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: Vec::new(),
+        };
+        if let Err(err) = self.reader.collect(&mut metrics) {
+            return plain(
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("failed to collect metrics: {err}\n"),
+            );
+        }
+
+        let format = Format::negotiate(request.headers().get(ACCEPT));
+        let mut response = Response::new(encode(&metrics, format));
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(format.content_type()),
+        );
+        response
+    }
+}
+
+impl MetricReader for PrometheusReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.reader.register_pipeline(pipeline)
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> MetricResult<()> {
+        self.reader.collect(rm)
+    }
+
+    fn force_flush(&self) -> MetricResult<()> {
+        self.reader.force_flush()
+    }
+
+    fn shutdown(&self) -> MetricResult<()> {
+        self.stop.notify_one();
+        self.reader.shutdown()
+    }
+
+    /// Prometheus only understands cumulative values.
+    fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+fn plain(status: StatusCode, body: String) -> Response<String> {
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    response
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FamilyType {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl FamilyType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FamilyType::Counter => "counter",
+            FamilyType::Gauge => "gauge",
+            FamilyType::Histogram => "histogram",
+        }
+    }
+}
+
+/// Samples sharing a name and type, possibly from several instrumentation scopes.
+struct Family {
+    name: String,
+    kind: FamilyType,
+    help: String,
+    samples: String,
+}
+
+/// Renders a collection in the requested format, leading with `target_info`
+/// for the resource. Exponential histograms have no Prometheus equivalent and
+/// are skipped, as are instruments whose name clashes with one of another type.
+fn encode(metrics: &ResourceMetrics, format: Format) -> String {
+    let mut families: Vec<Family> = Vec::new();
+
+    for scope in &metrics.scope_metrics {
+        let mut scope_labels = vec![KeyValue::new(
+            "otel_scope_name",
+            scope.scope.name().to_string(),
+        )];
+        if let Some(version) = scope.scope.version() {
+            scope_labels.push(KeyValue::new("otel_scope_version", version.to_string()));
+        }
+
+        for metric in &scope.metrics {
+            let Some(kind) = family_type(metric) else {
+                continue;
+            };
+            let name = family_name(&metric.name, &metric.unit, kind);
+            let family = match families.iter().position(|family| family.name == name) {
+                Some(index) if families[index].kind == kind => &mut families[index],
+                Some(_) => continue,
+                None => {
+                    families.push(Family {
+                        name,
+                        kind,
+                        help: metric.description.to_string(),
+                        samples: String::new(),
+                    });
+                    families.last_mut().expect("just pushed")
+                }
+            };
+            write_samples(family, metric, &scope_labels);
+        }
+    }
+
+    let mut out = String::new();
+    let target = labels(
+        metrics
+            .resource
+            .iter()
+            .map(|(key, value)| (key.as_str(), value)),
+        &[],
+    );
+    match format {
+        Format::Text => {
+            out.push_str("# HELP target_info Target metadata\n# TYPE target_info gauge\n");
+        }
+        Format::OpenMetrics => {
+            out.push_str("# TYPE target info\n# HELP target Target metadata\n");
+        }
+    }
+    let _ = writeln!(out, "target_info{target} 1");
+
+    for family in &families {
+        // OpenMetrics names counter families without the `_total` their samples carry.
+        let declared = match (format, family.kind) {
+            (Format::Text, FamilyType::Counter) => Cow::Owned(format!("{}_total", family.name)),
+            _ => Cow::Borrowed(family.name.as_str()),
+        };
+        let _ = writeln!(out, "# TYPE {declared} {}", family.kind.as_str());
+        if !family.help.is_empty() {
+            let _ = writeln!(out, "# HELP {declared} {}", escape_help(&family.help));
+        }
+        out.push_str(&family.samples);
+    }
+
+    if format == Format::OpenMetrics {
+        out.push_str("# EOF\n");
+    }
+    out
+}
+
+fn family_type(metric: &Metric) -> Option<FamilyType> {
+    let data = metric.data.as_any();
+    macro_rules! sum {
+        ($($ty:ty),*) => {
+            $(if let Some(sum) = data.downcast_ref::<Sum<$ty>>() {
+                return Some(if sum.is_monotonic { FamilyType::Counter } else { FamilyType::Gauge });
+            })*
+        };
+    }
+    sum!(u64, i64, f64);
+    if data.is::<Gauge<u64>>() || data.is::<Gauge<i64>>() || data.is::<Gauge<f64>>() {
+        return Some(FamilyType::Gauge);
+    }
+    if data.is::<Histogram<u64>>() || data.is::<Histogram<i64>>() || data.is::<Histogram<f64>>() {
+        return Some(FamilyType::Histogram);
+    }
+    None
+}
+
+fn write_samples(family: &mut Family, metric: &Metric, scope_labels: &[KeyValue]) {
+    let data = metric.data.as_any();
+    let name = &family.name;
+    let out = &mut family.samples;
+    let suffix = if family.kind == FamilyType::Counter {
+        "_total"
+    } else {
+        ""
+    };
+
+    macro_rules! points {
+        ($($kind:ident<$ty:ty>),*) => {
+            $(if let Some(agg) = data.downcast_ref::<$kind<$ty>>() {
+                for point in &agg.data_points {
+                    let labels = attribute_labels(&point.attributes, scope_labels, None);
+                    let _ = writeln!(out, "{name}{suffix}{labels} {}", number(point.value as f64));
+                }
+                return;
+            })*
+        };
+    }
+    points!(
+        Sum<u64>,
+        Sum<i64>,
+        Sum<f64>,
+        Gauge<u64>,
+        Gauge<i64>,
+        Gauge<f64>
+    );
+
+    macro_rules! histograms {
+        ($($ty:ty),*) => {
+            $(if let Some(histogram) = data.downcast_ref::<Histogram<$ty>>() {
+                for point in &histogram.data_points {
+                    let mut cumulative = 0;
+                    for (index, count) in point.bucket_counts.iter().enumerate() {
+                        cumulative += count;
+                        let le = point.bounds.get(index).map_or("+Inf".to_string(), |b| number(*b));
+                        let labels = attribute_labels(&point.attributes, scope_labels, Some(&le));
+                        let _ = writeln!(out, "{name}_bucket{labels} {cumulative}");
+                    }
+                    let labels = attribute_labels(&point.attributes, scope_labels, None);
+                    let _ = writeln!(out, "{name}_sum{labels} {}", number(point.sum as f64));
+                    let _ = writeln!(out, "{name}_count{labels} {}", point.count);
+                }
+                return;
+            })*
+        };
+    }
+    histograms!(u64, i64, f64);
+}
+
+fn attribute_labels(attributes: &[KeyValue], scope: &[KeyValue], le: Option<&str>) -> String {
+    let le = le.map(|le| Value::from(le.to_string()));
+    let extra: Vec<(&str, &Value)> = scope
+        .iter()
+        .map(|kv| (kv.key.as_str(), &kv.value))
+        .chain(le.as_ref().map(|le| ("le", le)))
+        .collect();
+    labels(
+        attributes.iter().map(|kv| (kv.key.as_str(), &kv.value)),
+        &extra,
+    )
+}
+
+/// `{key="value",...}`, or nothing when there are no labels.
+fn labels<'a>(
+    attributes: impl Iterator<Item = (&'a str, &'a Value)>,
+    extra: &[(&'a str, &'a Value)],
+) -> String {
+    let pairs: Vec<String> = attributes
+        .chain(extra.iter().copied())
+        .map(|(key, value)| {
+            format!(
+                "{}=\"{}\"",
+                sanitize(key, false),
+                escape_label(&value.as_str())
+            )
+        })
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// The Prometheus name for an instrument: sanitized, with the unit appended
+/// and any `_total` left for the samples to add.
+fn family_name(name: &str, unit: &str, kind: FamilyType) -> String {
+    let mut name = sanitize(name, true);
+    if kind == FamilyType::Counter
+        && let Some(stripped) = name.strip_suffix("_total")
+    {
+        name.truncate(stripped.len());
+    }
+    if let Some(unit) = unit_suffix(unit)
+        && !name.ends_with(unit)
+    {
+        name.push('_');
+        name.push_str(unit);
+    }
+    name
+}
+
+/// Prometheus spellings of the common UCUM units; annotations like
+/// `{request}` and unknown units are left off the name.
+fn unit_suffix(unit: &str) -> Option<&'static str> {
+    Some(match unit {
+        "1" => "ratio",
+        "%" => "percent",
+        "s" => "seconds",
+        "ms" => "milliseconds",
+        "us" => "microseconds",
+        "ns" => "nanoseconds",
+        "By" => "bytes",
+        "KiBy" => "kibibytes",
+        "MiBy" => "mebibytes",
+        "GiBy" => "gibibytes",
+        "Hz" => "hertz",
+        _ => return None,
+    })
+}
+
+/// Replaces characters Prometheus does not allow in metric (`:` allowed) or
+/// label names, and prefixes names that start with a digit.
+fn sanitize(name: &str, metric: bool) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || (metric && c == ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', r"\\").replace('\n', r"\n")
+}
+
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::metrics::MeterProvider as _;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+
+    fn free_addr() -> SocketAddr {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+    }
+
+    fn provider_on(addr: SocketAddr) -> (SdkMeterProvider, PrometheusReader) {
+        let reader = PrometheusReader::serve(addr).unwrap();
+        let provider = SdkMeterProvider::builder()
+            .with_resource(Resource::new([KeyValue::new("service.name", "billing")]))
+            .with_reader(reader.clone())
+            .build();
+        (provider, reader)
+    }
+
+    fn provider() -> (SdkMeterProvider, PrometheusReader) {
+        provider_on(free_addr())
+    }
+
+    fn scrape(reader: &PrometheusReader, format: Format) -> String {
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: Vec::new(),
+        };
+        reader.collect(&mut metrics).unwrap();
+        encode(&metrics, format)
+    }
+
+    #[tokio::test]
+    async fn test_text_format() {
+        let (provider, reader) = provider();
+        let meter = provider.meter("checkout");
+        meter
+            .u64_counter("http.requests")
+            .with_description("Handled requests")
+            .build()
+            .add(3, &[KeyValue::new("http.method", "GET")]);
+        meter
+            .f64_histogram("http.duration")
+            .with_unit("s")
+            .with_boundaries(vec![0.1, 1.0])
+            .build()
+            .record(0.5, &[]);
+
+        let body = scrape(&reader, Format::Text);
+
+        assert!(
+            body.contains("target_info{service_name=\"billing\""),
+            "{body}"
+        );
+        assert!(
+            body.contains("# TYPE http_requests_total counter\n"),
+            "{body}"
+        );
+        assert!(
+            body.contains("# HELP http_requests_total Handled requests\n"),
+            "{body}"
+        );
+        assert!(
+            body.contains(
+                "http_requests_total{http_method=\"GET\",otel_scope_name=\"checkout\"} 3\n"
+            ),
+            "{body}"
+        );
+        assert!(
+            body.contains("# TYPE http_duration_seconds histogram\n"),
+            "{body}"
+        );
+        assert!(
+            body.contains(
+                "http_duration_seconds_bucket{otel_scope_name=\"checkout\",le=\"0.1\"} 0\n"
+            ),
+            "{body}"
+        );
+        assert!(
+            body.contains(
+                "http_duration_seconds_bucket{otel_scope_name=\"checkout\",le=\"+Inf\"} 1\n"
+            ),
+            "{body}"
+        );
+        assert!(body.contains("http_duration_seconds_count{otel_scope_name=\"checkout\"} 1\n"));
+        assert!(!body.contains("# EOF"));
+    }
+
+    #[tokio::test]
+    async fn test_openmetrics_format() {
+        let (provider, reader) = provider();
+        provider
+            .meter("checkout")
+            .u64_counter("jobs_total")
+            .build()
+            .add(1, &[]);
+        provider
+            .meter("checkout")
+            .i64_up_down_counter("queue.depth")
+            .build()
+            .add(-2, &[]);
+
+        let body = scrape(&reader, Format::OpenMetrics);
+
+        assert!(body.starts_with("# TYPE target info\n"), "{body}");
+        assert!(body.contains("# TYPE jobs counter\njobs_total{"), "{body}");
+        assert!(
+            body.contains("# TYPE queue_depth gauge\nqueue_depth{"),
+            "{body}"
+        );
+        assert!(body.contains("} -2\n"), "{body}");
+        assert!(body.ends_with("# EOF\n"), "{body}");
+    }
+
+    #[test]
+    fn test_names_and_escaping() {
+        assert_eq!(
+            family_name("http.server.duration", "ms", FamilyType::Histogram),
+            "http_server_duration_milliseconds"
+        );
+        assert_eq!(
+            family_name("memory_bytes", "By", FamilyType::Gauge),
+            "memory_bytes"
+        );
+        assert_eq!(
+            family_name("requests", "{request}", FamilyType::Counter),
+            "requests"
+        );
+        assert_eq!(sanitize("1st:metric", true), "_1st:metric");
+        assert_eq!(sanitize("a:b", false), "a_b");
+        assert_eq!(escape_label("say \"hi\"\n\\"), r#"say \"hi\"\n\\"#);
+        assert_eq!(number(f64::INFINITY), "+Inf");
+        assert_eq!(number(2.0), "2");
+        assert_eq!(number(0.25), "0.25");
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_serving() {
+        let addr = free_addr();
+        let (provider, _reader) = provider_on(addr);
+        provider.shutdown().unwrap();
+
+        // The accept loop exits on its next poll; retry until connections are refused.
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(addr).await.is_err() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("prometheus exporter still accepting connections after shutdown");
+    }
+}
@@ -1,30 +1,367 @@
-use opentelemetry::global;
-use tokio::runtime::Handle;
-
-pub fn register_runtime_metrics(meter_name: String) -> Result<(), Box<dyn std::error::Error>> {
-    let meter = global::meter(Box::leak(meter_name.into_boxed_str()) as &'static str);
-
-    let _num_workers = meter
-        .u64_observable_gauge("tokio.runtime.workers")
-        .with_description("Number of worker threads in the runtime")
-        .with_callback(move |observer| {
-            if let Ok(handle) = Handle::try_current() {
-                let metrics = handle.metrics();
-                observer.observe(metrics.num_workers() as u64, &[]);
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Meter;
+use std::sync::{Arc, Weak};
+use tokio::runtime::{Handle, RuntimeMetrics};
+
+use super::{MeterProvider, RuntimeConfig};
+
+const WORKER_KEY: &str = "tokio.worker";
+
+/// Registers observable instruments for the Tokio runtime on `provider`,
+/// skipping the ones disabled in `config`.
+///
+/// The runtime is captured when this is called from within one; otherwise each
+/// collection observes the runtime it runs on, if any. Registrations on
+/// different providers are independent. Registering on the same provider again
+/// never double counts: the SDK merges the repeated instruments, and each
+/// observation replaces the previous one for its attributes.
+pub fn register_runtime_metrics(
+    provider: &MeterProvider,
+    config: &RuntimeConfig,
+) -> RuntimeMetricsHandle {
+    register(
+        &super::scoped_meter(provider),
+        config,
+        Handle::try_current().ok(),
+    )
+}
+
+/// Keeps the runtime instruments from [`register_runtime_metrics`] observing.
+///
+/// Dropping the handle or calling [`RuntimeMetricsHandle::unregister`]
+/// releases the runtime. The SDK cannot remove callbacks from a provider, so
+/// the instruments stay registered but report nothing.
+#[must_use = "runtime metrics stop being observed when the handle is dropped"]
+#[derive(Debug)]
+pub struct RuntimeMetricsHandle {
+    /// Only held; the callbacks stop observing once it can no longer be upgraded.
+    _registration: Arc<Registration>,
+}
+
+impl RuntimeMetricsHandle {
+    pub fn unregister(self) {}
+}
+
+#[derive(Debug)]
+struct Registration {
+    handle: Option<Handle>,
+}
+
+fn register(meter: &Meter, config: &RuntimeConfig, handle: Option<Handle>) -> RuntimeMetricsHandle {
+    let registration = Arc::new(Registration { handle });
+    let observer = RuntimeObserver(Arc::downgrade(&registration));
+
+    if config.workers {
+        observer.gauge(
+            meter,
+            "tokio.runtime.workers",
+            "Number of worker threads in the runtime",
+            |metrics| metrics.num_workers() as u64,
+        );
+    }
+    if config.alive_tasks {
+        observer.gauge(
+            meter,
+            "tokio.runtime.alive_tasks",
+            "Number of tasks that have been spawned and not yet completed",
+            |metrics| metrics.num_alive_tasks() as u64,
+        );
+    }
+    if config.global_queue_depth {
+        observer.gauge(
+            meter,
+            "tokio.runtime.global_queue_depth",
+            "Number of tasks waiting in the runtime's global queue",
+            |metrics| metrics.global_queue_depth() as u64,
+        );
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    if config.worker_busy_duration {
+        let observer = observer.clone();
+        meter
+            .f64_observable_counter("tokio.runtime.worker.busy_duration")
+            .with_description("Time each worker has spent executing tasks")
+            .with_unit("s")
+            .with_callback(move |instrument| {
+                observer.each_worker(|metrics, worker, attrs| {
+                    let busy = metrics.worker_total_busy_duration(worker);
+                    instrument.observe(busy.as_secs_f64(), attrs);
+                });
+            })
+            .build();
+    }
+    #[cfg(target_has_atomic = "64")]
+    if config.worker_park_count {
+        observer.worker_counter(
+            meter,
+            "tokio.runtime.worker.park_count",
+            "Number of times each worker has parked",
+            |metrics, worker| metrics.worker_park_count(worker),
+        );
+    }
+
+    #[cfg(all(tokio_unstable, feature = "tokio-unstable"))]
+    register_unstable(meter, config, &observer);
+
+    RuntimeMetricsHandle {
+        _registration: registration,
+    }
+}
+
+#[cfg(all(tokio_unstable, feature = "tokio-unstable"))]
+fn register_unstable(meter: &Meter, config: &RuntimeConfig, observer: &RuntimeObserver) {
+    if config.blocking_threads {
+        observer.gauge(
+            meter,
+            "tokio.runtime.blocking_threads",
+            "Number of threads in the blocking pool",
+            |metrics| metrics.num_blocking_threads() as u64,
+        );
+        observer.gauge(
+            meter,
+            "tokio.runtime.idle_blocking_threads",
+            "Number of idle threads in the blocking pool",
+            |metrics| metrics.num_idle_blocking_threads() as u64,
+        );
+    }
+    if config.worker_steal_count {
+        observer.worker_counter(
+            meter,
+            "tokio.runtime.worker.steal_count",
+            "Number of tasks each worker has stolen from other workers",
+            |metrics, worker| metrics.worker_steal_count(worker),
+        );
+    }
+    if config.worker_poll_count {
+        observer.worker_counter(
+            meter,
+            "tokio.runtime.worker.poll_count",
+            "Number of tasks each worker has polled",
+            |metrics, worker| metrics.worker_poll_count(worker),
+        );
+    }
+    if config.budget_forced_yields {
+        observer.counter(
+            meter,
+            "tokio.runtime.budget_forced_yields",
+            "Number of times tasks were forced to yield after exhausting their budget",
+            |metrics| metrics.budget_forced_yield_count(),
+        );
+    }
+    if config.io_driver_ready {
+        observer.counter(
+            meter,
+            "tokio.runtime.io_driver.ready_count",
+            "Number of ready events processed by the IO driver",
+            |metrics| metrics.io_driver_ready_count(),
+        );
+    }
+}
+
+/// Callbacks hold the registration weakly so an unregistered one keeps nothing alive.
+#[derive(Clone)]
+struct RuntimeObserver(Weak<Registration>);
+
+impl RuntimeObserver {
+    fn metrics(&self) -> Option<RuntimeMetrics> {
+        let registration = self.0.upgrade()?;
+        match &registration.handle {
+            Some(handle) => Some(handle.metrics()),
+            None => Handle::try_current().ok().map(|handle| handle.metrics()),
+        }
+    }
+
+    fn each_worker(&self, mut observe: impl FnMut(&RuntimeMetrics, usize, &[KeyValue])) {
+        if let Some(metrics) = self.metrics() {
+            for worker in 0..metrics.num_workers() {
+                observe(
+                    &metrics,
+                    worker,
+                    &[KeyValue::new(WORKER_KEY, worker as i64)],
+                );
             }
-        })
-        .build();
+        }
+    }
+
+    fn gauge(
+        &self,
+        meter: &Meter,
+        name: &'static str,
+        description: &'static str,
+        value: impl Fn(&RuntimeMetrics) -> u64 + Send + Sync + 'static,
+    ) {
+        let observer = self.clone();
+        meter
+            .u64_observable_gauge(name)
+            .with_description(description)
+            .with_callback(move |instrument| {
+                if let Some(metrics) = observer.metrics() {
+                    instrument.observe(value(&metrics), &[]);
+                }
+            })
+            .build();
+    }
 
-    Ok(())
+    #[cfg(all(tokio_unstable, feature = "tokio-unstable"))]
+    fn counter(
+        &self,
+        meter: &Meter,
+        name: &'static str,
+        description: &'static str,
+        value: impl Fn(&RuntimeMetrics) -> u64 + Send + Sync + 'static,
+    ) {
+        let observer = self.clone();
+        meter
+            .u64_observable_counter(name)
+            .with_description(description)
+            .with_callback(move |instrument| {
+                if let Some(metrics) = observer.metrics() {
+                    instrument.observe(value(&metrics), &[]);
+                }
+            })
+            .build();
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    fn worker_counter(
+        &self,
+        meter: &Meter,
+        name: &'static str,
+        description: &'static str,
+        value: impl Fn(&RuntimeMetrics, usize) -> u64 + Send + Sync + 'static,
+    ) {
+        let observer = self.clone();
+        meter
+            .u64_observable_counter(name)
+            .with_description(description)
+            .with_callback(move |instrument| {
+                observer.each_worker(|metrics, worker, attrs| {
+                    instrument.observe(value(metrics, worker), attrs);
+                });
+            })
+            .build();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::meter::testing::TestReader;
+    use opentelemetry::metrics::MeterProvider as _;
+
+    fn collect_names(config: &RuntimeConfig) -> Vec<String> {
+        let (provider, reader) = TestReader::provider();
+        let _handle = register(
+            &provider.meter("test_meter"),
+            config,
+            Handle::try_current().ok(),
+        );
+        reader.metric_names()
+    }
+
+    #[tokio::test]
+    async fn test_unregister_stops_observing() {
+        let (provider, reader) = TestReader::provider();
+        let handle = register(
+            &provider.meter("test_meter"),
+            &RuntimeConfig::default(),
+            Handle::try_current().ok(),
+        );
+        let registration = Arc::downgrade(&handle._registration);
+
+        handle.unregister();
+
+        assert!(registration.upgrade().is_none(), "runtime handle leaked");
+        assert!(reader.metric_names().is_empty());
+    }
 
     #[tokio::test]
-    async fn test_register_runtime_metrics() {
-        let result = register_runtime_metrics("test_meter".to_string());
-        assert!(result.is_ok());
+    async fn test_registering_same_provider_again_does_not_double_count() {
+        let (provider, reader) = TestReader::provider();
+        let first = register_runtime_metrics(&provider, &RuntimeConfig::default());
+        let workers = reader.data_points("tokio.runtime.workers");
+        assert_eq!(workers.len(), 1);
+
+        let _second = register_runtime_metrics(&provider, &RuntimeConfig::default());
+        assert_eq!(reader.data_points("tokio.runtime.workers"), workers);
+
+        first.unregister();
+        assert_eq!(reader.data_points("tokio.runtime.workers"), workers);
+    }
+
+    #[tokio::test]
+    async fn test_separate_providers_keep_their_registrations() {
+        let (first_provider, first_reader) = TestReader::provider();
+        let (second_provider, second_reader) = TestReader::provider();
+        let _first = register_runtime_metrics(&first_provider, &RuntimeConfig::default());
+        let _second = register_runtime_metrics(&second_provider, &RuntimeConfig::default());
+
+        assert_eq!(first_reader.data_points("tokio.runtime.workers").len(), 1);
+        assert_eq!(second_reader.data_points("tokio.runtime.workers").len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_stable_runtime_metrics_are_observed() {
+        let names = collect_names(&RuntimeConfig::default().enabled(true));
+
+        for name in [
+            "tokio.runtime.workers",
+            "tokio.runtime.alive_tasks",
+            "tokio.runtime.global_queue_depth",
+        ] {
+            assert!(
+                names.iter().any(|n| n == name),
+                "missing {name} in {names:?}"
+            );
+        }
+        #[cfg(target_has_atomic = "64")]
+        for name in [
+            "tokio.runtime.worker.busy_duration",
+            "tokio.runtime.worker.park_count",
+        ] {
+            assert!(
+                names.iter().any(|n| n == name),
+                "missing {name} in {names:?}"
+            );
+        }
+    }
+
+    #[cfg(all(tokio_unstable, feature = "tokio-unstable"))]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_unstable_runtime_metrics_are_observed() {
+        let names = collect_names(&RuntimeConfig::default().enabled(true));
+
+        for name in [
+            "tokio.runtime.blocking_threads",
+            "tokio.runtime.idle_blocking_threads",
+            "tokio.runtime.worker.steal_count",
+            "tokio.runtime.worker.poll_count",
+            "tokio.runtime.budget_forced_yields",
+            "tokio.runtime.io_driver.ready_count",
+        ] {
+            assert!(
+                names.iter().any(|n| n == name),
+                "missing {name} in {names:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_runtime_metrics_are_skipped() {
+        let config = RuntimeConfig::default()
+            .alive_tasks(false)
+            .global_queue_depth(false)
+            .worker_busy_duration(false)
+            .worker_park_count(false)
+            .blocking_threads(false)
+            .worker_steal_count(false)
+            .worker_poll_count(false)
+            .budget_forced_yields(false)
+            .io_driver_ready(false);
+
+        let names = collect_names(&config);
+
+        assert_eq!(names, vec!["tokio.runtime.workers".to_string()]);
     }
 }
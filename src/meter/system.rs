@@ -0,0 +1,551 @@
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Meter;
+use opentelemetry_semantic_conventions::{attribute, metric};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{MeterProvider, SystemMetricsConfig};
+
+const PROC_DIR: &str = "/proc";
+/// procfs reports CPU times in `USER_HZ`, which Linux fixes at 100 on every
+/// mainstream architecture.
+const USER_HZ: f64 = 100.0;
+/// `/proc/diskstats` counts 512-byte sectors regardless of the device.
+const SECTOR_SIZE: u64 = 512;
+// Not yet in the semantic conventions; named as in the collector's hostmetrics receiver.
+const SYSTEM_CPU_LOAD_AVERAGE: [&str; 3] = [
+    "system.cpu.load_average.1m",
+    "system.cpu.load_average.5m",
+    "system.cpu.load_average.15m",
+];
+
+/// Registers process and host instruments on `provider`, skipping the ones
+/// disabled in `config`.
+pub fn register_system_metrics(provider: &MeterProvider, config: &SystemMetricsConfig) {
+    register(
+        &super::scoped_meter(provider),
+        config,
+        Procfs::new(PROC_DIR),
+    );
+}
+
+fn register(meter: &Meter, config: &SystemMetricsConfig, procfs: Procfs) {
+    if config.process_cpu {
+        let procfs = procfs.clone();
+        meter
+            .f64_observable_counter(metric::PROCESS_CPU_TIME)
+            .with_description("Total CPU seconds broken down by mode")
+            .with_unit("s")
+            .with_callback(move |instrument| {
+                let Some((user, system)) = procfs.read("self/stat").and_then(process_cpu_times)
+                else {
+                    return;
+                };
+                instrument.observe(user, &[KeyValue::new(attribute::CPU_MODE, "user")]);
+                instrument.observe(system, &[KeyValue::new(attribute::CPU_MODE, "system")]);
+            })
+            .build();
+    }
+    if config.process_memory {
+        for (name, key, description) in [
+            (
+                metric::PROCESS_MEMORY_USAGE,
+                "VmRSS",
+                "The amount of physical memory in use",
+            ),
+            (
+                metric::PROCESS_MEMORY_VIRTUAL,
+                "VmSize",
+                "The amount of committed virtual memory",
+            ),
+        ] {
+            let procfs = procfs.clone();
+            meter
+                .i64_observable_up_down_counter(name)
+                .with_description(description)
+                .with_unit("By")
+                .with_callback(move |instrument| {
+                    if let Some(bytes) = procfs.status_value(key) {
+                        instrument.observe(bytes as i64, &[]);
+                    }
+                })
+                .build();
+        }
+    }
+    if config.process_open_fds {
+        let procfs = procfs.clone();
+        meter
+            .i64_observable_up_down_counter(metric::PROCESS_OPEN_FILE_DESCRIPTOR_COUNT)
+            .with_description("Number of file descriptors in use by the process")
+            .with_unit("{count}")
+            .with_callback(move |instrument| {
+                if let Some(count) = procfs.count_entries("self/fd") {
+                    instrument.observe(count as i64, &[]);
+                }
+            })
+            .build();
+    }
+    if config.process_threads {
+        let procfs = procfs.clone();
+        meter
+            .i64_observable_up_down_counter(metric::PROCESS_THREAD_COUNT)
+            .with_description("Process threads count")
+            .with_unit("{thread}")
+            .with_callback(move |instrument| {
+                if let Some(threads) = procfs.status_value("Threads") {
+                    instrument.observe(threads as i64, &[]);
+                }
+            })
+            .build();
+    }
+    if config.process_context_switches {
+        let procfs = procfs.clone();
+        meter
+            .u64_observable_counter(metric::PROCESS_CONTEXT_SWITCHES)
+            .with_description("Number of times the process has been context switched")
+            .with_unit("{count}")
+            .with_callback(move |instrument| {
+                for (key, kind) in [
+                    ("voluntary_ctxt_switches", "voluntary"),
+                    ("nonvoluntary_ctxt_switches", "involuntary"),
+                ] {
+                    if let Some(count) = procfs.status_value(key) {
+                        instrument.observe(
+                            count,
+                            &[KeyValue::new(attribute::PROCESS_CONTEXT_SWITCH_TYPE, kind)],
+                        );
+                    }
+                }
+            })
+            .build();
+    }
+
+    if config.host_cpu {
+        let procfs = procfs.clone();
+        meter
+            .f64_observable_counter(metric::SYSTEM_CPU_TIME)
+            .with_description("Seconds each logical CPU spent on each mode")
+            .with_unit("s")
+            .with_callback(move |instrument| {
+                let Some(stat) = procfs.read("stat") else {
+                    return;
+                };
+                for (cpu, times) in host_cpu_times(&stat) {
+                    for (mode, seconds) in times {
+                        instrument.observe(
+                            seconds,
+                            &[
+                                KeyValue::new(attribute::CPU_MODE, mode),
+                                KeyValue::new(attribute::SYSTEM_CPU_LOGICAL_NUMBER, cpu),
+                            ],
+                        );
+                    }
+                }
+            })
+            .build();
+    }
+    if config.host_memory {
+        let usage = procfs.clone();
+        meter
+            .i64_observable_up_down_counter(metric::SYSTEM_MEMORY_USAGE)
+            .with_description("Reports memory in use by state")
+            .with_unit("By")
+            .with_callback(move |instrument| {
+                let Some(meminfo) = usage.read("meminfo") else {
+                    return;
+                };
+                for (state, bytes) in memory_usage(&parse_meminfo(&meminfo)) {
+                    instrument.observe(
+                        bytes as i64,
+                        &[KeyValue::new(attribute::SYSTEM_MEMORY_STATE, state)],
+                    );
+                }
+            })
+            .build();
+
+        let available = procfs.clone();
+        meter
+            .i64_observable_up_down_counter(metric::SYSTEM_LINUX_MEMORY_AVAILABLE)
+            .with_description(
+                "An estimate of how much memory is available for starting new applications",
+            )
+            .with_unit("By")
+            .with_callback(move |instrument| {
+                let Some(meminfo) = available.read("meminfo") else {
+                    return;
+                };
+                if let Some(&bytes) = parse_meminfo(&meminfo).get("MemAvailable") {
+                    instrument.observe(bytes as i64, &[]);
+                }
+            })
+            .build();
+    }
+    if config.host_load {
+        for (index, name) in SYSTEM_CPU_LOAD_AVERAGE.into_iter().enumerate() {
+            let procfs = procfs.clone();
+            meter
+                .f64_observable_gauge(name)
+                .with_description("Average number of runnable and uninterruptible threads")
+                .with_unit("{thread}")
+                .with_callback(move |instrument| {
+                    if let Some(load) = procfs.read("loadavg").and_then(|l| load_average(&l)) {
+                        instrument.observe(load[index], &[]);
+                    }
+                })
+                .build();
+        }
+    }
+    if config.host_network {
+        let procfs = procfs.clone();
+        meter
+            .u64_observable_counter(metric::SYSTEM_NETWORK_IO)
+            .with_description("Bytes received and transmitted by each network interface")
+            .with_unit("By")
+            .with_callback(move |instrument| {
+                let Some(dev) = procfs.read("net/dev") else {
+                    return;
+                };
+                for (device, received, transmitted) in network_io(&dev) {
+                    for (direction, bytes) in [("receive", received), ("transmit", transmitted)] {
+                        instrument.observe(
+                            bytes,
+                            &[
+                                KeyValue::new(attribute::NETWORK_IO_DIRECTION, direction),
+                                KeyValue::new(attribute::SYSTEM_DEVICE, device.clone()),
+                            ],
+                        );
+                    }
+                }
+            })
+            .build();
+    }
+    if config.host_disk {
+        meter
+            .u64_observable_counter(metric::SYSTEM_DISK_IO)
+            .with_description("Bytes read from and written to each block device")
+            .with_unit("By")
+            .with_callback(move |instrument| {
+                let Some(diskstats) = procfs.read("diskstats") else {
+                    return;
+                };
+                for (device, read, written) in disk_io(&diskstats) {
+                    for (direction, bytes) in [("read", read), ("write", written)] {
+                        instrument.observe(
+                            bytes,
+                            &[
+                                KeyValue::new(attribute::DISK_IO_DIRECTION, direction),
+                                KeyValue::new(attribute::SYSTEM_DEVICE, device.clone()),
+                            ],
+                        );
+                    }
+                }
+            })
+            .build();
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Procfs {
+    root: PathBuf,
+}
+
+impl Procfs {
+    fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(path)).ok()
+    }
+
+    fn count_entries(&self, path: &str) -> Option<usize> {
+        fs::read_dir(self.root.join(path))
+            .ok()
+            .map(|entries| entries.count())
+    }
+
+    fn status_value(&self, key: &str) -> Option<u64> {
+        status_value(&self.read("self/status")?, key)
+    }
+}
+
+/// User and system CPU seconds from `/proc/self/stat`.
+fn process_cpu_times(stat: String) -> Option<(f64, f64)> {
+    // The command name may contain spaces, so count fields after its closing parenthesis.
+    let (_, rest) = stat.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(11);
+    let user: u64 = fields.next()?.parse().ok()?;
+    let system: u64 = fields.next()?.parse().ok()?;
+    Some((user as f64 / USER_HZ, system as f64 / USER_HZ))
+}
+
+/// Reads a `Key: value` line from `/proc/self/status`, converting `kB` to bytes.
+fn status_value(status: &str, key: &str) -> Option<u64> {
+    let line = status
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))?;
+    let mut parts = line.split_whitespace();
+    let value: u64 = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some("kB") => Some(value * 1024),
+        _ => Some(value),
+    }
+}
+
+/// Per-CPU times in seconds from the `cpuN` lines of `/proc/stat`.
+fn host_cpu_times(stat: &str) -> Vec<(i64, [(&'static str, f64); 7])> {
+    stat.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let cpu = fields.next()?.strip_prefix("cpu")?.parse().ok()?;
+            let ticks: Vec<u64> = fields.take(8).map_while(|f| f.parse().ok()).collect();
+            let &[user, nice, system, idle, iowait, irq, softirq, steal] = ticks.as_slice() else {
+                return None;
+            };
+            let seconds = |ticks: u64| ticks as f64 / USER_HZ;
+            Some((
+                cpu,
+                [
+                    ("user", seconds(user)),
+                    ("nice", seconds(nice)),
+                    ("system", seconds(system)),
+                    ("idle", seconds(idle)),
+                    ("iowait", seconds(iowait)),
+                    ("interrupt", seconds(irq + softirq)),
+                    ("steal", seconds(steal)),
+                ],
+            ))
+        })
+        .collect()
+}
+
+/// `/proc/meminfo` values in bytes, keyed by field name.
+fn parse_meminfo(meminfo: &str) -> HashMap<&str, u64> {
+    meminfo
+        .lines()
+        .filter_map(|line| {
+            let (key, _) = line.split_once(':')?;
+            Some((key, status_value(line, key)?))
+        })
+        .collect()
+}
+
+/// Splits total memory into the `system.memory.state` values, counting
+/// reclaimable slab as cache.
+fn memory_usage(meminfo: &HashMap<&str, u64>) -> Vec<(&'static str, u64)> {
+    let get = |key: &str| meminfo.get(key).copied();
+    let (Some(total), Some(free)) = (get("MemTotal"), get("MemFree")) else {
+        return Vec::new();
+    };
+    let buffers = get("Buffers").unwrap_or_default();
+    let cached = get("Cached").unwrap_or_default() + get("SReclaimable").unwrap_or_default();
+    let used = total.saturating_sub(free + buffers + cached);
+
+    vec![
+        ("used", used),
+        ("free", free),
+        ("buffers", buffers),
+        ("cached", cached),
+    ]
+}
+
+fn load_average(loadavg: &str) -> Option<[f64; 3]> {
+    let mut fields = loadavg.split_whitespace().map(|f| f.parse().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+/// Received and transmitted bytes per interface from `/proc/net/dev`.
+fn network_io(dev: &str) -> Vec<(String, u64, u64)> {
+    dev.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (device, counters) = line.split_once(':')?;
+            let counters: Vec<u64> = counters
+                .split_whitespace()
+                .map_while(|f| f.parse().ok())
+                .collect();
+            Some((
+                device.trim().to_string(),
+                *counters.first()?,
+                *counters.get(8)?,
+            ))
+        })
+        .collect()
+}
+
+/// Read and written bytes per block device from `/proc/diskstats`, leaving out
+/// loop and RAM devices.
+fn disk_io(diskstats: &str) -> Vec<(String, u64, u64)> {
+    diskstats
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let device = *fields.get(2)?;
+            if device.starts_with("loop") || device.starts_with("ram") {
+                return None;
+            }
+            let sectors_read: u64 = fields.get(5)?.parse().ok()?;
+            let sectors_written: u64 = fields.get(9)?.parse().ok()?;
+            Some((
+                device.to_string(),
+                sectors_read * SECTOR_SIZE,
+                sectors_written * SECTOR_SIZE,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meter::testing::TestReader;
+    use opentelemetry::metrics::MeterProvider as _;
+    use std::path::Path;
+
+    fn fixture_procfs() -> Procfs {
+        Procfs::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs"))
+    }
+
+    fn value(points: &[(Vec<KeyValue>, f64)], attrs: &[(&str, &str)]) -> Option<f64> {
+        points
+            .iter()
+            .find(|(point_attrs, _)| {
+                attrs.iter().all(|(key, expected)| {
+                    point_attrs
+                        .iter()
+                        .any(|kv| kv.key.as_str() == *key && kv.value.as_str() == *expected)
+                })
+            })
+            .map(|(_, value)| *value)
+    }
+
+    #[test]
+    fn test_process_metrics_from_procfs() {
+        let (provider, reader) = TestReader::provider();
+        register(
+            &provider.meter("test_meter"),
+            &SystemMetricsConfig::default().enabled(true),
+            fixture_procfs(),
+        );
+
+        let cpu = reader.data_points(metric::PROCESS_CPU_TIME);
+        assert_eq!(value(&cpu, &[("cpu.mode", "user")]), Some(12.5));
+        assert_eq!(value(&cpu, &[("cpu.mode", "system")]), Some(3.25));
+
+        let rss = reader.data_points(metric::PROCESS_MEMORY_USAGE);
+        assert_eq!(rss[0].1, (10240 * 1024) as f64);
+        let threads = reader.data_points(metric::PROCESS_THREAD_COUNT);
+        assert_eq!(threads[0].1, 9.0);
+        let fds = reader.data_points(metric::PROCESS_OPEN_FILE_DESCRIPTOR_COUNT);
+        assert_eq!(fds[0].1, 3.0);
+        let switches = reader.data_points(metric::PROCESS_CONTEXT_SWITCHES);
+        assert_eq!(
+            value(&switches, &[("process.context_switch_type", "involuntary")]),
+            Some(17.0)
+        );
+    }
+
+    #[test]
+    fn test_host_metrics_from_procfs() {
+        let (provider, reader) = TestReader::provider();
+        register(
+            &provider.meter("test_meter"),
+            &SystemMetricsConfig::default().enabled(true),
+            fixture_procfs(),
+        );
+
+        let cpu = reader.data_points(metric::SYSTEM_CPU_TIME);
+        assert_eq!(cpu.len(), 14);
+        assert_eq!(
+            value(
+                &cpu,
+                &[
+                    ("cpu.mode", "interrupt"),
+                    ("system.cpu.logical_number", "1")
+                ]
+            ),
+            Some(0.1)
+        );
+
+        let memory = reader.data_points(metric::SYSTEM_MEMORY_USAGE);
+        assert_eq!(
+            value(&memory, &[("system.memory.state", "cached")]),
+            Some(((4096 + 1024) * 1024) as f64)
+        );
+        assert_eq!(
+            value(&memory, &[("system.memory.state", "used")]),
+            Some(((16384 - 2048 - 512 - 4096 - 1024) * 1024) as f64)
+        );
+
+        let load = reader.data_points("system.cpu.load_average.5m");
+        assert_eq!(load[0].1, 0.75);
+
+        let network = reader.data_points(metric::SYSTEM_NETWORK_IO);
+        assert_eq!(
+            value(
+                &network,
+                &[
+                    ("network.io.direction", "transmit"),
+                    ("system.device", "eth0")
+                ]
+            ),
+            Some(2048.0)
+        );
+
+        let disk = reader.data_points(metric::SYSTEM_DISK_IO);
+        assert_eq!(disk.len(), 2);
+        assert_eq!(
+            value(
+                &disk,
+                &[("disk.io.direction", "read"), ("system.device", "sda")]
+            ),
+            Some((100 * SECTOR_SIZE) as f64)
+        );
+    }
+
+    #[test]
+    fn test_disabled_instruments_are_skipped() {
+        let (provider, reader) = TestReader::provider();
+        let config = SystemMetricsConfig::default()
+            .enabled(true)
+            .process_cpu(false)
+            .process_memory(false)
+            .process_open_fds(false)
+            .process_context_switches(false)
+            .host_cpu(false)
+            .host_memory(false)
+            .host_load(false)
+            .host_network(false)
+            .host_disk(false);
+        register(&provider.meter("test_meter"), &config, fixture_procfs());
+
+        assert_eq!(reader.metric_names(), vec![metric::PROCESS_THREAD_COUNT]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_register_reads_live_procfs() {
+        let (provider, reader) = TestReader::provider();
+        register_system_metrics(&provider, &SystemMetricsConfig::default().enabled(true));
+
+        let threads = reader.data_points(metric::PROCESS_THREAD_COUNT);
+        assert!(threads[0].1 >= 1.0);
+        assert!(!reader.data_points(metric::SYSTEM_MEMORY_USAGE).is_empty());
+    }
+
+    #[test]
+    fn test_parsers() {
+        assert_eq!(
+            process_cpu_times("1 (my (odd) app) S 1 2 3 4 5 6 7 8 9 10 200 100 0 0".to_string()),
+            Some((2.0, 1.0))
+        );
+        assert_eq!(
+            status_value("VmRSS:\t  4 kB\nThreads:\t3", "VmRSS"),
+            Some(4096)
+        );
+        assert_eq!(status_value("VmRSS:\t  4 kB", "Vm"), None);
+        assert_eq!(load_average("0.5 1.0 1.5 2/300 42"), Some([0.5, 1.0, 1.5]));
+        assert!(host_cpu_times("cpu  1 2 3 4 5 6 7 8 0 0\n").is_empty());
+    }
+}
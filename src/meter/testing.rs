@@ -0,0 +1,104 @@
+//! In-memory metric collection for the meter tests.
+
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::data::{Gauge, Metric, ResourceMetrics, Sum};
+use opentelemetry_sdk::metrics::reader::MetricReader;
+use opentelemetry_sdk::metrics::{
+    InstrumentKind, ManualReader, MetricResult, Pipeline, SdkMeterProvider, Temporality,
+};
+use std::sync::{Arc, Weak};
+
+/// A manual reader that stays reachable after being handed to the provider.
+#[derive(Clone, Debug)]
+pub(crate) struct TestReader(Arc<ManualReader>);
+
+impl TestReader {
+    pub(crate) fn new() -> Self {
+        TestReader(Arc::new(ManualReader::builder().build()))
+    }
+
+    pub(crate) fn provider() -> (SdkMeterProvider, TestReader) {
+        let reader = TestReader::new();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .build();
+        (provider, reader)
+    }
+
+    pub(crate) fn collect(&self) -> ResourceMetrics {
+        let mut collected = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: Vec::new(),
+        };
+        self.0.collect(&mut collected).unwrap();
+        collected
+    }
+
+    pub(crate) fn metrics(&self) -> Vec<Metric> {
+        self.collect()
+            .scope_metrics
+            .into_iter()
+            .flat_map(|scope| scope.metrics)
+            .collect()
+    }
+
+    pub(crate) fn metric_names(&self) -> Vec<String> {
+        self.metrics()
+            .iter()
+            .map(|metric| metric.name.to_string())
+            .collect()
+    }
+
+    /// Data points of the named sum or gauge, with every value widened to `f64`.
+    pub(crate) fn data_points(&self, name: &str) -> Vec<(Vec<KeyValue>, f64)> {
+        let metrics = self.metrics();
+        let Some(metric) = metrics.iter().find(|metric| metric.name == name) else {
+            return Vec::new();
+        };
+        let data = metric.data.as_any();
+
+        macro_rules! points {
+            ($($kind:ident<$ty:ty>),*) => {
+                $(if let Some(agg) = data.downcast_ref::<$kind<$ty>>() {
+                    return agg
+                        .data_points
+                        .iter()
+                        .map(|point| (point.attributes.clone(), point.value as f64))
+                        .collect();
+                })*
+            };
+        }
+        points!(
+            Sum<u64>,
+            Sum<i64>,
+            Sum<f64>,
+            Gauge<u64>,
+            Gauge<i64>,
+            Gauge<f64>
+        );
+        Vec::new()
+    }
+}
+
+impl MetricReader for TestReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.0.register_pipeline(pipeline)
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> MetricResult<()> {
+        self.0.collect(rm)
+    }
+
+    fn force_flush(&self) -> MetricResult<()> {
+        self.0.force_flush()
+    }
+
+    fn shutdown(&self) -> MetricResult<()> {
+        self.0.shutdown()
+    }
+
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.0.temporality(kind)
+    }
+}
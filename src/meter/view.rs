@@ -0,0 +1,333 @@
+use opentelemetry::{InstrumentationScope, Key};
+use opentelemetry_sdk::metrics::{
+    Aggregation, Instrument, InstrumentKind, MetricResult, Stream, View, new_view,
+};
+
+/// Reshapes the streams of the instruments it selects.
+///
+/// An instrument is selected when it matches every selector that is set; the
+/// instrument name may contain `*` and `?` wildcards.
+///
+/// Attributes can only be narrowed with an allow list; there is no deny list.
+/// The SDK fixes the keys a stream keeps when the instrument is created, before
+/// any measurement is recorded, so keys to drop cannot be turned into keys to
+/// keep. Unknown fields, such as a `denied_attributes` list, are rejected when
+/// loading a configuration file rather than ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ViewConfig {
+    pub instrument_name: Option<String>,
+    pub instrument_kind: Option<InstrumentType>,
+    pub meter_name: Option<String>,
+    /// New name for the stream; needs an instrument name without wildcards.
+    pub rename: Option<String>,
+    /// Attribute keys to keep; every other key is dropped. `None` keeps all.
+    pub allowed_attributes: Option<Vec<String>>,
+    pub aggregation: Option<ViewAggregation>,
+}
+
+impl ViewConfig {
+    pub fn with_instrument_name(mut self, name: impl Into<String>) -> Self {
+        self.instrument_name = Some(name.into());
+        self
+    }
+
+    pub fn with_instrument_kind(mut self, kind: InstrumentType) -> Self {
+        self.instrument_kind = Some(kind);
+        self
+    }
+
+    pub fn with_meter_name(mut self, name: impl Into<String>) -> Self {
+        self.meter_name = Some(name.into());
+        self
+    }
+
+    pub fn with_rename(mut self, name: impl Into<String>) -> Self {
+        self.rename = Some(name.into());
+        self
+    }
+
+    pub fn with_allowed_attributes<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.allowed_attributes = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_aggregation(mut self, aggregation: ViewAggregation) -> Self {
+        self.aggregation = Some(aggregation);
+        self
+    }
+
+    /// Why the view cannot be applied, if it cannot.
+    pub(crate) fn problem(&self) -> Option<String> {
+        let name = self.instrument_name.as_deref().filter(|n| !n.is_empty());
+        if name.is_none() && self.instrument_kind.is_none() && self.meter_name.is_none() {
+            return Some("selects no instruments".to_string());
+        }
+        if self.rename.is_some() && name.is_none_or(|n| n.contains(['*', '?'])) {
+            return Some("rename needs an instrument name without wildcards".to_string());
+        }
+        if let Some(aggregation) = &self.aggregation
+            && let Err(err) = Aggregation::from(aggregation.clone()).validate()
+        {
+            return Some(err.to_string());
+        }
+        self.to_view().err().map(|err| err.to_string())
+    }
+
+    pub(crate) fn to_view(&self) -> MetricResult<Box<dyn View>> {
+        let mut criteria = Instrument::new();
+        if let Some(name) = &self.instrument_name {
+            criteria = criteria.name(name.clone());
+        }
+        criteria.kind = self.instrument_kind.map(Into::into);
+        if let Some(meter) = &self.meter_name {
+            criteria = criteria.scope(InstrumentationScope::builder(meter.clone()).build());
+        }
+
+        let mut mask = Stream::new();
+        if let Some(name) = &self.rename {
+            mask = mask.name(name.clone());
+        }
+        if let Some(keys) = &self.allowed_attributes {
+            mask = mask.allowed_attribute_keys(keys.iter().cloned().map(Key::new));
+        }
+        if let Some(aggregation) = &self.aggregation {
+            mask = mask.aggregation(aggregation.clone().into());
+        }
+
+        new_view(criteria, mask)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum InstrumentType {
+    Counter,
+    UpDownCounter,
+    Histogram,
+    Gauge,
+    ObservableCounter,
+    ObservableUpDownCounter,
+    ObservableGauge,
+}
+
+impl From<InstrumentType> for InstrumentKind {
+    fn from(kind: InstrumentType) -> Self {
+        match kind {
+            InstrumentType::Counter => InstrumentKind::Counter,
+            InstrumentType::UpDownCounter => InstrumentKind::UpDownCounter,
+            InstrumentType::Histogram => InstrumentKind::Histogram,
+            InstrumentType::Gauge => InstrumentKind::Gauge,
+            InstrumentType::ObservableCounter => InstrumentKind::ObservableCounter,
+            InstrumentType::ObservableUpDownCounter => InstrumentKind::ObservableUpDownCounter,
+            InstrumentType::ObservableGauge => InstrumentKind::ObservableGauge,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ViewAggregation {
+    /// Discards every measurement of the selected instruments.
+    Drop,
+    Sum,
+    LastValue,
+    /// A histogram with the given increasing bucket upper bounds.
+    ExplicitBucketHistogram {
+        boundaries: Vec<f64>,
+        record_min_max: bool,
+    },
+    /// A base-2 exponential histogram; `max_scale` must be within -10..=20.
+    ExponentialHistogram {
+        max_size: u32,
+        max_scale: i8,
+        record_min_max: bool,
+    },
+}
+
+impl ViewAggregation {
+    pub fn explicit_buckets(boundaries: impl Into<Vec<f64>>) -> Self {
+        ViewAggregation::ExplicitBucketHistogram {
+            boundaries: boundaries.into(),
+            record_min_max: true,
+        }
+    }
+
+    /// The specification's default of 160 buckets at scale 20.
+    pub fn exponential() -> Self {
+        ViewAggregation::ExponentialHistogram {
+            max_size: 160,
+            max_scale: 20,
+            record_min_max: true,
+        }
+    }
+}
+
+impl From<ViewAggregation> for Aggregation {
+    fn from(aggregation: ViewAggregation) -> Self {
+        match aggregation {
+            ViewAggregation::Drop => Aggregation::Drop,
+            ViewAggregation::Sum => Aggregation::Sum,
+            ViewAggregation::LastValue => Aggregation::LastValue,
+            ViewAggregation::ExplicitBucketHistogram {
+                boundaries,
+                record_min_max,
+            } => Aggregation::ExplicitBucketHistogram {
+                boundaries,
+                record_min_max,
+            },
+            ViewAggregation::ExponentialHistogram {
+                max_size,
+                max_scale,
+                record_min_max,
+            } => Aggregation::Base2ExponentialHistogram {
+                max_size,
+                max_scale,
+                record_min_max,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meter::testing::TestReader;
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::MeterProvider as _;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::metrics::data::Histogram;
+
+    fn provider_with(view: &ViewConfig) -> (SdkMeterProvider, TestReader) {
+        let reader = TestReader::new();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .with_view(view.to_view().unwrap())
+            .build();
+        (provider, reader)
+    }
+
+    #[test]
+    fn test_rename_and_allowed_attributes() {
+        let view = ViewConfig::default()
+            .with_instrument_name("http.requests")
+            .with_rename("http.server.requests")
+            .with_allowed_attributes(["method"]);
+        let (provider, reader) = provider_with(&view);
+
+        let counter = provider
+            .meter("test_meter")
+            .u64_counter("http.requests")
+            .build();
+        counter.add(
+            1,
+            &[KeyValue::new("method", "GET"), KeyValue::new("path", "/a")],
+        );
+        counter.add(
+            1,
+            &[KeyValue::new("method", "GET"), KeyValue::new("path", "/b")],
+        );
+
+        let points = reader.data_points("http.server.requests");
+        assert_eq!(points, vec![(vec![KeyValue::new("method", "GET")], 2.0)]);
+    }
+
+    #[test]
+    fn test_wildcard_and_kind_selector() {
+        let view = ViewConfig::default()
+            .with_instrument_name("queue.*")
+            .with_instrument_kind(InstrumentType::Counter)
+            .with_aggregation(ViewAggregation::Drop);
+        let (provider, reader) = provider_with(&view);
+
+        let meter = provider.meter("test_meter");
+        meter.u64_counter("queue.pushed").build().add(1, &[]);
+        meter.i64_up_down_counter("queue.depth").build().add(1, &[]);
+        meter.u64_counter("jobs.done").build().add(1, &[]);
+
+        assert_eq!(reader.metric_names(), vec!["queue.depth", "jobs.done"]);
+    }
+
+    #[test]
+    fn test_meter_selector() {
+        let view = ViewConfig::default()
+            .with_meter_name("noisy")
+            .with_aggregation(ViewAggregation::Drop);
+        let (provider, reader) = provider_with(&view);
+
+        provider.meter("noisy").u64_counter("a").build().add(1, &[]);
+        provider.meter("quiet").u64_counter("b").build().add(1, &[]);
+
+        assert_eq!(reader.metric_names(), vec!["b"]);
+    }
+
+    #[test]
+    fn test_explicit_bucket_boundaries() {
+        let view = ViewConfig::default()
+            .with_instrument_name("latency")
+            .with_aggregation(ViewAggregation::explicit_buckets([0.1, 1.0]));
+        let (provider, reader) = provider_with(&view);
+
+        let histogram = provider
+            .meter("test_meter")
+            .f64_histogram("latency")
+            .build();
+        histogram.record(0.5, &[]);
+
+        let metrics = reader.metrics();
+        let data = metrics[0].data.as_any();
+        let histogram = data.downcast_ref::<Histogram<f64>>().unwrap();
+        assert_eq!(histogram.data_points[0].bounds, vec![0.1, 1.0]);
+        assert_eq!(histogram.data_points[0].bucket_counts, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_problems() {
+        assert!(ViewConfig::default().problem().is_some());
+        assert!(
+            ViewConfig::default()
+                .with_instrument_name("http.*")
+                .with_rename("http")
+                .problem()
+                .is_some()
+        );
+        assert!(
+            ViewConfig::default()
+                .with_instrument_name("latency")
+                .with_aggregation(ViewAggregation::explicit_buckets([1.0, 0.5]))
+                .problem()
+                .is_some()
+        );
+        assert!(
+            ViewConfig::default()
+                .with_instrument_kind(InstrumentType::Histogram)
+                .with_aggregation(ViewAggregation::ExponentialHistogram {
+                    max_size: 160,
+                    max_scale: 21,
+                    record_min_max: true,
+                })
+                .problem()
+                .is_some()
+        );
+        assert!(
+            ViewConfig::default()
+                .with_instrument_name("http[*")
+                .problem()
+                .is_some()
+        );
+        assert_eq!(
+            ViewConfig::default()
+                .with_instrument_name("latency")
+                .with_aggregation(ViewAggregation::exponential())
+                .problem(),
+            None
+        );
+    }
+}
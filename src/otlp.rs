@@ -0,0 +1,353 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use opentelemetry_otlp::WithHttpConfig;
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+use std::collections::HashMap;
+
+use crate::auth::Credentials;
+
+#[derive(Debug, Error)]
+#[error("unknown otlp protocol {0:?}, expected grpc, http/protobuf or http/json")]
+pub struct ParseProtocolError(String);
+
+/// Transport used to ship a signal to an OTLP endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Protocol {
+    /// OTLP over gRPC, usually on port 4317. Requires the `grpc` feature.
+    #[cfg_attr(feature = "serde", serde(rename = "grpc"))]
+    Grpc,
+    /// OTLP over HTTP with protobuf payloads, usually on port 4318. Requires the `http-proto` feature.
+    #[cfg_attr(feature = "serde", serde(rename = "http/protobuf"))]
+    HttpProtobuf,
+    /// OTLP over HTTP with JSON payloads. Requires the `http-json` feature.
+    #[cfg_attr(feature = "serde", serde(rename = "http/json"))]
+    HttpJson,
+}
+
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Grpc => "grpc",
+            Protocol::HttpProtobuf => "http/protobuf",
+            Protocol::HttpJson => "http/json",
+        }
+    }
+
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Protocol::Grpc => "grpc",
+            Protocol::HttpProtobuf => "http-proto",
+            Protocol::HttpJson => "http-json",
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Protocol::Grpc => cfg!(feature = "grpc"),
+            Protocol::HttpProtobuf => cfg!(feature = "http-proto"),
+            Protocol::HttpJson => cfg!(feature = "http-json"),
+        }
+    }
+
+    pub fn is_http(&self) -> bool {
+        matches!(self, Protocol::HttpProtobuf | Protocol::HttpJson)
+    }
+
+    /// Collector address assumed by the OpenTelemetry specification when none is configured.
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            Protocol::Grpc => "http://localhost:4317",
+            Protocol::HttpProtobuf | Protocol::HttpJson => "http://localhost:4318",
+        }
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = ParseProtocolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "grpc" => Ok(Protocol::Grpc),
+            "http/protobuf" => Ok(Protocol::HttpProtobuf),
+            "http/json" => Ok(Protocol::HttpJson),
+            other => Err(ParseProtocolError(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<Protocol> for opentelemetry_otlp::Protocol {
+    fn from(protocol: Protocol) -> Self {
+        match protocol {
+            Protocol::Grpc => opentelemetry_otlp::Protocol::Grpc,
+            Protocol::HttpProtobuf => opentelemetry_otlp::Protocol::HttpBinary,
+            Protocol::HttpJson => opentelemetry_otlp::Protocol::HttpJson,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TargetError {
+    #[error("endpoint is required")]
+    EndpointRequired,
+    #[error("protocol {0} requires the `{feature}` cargo feature", feature = .0.feature())]
+    UnsupportedProtocol(Protocol),
+}
+
+/// A further OTLP destination for a signal, fed from the same provider as the
+/// component's own `endpoint`, e.g. to dual-write during a backend migration.
+/// Added through `targets` on `LoggerConfig`, `TracerConfig` and `MeterConfig`;
+/// protocol and timeout fall back to the component's.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ExporterTarget {
+    pub endpoint: String,
+    pub protocol: Option<Protocol>,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub timeout: Option<Duration>,
+    pub credentials: Credentials,
+}
+
+impl ExporterTarget {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Checks the target as it would be used by a component speaking `fallback`.
+    pub fn validate(&self, fallback: Protocol) -> Result<(), TargetError> {
+        if self.endpoint.is_empty() {
+            return Err(TargetError::EndpointRequired);
+        }
+        let protocol = self.protocol.unwrap_or(fallback);
+        if !protocol.is_supported() {
+            return Err(TargetError::UnsupportedProtocol(protocol));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn destination(
+        &self,
+        protocol: Protocol,
+        timeout: Duration,
+        signal_path: &str,
+    ) -> Destination<'_> {
+        Destination::new(
+            &self.endpoint,
+            self.protocol.unwrap_or(protocol),
+            self.timeout.unwrap_or(timeout),
+            &self.credentials,
+            Some(signal_path),
+        )
+    }
+}
+
+/// Everything needed to build one signal exporter.
+pub(crate) struct Destination<'a> {
+    pub(crate) endpoint: String,
+    pub(crate) protocol: Protocol,
+    pub(crate) timeout: Duration,
+    pub(crate) credentials: &'a Credentials,
+}
+
+impl<'a> Destination<'a> {
+    pub(crate) fn new(
+        endpoint: &str,
+        protocol: Protocol,
+        timeout: Duration,
+        credentials: &'a Credentials,
+        signal_path: Option<&str>,
+    ) -> Self {
+        Self {
+            endpoint: signal_endpoint(endpoint, protocol, signal_path),
+            protocol,
+            timeout,
+            credentials,
+        }
+    }
+}
+
+/// Resolves the endpoint a signal exporter should dial.
+///
+/// HTTP exporters post to `{endpoint}/v1/{signal}` unless the path is already present.
+/// gRPC exporters dial the bare authority, so a trailing signal path is stripped.
+/// Without a `signal_path` the endpoint is a full signal URL and is used as-is.
+pub(crate) fn signal_endpoint(
+    endpoint: &str,
+    protocol: Protocol,
+    signal_path: Option<&str>,
+) -> String {
+    let Some(signal_path) = signal_path else {
+        return endpoint.to_string();
+    };
+    let trimmed = endpoint.trim_end_matches('/');
+
+    if protocol.is_http() {
+        if trimmed.ends_with(signal_path) {
+            trimmed.to_string()
+        } else {
+            format!("{trimmed}{signal_path}")
+        }
+    } else {
+        trimmed
+            .strip_suffix(signal_path)
+            .unwrap_or(trimmed)
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
+
+pub(crate) fn unsupported(protocol: Protocol) -> anyhow::Error {
+    anyhow::anyhow!(
+        "otlp protocol {protocol} requires the `{}` cargo feature",
+        protocol.feature()
+    )
+}
+
+// `WithHttpConfig::with_headers` only keeps the first entry of each map it is
+// given, so credentials are applied one header at a time.
+#[cfg(any(feature = "http-proto", feature = "http-json"))]
+pub(crate) fn with_http_headers<B: WithHttpConfig>(builder: B, credentials: &Credentials) -> B {
+    credentials
+        .header_map()
+        .into_iter()
+        .fold(builder, |builder, (key, value)| {
+            builder.with_headers(HashMap::from([(key, value)]))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_endpoint_appends_signal_path() {
+        assert_eq!(
+            signal_endpoint(
+                "http://localhost:4318",
+                Protocol::HttpProtobuf,
+                Some("/v1/logs")
+            ),
+            "http://localhost:4318/v1/logs"
+        );
+        assert_eq!(
+            signal_endpoint(
+                "http://localhost:3100/otlp/",
+                Protocol::HttpJson,
+                Some("/v1/logs")
+            ),
+            "http://localhost:3100/otlp/v1/logs"
+        );
+        assert_eq!(
+            signal_endpoint(
+                "http://localhost:4318/v1/traces",
+                Protocol::HttpProtobuf,
+                Some("/v1/traces")
+            ),
+            "http://localhost:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn test_signal_url_is_used_as_is() {
+        assert_eq!(
+            signal_endpoint("http://collector:4318/custom", Protocol::HttpProtobuf, None),
+            "http://collector:4318/custom"
+        );
+    }
+
+    #[test]
+    fn test_grpc_endpoint_strips_signal_path() {
+        assert_eq!(
+            signal_endpoint("http://localhost:4317", Protocol::Grpc, Some("/v1/traces")),
+            "http://localhost:4317"
+        );
+        assert_eq!(
+            signal_endpoint(
+                "http://localhost:4317/v1/traces/",
+                Protocol::Grpc,
+                Some("/v1/traces")
+            ),
+            "http://localhost:4317"
+        );
+    }
+
+    #[test]
+    fn test_target_falls_back_to_component_settings() {
+        let target = ExporterTarget::new("http://new-collector:4318");
+        let destination =
+            target.destination(Protocol::HttpProtobuf, Duration::from_secs(5), "/v1/traces");
+        assert_eq!(destination.endpoint, "http://new-collector:4318/v1/traces");
+        assert_eq!(destination.timeout, Duration::from_secs(5));
+
+        let target = ExporterTarget::new("http://new-collector:4317/")
+            .with_protocol(Protocol::Grpc)
+            .with_timeout(Duration::from_secs(1));
+        let destination =
+            target.destination(Protocol::HttpProtobuf, Duration::from_secs(5), "/v1/traces");
+        assert_eq!(destination.endpoint, "http://new-collector:4317");
+        assert_eq!(destination.protocol, Protocol::Grpc);
+        assert_eq!(destination.timeout, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_target_validation() {
+        assert!(matches!(
+            ExporterTarget::default().validate(Protocol::HttpProtobuf),
+            Err(TargetError::EndpointRequired)
+        ));
+        assert!(
+            ExporterTarget::new("http://collector:4318")
+                .validate(Protocol::HttpProtobuf)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_protocol_conversion() {
+        assert_eq!(
+            opentelemetry_otlp::Protocol::from(Protocol::HttpProtobuf),
+            opentelemetry_otlp::Protocol::HttpBinary
+        );
+        assert_eq!(Protocol::HttpJson.to_string(), "http/json");
+    }
+
+    #[test]
+    fn test_protocol_from_str() {
+        assert_eq!("grpc".parse::<Protocol>().unwrap(), Protocol::Grpc);
+        assert_eq!(
+            "http/protobuf".parse::<Protocol>().unwrap(),
+            Protocol::HttpProtobuf
+        );
+        assert_eq!("http/json".parse::<Protocol>().unwrap(), Protocol::HttpJson);
+        assert!("thrift".parse::<Protocol>().is_err());
+    }
+}
@@ -11,6 +11,8 @@ pub enum ProfilerError {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ProfilerConfig {
     pub enabled: bool,
     pub server_url: Option<String>,
@@ -20,6 +22,12 @@ pub struct ProfilerConfig {
     pub credentials: Credentials,
 }
 
+impl Default for ProfilerConfig {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
 impl ProfilerConfig {
     pub fn new(service_name: impl Into<String>) -> Self {
         let service_name = service_name.into();
@@ -67,13 +75,10 @@ impl ProfilerConfig {
             self.tenant_id = Some("anonymous".to_string());
         }
 
-        if !self.tags.contains_key("service") {
-            self.tags
-                .insert("service".to_string(), self.service_name.clone());
-        }
-        if !self.tags.contains_key("service_name") {
-            self.tags
-                .insert("service_name".to_string(), self.service_name.clone());
+        for key in ["service", "service_name"] {
+            if self.tags.get(key).is_none_or(|value| value.is_empty()) {
+                self.tags.insert(key.to_string(), self.service_name.clone());
+            }
         }
     }
 
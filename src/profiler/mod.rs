@@ -45,6 +45,8 @@ pub fn setup(config: &ProfilerConfig) -> Result<Option<PyroscopeAgent>> {
 
     if let Some((username, password)) = config.credentials.basic_auth() {
         agent_builder = agent_builder.basic_auth(username, password);
+    } else if let Some(token) = config.credentials.bearer() {
+        agent_builder = agent_builder.auth_token(token);
     }
 
     let agent = agent_builder
@@ -71,6 +73,18 @@ pub fn shutdown(agent: PyroscopeAgent) {
 #[cfg(not(all(unix, feature = "profiler")))]
 pub fn shutdown(_agent: PyroscopeAgent) {}
 
+/// Stops the agent, which uploads the profile collected so far.
+#[cfg(all(unix, feature = "profiler"))]
+pub fn try_shutdown(agent: PyroscopeAgent) -> Result<()> {
+    agent.stop().context("failed to stop pyroscope agent")?;
+    Ok(())
+}
+
+#[cfg(not(all(unix, feature = "profiler")))]
+pub fn try_shutdown(_agent: PyroscopeAgent) -> anyhow::Result<()> {
+    Ok(())
+}
+
 #[cfg(test)]
 #[cfg(all(unix, feature = "profiler"))]
 mod tests {
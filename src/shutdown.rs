@@ -0,0 +1,195 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use crate::telemetry::{Component, Telemetry};
+
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShutdownStatus {
+    Completed,
+    /// The component had not finished when the deadline passed. Its shutdown
+    /// keeps running on a background thread.
+    TimedOut,
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct ComponentShutdown {
+    pub component: Component,
+    pub status: ShutdownStatus,
+    /// Time until the component finished, or the deadline if it timed out.
+    pub elapsed: Duration,
+}
+
+/// Outcome of [`Telemetry::shutdown_with_timeout`] and [`Telemetry::shutdown_async`],
+/// listing every component that was running.
+///
+/// [`Telemetry::shutdown_with_timeout`]: crate::Telemetry::shutdown_with_timeout
+/// [`Telemetry::shutdown_async`]: crate::Telemetry::shutdown_async
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownReport {
+    pub components: Vec<ComponentShutdown>,
+}
+
+impl ShutdownReport {
+    /// Orders the finished components as started and marks the rest as timed out.
+    pub(crate) fn new(
+        started: &[Component],
+        mut finished: Vec<ComponentShutdown>,
+        timeout: Duration,
+    ) -> Self {
+        let components = started
+            .iter()
+            .map(|component| {
+                match finished
+                    .iter()
+                    .position(|done| done.component == *component)
+                {
+                    Some(index) => finished.swap_remove(index),
+                    None => ComponentShutdown {
+                        component: *component,
+                        status: ShutdownStatus::TimedOut,
+                        elapsed: timeout,
+                    },
+                }
+            })
+            .collect();
+
+        Self { components }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.components
+            .iter()
+            .all(|c| c.status == ShutdownStatus::Completed)
+    }
+
+    pub fn status(&self, component: Component) -> Option<&ShutdownStatus> {
+        self.components
+            .iter()
+            .find(|c| c.component == component)
+            .map(|c| &c.status)
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.components.is_empty() {
+            return f.write_str("no components running");
+        }
+
+        for (i, c) in self.components.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match &c.status {
+                ShutdownStatus::Completed => write!(f, "{}: ok in {:?}", c.component, c.elapsed)?,
+                ShutdownStatus::TimedOut => {
+                    write!(f, "{}: timed out after {:?}", c.component, c.elapsed)?
+                }
+                ShutdownStatus::Failed(reason) => write!(f, "{}: {reason}", c.component)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Owns a [`Telemetry`] and shuts it down when dropped, so buffered logs and
+/// spans are flushed on early returns and panics.
+///
+/// Dropping blocks for up to the timeout. Inside a single-threaded Tokio
+/// runtime the batch processors cannot make progress while it blocks, so
+/// prefer [`TelemetryGuard::shutdown_async`] there.
+#[must_use = "dropping the guard shuts telemetry down immediately"]
+pub struct TelemetryGuard {
+    telemetry: Option<Telemetry>,
+    timeout: Duration,
+    on_incomplete: Option<IncompleteHook>,
+}
+
+type IncompleteHook = Box<dyn Fn(&ShutdownReport) + Send + Sync>;
+
+impl TelemetryGuard {
+    pub fn new(telemetry: Telemetry) -> Self {
+        Self {
+            telemetry: Some(telemetry),
+            timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            on_incomplete: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Called with the report when a shutdown the guard starts itself, on drop
+    /// or after a signal, does not complete. Without a hook the report is
+    /// logged as a `tracing` warning.
+    pub fn on_incomplete(mut self, hook: impl Fn(&ShutdownReport) + Send + Sync + 'static) -> Self {
+        self.on_incomplete = Some(Box::new(hook));
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn shutdown(mut self) -> ShutdownReport {
+        self.take().shutdown_with_timeout(self.timeout)
+    }
+
+    pub async fn shutdown_async(mut self) -> ShutdownReport {
+        self.take().shutdown_async(self.timeout).await
+    }
+
+    /// Disarms the guard and hands back the telemetry without shutting it down.
+    pub fn into_inner(mut self) -> Telemetry {
+        self.take()
+    }
+
+    pub(crate) fn report_incomplete(&self, report: &ShutdownReport) {
+        if report.is_success() {
+            return;
+        }
+        match &self.on_incomplete {
+            Some(hook) => hook(report),
+            None => tracing::warn!(%report, "telemetry shutdown incomplete"),
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Telemetry {
+        self.telemetry
+            .take()
+            .expect("telemetry is only taken when the guard is consumed")
+    }
+}
+
+impl Deref for TelemetryGuard {
+    type Target = Telemetry;
+
+    fn deref(&self) -> &Telemetry {
+        self.telemetry
+            .as_ref()
+            .expect("telemetry is only taken when the guard is consumed")
+    }
+}
+
+impl DerefMut for TelemetryGuard {
+    fn deref_mut(&mut self) -> &mut Telemetry {
+        self.telemetry
+            .as_mut()
+            .expect("telemetry is only taken when the guard is consumed")
+    }
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(telemetry) = self.telemetry.take() {
+            let report = telemetry.shutdown_with_timeout(self.timeout);
+            self.report_incomplete(&report);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Flushes telemetry when the process is asked to stop.
+//!
+//! Installing a handler replaces the default action of SIGINT and SIGTERM, so
+//! the process no longer exits on its own; either shut the application down
+//! after [`TelemetryGuard::shutdown_on_signal`] returns or use
+//! [`TelemetryGuard::exit_on_signal`].
+
+use std::fmt;
+use std::io;
+
+use tokio::task::JoinHandle;
+
+use crate::shutdown::{ShutdownReport, TelemetryGuard};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    Interrupt,
+    Terminate,
+}
+
+impl Signal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Signal::Interrupt => "SIGINT",
+            Signal::Terminate => "SIGTERM",
+        }
+    }
+
+    /// Exit status a shell reports for a process killed by this signal.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Signal::Interrupt => 130,
+            Signal::Terminate => 143,
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Installed SIGINT and SIGTERM handlers. Only Ctrl-C is observed on non-Unix
+/// targets.
+struct Signals {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(windows)]
+    ctrl_c: tokio::signal::windows::CtrlC,
+}
+
+impl Signals {
+    /// Installs the handlers; must be called from within a Tokio runtime.
+    fn listen() -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            Ok(Self {
+                interrupt: signal(SignalKind::interrupt())?,
+                terminate: signal(SignalKind::terminate())?,
+            })
+        }
+
+        #[cfg(windows)]
+        {
+            Ok(Self {
+                ctrl_c: tokio::signal::windows::ctrl_c()?,
+            })
+        }
+    }
+
+    async fn recv(&mut self) -> Signal {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.interrupt.recv() => Signal::Interrupt,
+                _ = self.terminate.recv() => Signal::Terminate,
+            }
+        }
+
+        #[cfg(windows)]
+        {
+            self.ctrl_c.recv().await;
+            Signal::Interrupt
+        }
+    }
+}
+
+/// Waits for SIGINT or SIGTERM. Only Ctrl-C is observed on non-Unix targets.
+pub async fn wait_for_signal() -> io::Result<Signal> {
+    Ok(Signals::listen()?.recv().await)
+}
+
+impl TelemetryGuard {
+    /// Waits for SIGINT or SIGTERM, then shuts down within the guard's timeout.
+    ///
+    /// If the handlers cannot be installed the guard is dropped, which still
+    /// shuts telemetry down, and the error is returned.
+    pub async fn shutdown_on_signal(self) -> io::Result<(Signal, ShutdownReport)> {
+        let signal = wait_for_signal().await?;
+        Ok((signal, self.shutdown_async().await))
+    }
+
+    /// Installs SIGINT and SIGTERM handlers and spawns a task that flushes on
+    /// either signal and then exits the process with the conventional status
+    /// for it.
+    ///
+    /// Must be called from within a Tokio runtime. If the handlers cannot be
+    /// installed nothing is spawned and the guard is handed back with the
+    /// error. An incomplete shutdown goes to the
+    /// [`TelemetryGuard::on_incomplete`] hook before the process exits.
+    #[allow(clippy::result_large_err)]
+    pub fn exit_on_signal(mut self) -> Result<JoinHandle<()>, (io::Error, TelemetryGuard)> {
+        let mut signals = match Signals::listen() {
+            Ok(signals) => signals,
+            Err(err) => return Err((err, self)),
+        };
+
+        Ok(tokio::spawn(async move {
+            let signal = signals.recv().await;
+            let report = self.take().shutdown_async(self.timeout()).await;
+            self.report_incomplete(&report);
+            std::process::exit(signal.exit_code());
+        }))
+    }
+}
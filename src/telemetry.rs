@@ -1,40 +1,154 @@
-use anyhow::Result;
-use opentelemetry::KeyValue;
 use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry::{Context, KeyValue};
 use opentelemetry_sdk::resource::Resource;
-use tracing::{Dispatch, Span, dispatcher};
+use opentelemetry_sdk::trace::Tracer;
+use std::fmt;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::{Dispatch, Span, Subscriber, dispatcher};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
-use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt};
-
-use crate::config::Config;
-use crate::logger::{self, LoggerProvider};
-use crate::meter::{self, MeterProvider};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry, layer::SubscriberExt};
+
+use crate::config::{Config, ConfigError};
+use crate::flush::{FlushReport, FlushUnsupported};
+use crate::logger::{
+    self, LayerFilters, LogFilterConfig, LogLevelHandle, LoggerProvider, TraceContextBridge,
+};
+use crate::meter::{self, MeterProvider, RuntimeMetricsHandle};
 use crate::profiler::{self, PyroscopeAgent};
+use crate::shutdown::{ComponentShutdown, ShutdownReport, ShutdownStatus, TelemetryGuard};
 use crate::tracer::{self, TracerProvider};
 
-const DEFAULT_LOG_FILTER_SUFFIX: &str = "otel::tracing=trace,axum_tracing_opentelemetry=trace";
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    Logger,
+    Tracer,
+    Meter,
+    Profiler,
+}
+
+impl Component {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Component::Logger => "logger",
+            Component::Tracer => "tracer",
+            Component::Meter => "meter",
+            Component::Profiler => "profiler",
+        }
+    }
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("invalid telemetry configuration: {0}")]
+    Config(#[from] ConfigError),
+    #[error("failed to set up {component}: {source}")]
+    Setup {
+        component: Component,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("a global tracing subscriber is already installed")]
+    SubscriberAlreadySet,
+    #[error("failed to install tracing subscriber: {0}")]
+    Subscriber(#[from] tracing::subscriber::SetGlobalDefaultError),
+    #[error("failed to watch the log filter file: {0}")]
+    FilterWatch(#[from] std::io::Error),
+    #[error("the subscriber filters already have a log level handle")]
+    LogLevelHandleExists,
+}
+
+impl TelemetryError {
+    /// The component whose setup failed, if the error came from one.
+    pub fn component(&self) -> Option<Component> {
+        match self {
+            TelemetryError::Setup { component, .. } => Some(*component),
+            _ => None,
+        }
+    }
+}
 
 pub struct Telemetry {
     pub logger: Option<LoggerProvider>,
     pub tracer: Option<TracerProvider>,
     pub meter: Option<MeterProvider>,
     pub profiler: Option<PyroscopeAgent>,
+    /// Keeps the Tokio runtime instruments on `meter` observing while held.
+    pub runtime_metrics: Option<RuntimeMetricsHandle>,
+    /// Replaces the installed subscriber's filter directives at runtime; `None`
+    /// when no subscriber was installed and `Telemetry::layer` has not been called.
+    pub log_level: Option<LogLevelHandle>,
+    /// What `Telemetry::layer` builds its layers from.
+    pub(crate) layer_config: LayerConfig,
+    /// Errors tolerated because of `Config::allow_partial` or a non-strict
+    /// subscriber install, including a log filter file that could not be watched.
+    pub failures: Vec<TelemetryError>,
 }
 
 impl Telemetry {
-    pub fn new(mut config: Config) -> Result<Self> {
+    /// Wraps providers built elsewhere so they get the same flush and shutdown
+    /// handling. `Telemetry::layer` uses the default log filters.
+    pub fn from_providers(
+        logger: Option<LoggerProvider>,
+        tracer: Option<TracerProvider>,
+        meter: Option<MeterProvider>,
+        profiler: Option<PyroscopeAgent>,
+    ) -> Self {
+        Self {
+            logger,
+            tracer,
+            meter,
+            profiler,
+            runtime_metrics: None,
+            log_level: None,
+            layer_config: LayerConfig::default(),
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn new(mut config: Config) -> Result<Self, TelemetryError> {
         config.apply_defaults();
         config.validate()?;
 
-        let resource = config.resource.build();
-
-        let logger = setup_logger(&config, &resource)?;
-        let tracer = setup_tracer(&config, &resource)?;
-        let meter = setup_meter(&config, &resource)?;
-        let profiler = setup_profiler(&config)?;
-
-        if let Err(err) = install_tracing_subscriber(&config, tracer.as_ref(), logger.as_ref()) {
-            eprintln!("failed to install tracing subscriber: {err}");
+        let resource = config.build_resource()?;
+        let mut failures = Vec::new();
+
+        let logger = tolerate(&config, &mut failures, setup_logger(&config, &resource))?;
+        let tracer = tolerate(&config, &mut failures, setup_tracer(&config, &resource))?;
+        let meter = tolerate(&config, &mut failures, setup_meter(&config, &resource))?;
+        let profiler = tolerate(&config, &mut failures, setup_profiler(&config))?;
+        let runtime_metrics = meter
+            .as_ref()
+            .filter(|_| config.meter.runtime.enabled)
+            .map(|provider| meter::register_runtime_metrics(provider, &config.meter.runtime));
+
+        let layer_config = LayerConfig::from(&config);
+        let log_level = match install_tracing_subscriber(
+            &config,
+            &layer_config,
+            tracer.as_ref(),
+            logger.as_ref(),
+        ) {
+            Ok(handle) => handle,
+            Err(err) if config.strict => return Err(err),
+            Err(err) => {
+                failures.push(err);
+                None
+            }
+        };
+        if let Some(handle) = &log_level
+            && let Err(err) = layer_config.watch(handle)
+        {
+            if config.strict {
+                return Err(err);
+            }
+            failures.push(err);
         }
 
         Ok(Self {
@@ -42,9 +156,39 @@ impl Telemetry {
             tracer,
             meter,
             profiler,
+            runtime_metrics,
+            log_level,
+            layer_config,
+            failures,
         })
     }
 
+    /// The OpenTelemetry span layer and log bridge, behind the filters from
+    /// `LogFilterConfig`, for adding to a subscriber the application installs
+    /// itself. Console output is left to that subscriber. Sets
+    /// `Telemetry::log_level` to a handle over the returned layer's filters, so
+    /// it fails with [`TelemetryError::LogLevelHandleExists`] once a subscriber
+    /// was installed or a layer was already built.
+    pub fn layer<S>(&mut self) -> Result<Box<dyn Layer<S> + Send + Sync>, TelemetryError>
+    where
+        S: Subscriber + Send + Sync + for<'a> LookupSpan<'a>,
+    {
+        if self.log_level.is_some() {
+            return Err(TelemetryError::LogLevelHandleExists);
+        }
+        let mut filters = self.layer_config.filters();
+        let tracer = self.layer_config.tracer(self.tracer.as_ref());
+        let layer = self
+            .layer_config
+            .otel_layer(tracer, self.logger.as_ref(), &mut filters);
+        let handle = filters.into_handle();
+        if let Err(err) = self.layer_config.watch(&handle) {
+            self.failures.push(err);
+        }
+        self.log_level = Some(handle);
+        Ok(Box::new(layer))
+    }
+
     pub fn shutdown(self) {
         if let Some(provider) = self.logger {
             logger::shutdown(provider);
@@ -60,6 +204,190 @@ impl Telemetry {
         }
     }
 
+    /// Flushes every running component in parallel without shutting it down.
+    /// A running profiler is reported as [`FlushUnsupported`], since the agent
+    /// uploads on its own schedule.
+    ///
+    /// Blocks until all flushes finish. Inside a Tokio runtime use
+    /// [`Telemetry::force_flush_async`] so the batch processors can make progress.
+    ///
+    /// [`FlushUnsupported`]: crate::FlushUnsupported
+    pub fn force_flush(&self) -> FlushReport {
+        let (logger, tracer, meter) = std::thread::scope(|scope| {
+            let logger = self
+                .logger
+                .as_ref()
+                .map(|provider| scope.spawn(|| logger::try_flush(provider)));
+            let tracer = self
+                .tracer
+                .as_ref()
+                .map(|provider| scope.spawn(|| tracer::try_flush(provider)));
+            let meter = self
+                .meter
+                .as_ref()
+                .map(|provider| scope.spawn(|| meter::try_flush(provider)));
+
+            (
+                logger.map(join_scoped),
+                tracer.map(join_scoped),
+                meter.map(join_scoped),
+            )
+        });
+
+        FlushReport {
+            logger,
+            tracer,
+            meter,
+            profiler: self.profiler_flush(),
+        }
+    }
+
+    /// Async counterpart of [`Telemetry::force_flush`] that runs the blocking
+    /// flushes on Tokio's blocking pool.
+    pub async fn force_flush_async(&self) -> FlushReport {
+        use tokio::task::spawn_blocking;
+
+        let logger = self
+            .logger
+            .clone()
+            .map(|provider| spawn_blocking(move || logger::try_flush(&provider)));
+        let tracer = self
+            .tracer
+            .clone()
+            .map(|provider| spawn_blocking(move || tracer::try_flush(&provider)));
+        let meter = self
+            .meter
+            .clone()
+            .map(|provider| spawn_blocking(move || meter::try_flush(&provider)));
+
+        FlushReport {
+            logger: join_flush(logger).await,
+            tracer: join_flush(tracer).await,
+            meter: join_flush(meter).await,
+            profiler: self.profiler_flush(),
+        }
+    }
+
+    fn profiler_flush(&self) -> Option<anyhow::Result<()>> {
+        self.profiler.as_ref().map(|_| Err(FlushUnsupported.into()))
+    }
+
+    /// Wraps the telemetry in a guard that shuts it down when dropped.
+    pub fn into_guard(self) -> TelemetryGuard {
+        TelemetryGuard::new(self)
+    }
+
+    /// Flushes and shuts down every component in parallel, giving up on those
+    /// still running when `timeout` elapses.
+    pub fn shutdown_with_timeout(self, timeout: Duration) -> ShutdownReport {
+        let start = Instant::now();
+        let deadline = start + timeout;
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let started = self.spawn_shutdowns(start, move |done| {
+            let _ = tx.send(done);
+        });
+
+        let mut finished = Vec::with_capacity(started.len());
+        while finished.len() < started.len() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(done) => finished.push(done),
+                Err(_) => break,
+            }
+        }
+
+        ShutdownReport::new(&started, finished, timeout)
+    }
+
+    /// Async counterpart of [`Telemetry::shutdown_with_timeout`] that does not
+    /// block the runtime while waiting.
+    pub async fn shutdown_async(self, timeout: Duration) -> ShutdownReport {
+        let start = Instant::now();
+        let deadline = tokio::time::Instant::from_std(start + timeout);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let started = self.spawn_shutdowns(start, move |done| {
+            let _ = tx.send(done);
+        });
+
+        let mut finished = Vec::with_capacity(started.len());
+        while finished.len() < started.len() {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(done)) => finished.push(done),
+                _ => break,
+            }
+        }
+
+        ShutdownReport::new(&started, finished, timeout)
+    }
+
+    /// Provider shutdowns block, and can hang on an unreachable collector, so
+    /// each one runs on a detached thread that reports back through `report`.
+    fn spawn_shutdowns(
+        self,
+        start: Instant,
+        report: impl Fn(ComponentShutdown) + Clone + Send + 'static,
+    ) -> Vec<Component> {
+        let mut started = Vec::new();
+        let mut spawn =
+            |component: Component, task: Box<dyn FnOnce() -> anyhow::Result<()> + Send>| {
+                let on_done = report.clone();
+                let run = move || {
+                    let status = match task() {
+                        Ok(()) => ShutdownStatus::Completed,
+                        Err(err) => ShutdownStatus::Failed(format!("{err:#}")),
+                    };
+                    on_done(ComponentShutdown {
+                        component,
+                        status,
+                        elapsed: start.elapsed(),
+                    });
+                };
+
+                started.push(component);
+                if let Err(err) = std::thread::Builder::new()
+                    .name(format!("o11y-shutdown-{component}"))
+                    .spawn(run)
+                {
+                    report(ComponentShutdown {
+                        component,
+                        status: ShutdownStatus::Failed(format!(
+                            "failed to spawn shutdown thread: {err}"
+                        )),
+                        elapsed: start.elapsed(),
+                    });
+                }
+            };
+
+        if let Some(provider) = self.logger {
+            spawn(
+                Component::Logger,
+                Box::new(move || logger::try_shutdown(provider)),
+            );
+        }
+        if let Some(provider) = self.tracer {
+            spawn(
+                Component::Tracer,
+                Box::new(move || tracer::try_shutdown(provider)),
+            );
+        }
+        if let Some(provider) = self.meter {
+            spawn(
+                Component::Meter,
+                Box::new(move || meter::try_shutdown(provider)),
+            );
+        }
+        if let Some(agent) = self.profiler {
+            spawn(
+                Component::Profiler,
+                Box::new(move || profiler::try_shutdown(agent)),
+            );
+        }
+
+        started
+    }
+
     pub fn has_logger(&self) -> bool {
         self.logger.is_some()
     }
@@ -75,87 +403,164 @@ impl Telemetry {
     pub fn has_profiler(&self) -> bool {
         self.profiler.is_some()
     }
+
+    pub fn is_partial(&self) -> bool {
+        self.failures.iter().any(|err| err.component().is_some())
+    }
+}
+
+/// Records a component failure instead of returning it when partial startup is allowed.
+fn tolerate<T>(
+    config: &Config,
+    failures: &mut Vec<TelemetryError>,
+    result: Result<Option<T>, TelemetryError>,
+) -> Result<Option<T>, TelemetryError> {
+    match result {
+        Err(err) if config.allow_partial => {
+            failures.push(err);
+            Ok(None)
+        }
+        result => result,
+    }
 }
 
-fn setup_logger(config: &Config, resource: &Resource) -> Result<Option<LoggerProvider>> {
-    logger::setup(&config.logger, resource)
+fn setup_error(component: Component) -> impl FnOnce(anyhow::Error) -> TelemetryError {
+    move |err| TelemetryError::Setup {
+        component,
+        source: err.into(),
+    }
+}
+
+fn setup_logger(
+    config: &Config,
+    resource: &Resource,
+) -> Result<Option<LoggerProvider>, TelemetryError> {
+    logger::setup(&config.logger, resource).map_err(setup_error(Component::Logger))
 }
 
-fn setup_tracer(config: &Config, resource: &Resource) -> Result<Option<TracerProvider>> {
+fn setup_tracer(
+    config: &Config,
+    resource: &Resource,
+) -> Result<Option<TracerProvider>, TelemetryError> {
     if config.tracer.use_global {
         tracer::init(&config.tracer, resource)
     } else {
         tracer::setup(&config.tracer, resource)
     }
+    .map_err(setup_error(Component::Tracer))
 }
 
-fn setup_meter(config: &Config, resource: &Resource) -> Result<Option<MeterProvider>> {
+fn setup_meter(
+    config: &Config,
+    resource: &Resource,
+) -> Result<Option<MeterProvider>, TelemetryError> {
     let provider = if config.meter.use_global {
-        meter::init(&config.meter, resource)?
+        meter::init(&config.meter, resource)
     } else {
-        meter::setup(&config.meter, resource)?
-    };
+        meter::setup(&config.meter, resource)
+    }
+    .map_err(setup_error(Component::Meter))?;
 
-    if config.meter.runtime.enabled && config.meter.use_global && provider.is_some() {
-        let meter_name = config.meter.service_name.clone();
-        if let Err(err) = meter::register_runtime_metrics(meter_name) {
-            eprintln!("failed to register runtime metrics: {err}");
-        }
+    if config.meter.system.enabled
+        && let Some(provider) = &provider
+    {
+        meter::register_system_metrics(provider, &config.meter.system);
     }
 
     Ok(provider)
 }
 
-fn setup_profiler(config: &Config) -> Result<Option<PyroscopeAgent>> {
-    profiler::setup(&config.profiler)
+fn setup_profiler(config: &Config) -> Result<Option<PyroscopeAgent>, TelemetryError> {
+    profiler::setup(&config.profiler).map_err(setup_error(Component::Profiler))
 }
 
 fn install_tracing_subscriber(
     config: &Config,
+    layer_config: &LayerConfig,
     tracer: Option<&TracerProvider>,
     logger: Option<&LoggerProvider>,
-) -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+) -> Result<Option<LogLevelHandle>, TelemetryError> {
+    if !config.install_subscriber {
+        return Ok(None);
+    }
     if dispatcher::has_been_set() {
-        return Ok(());
-    }
-
-    let fallback = fallback_log_filter(&config.resource.service_name);
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(fallback));
-    let fmt_layer = tracing_subscriber::fmt::layer()
-        .with_target(true)
-        .with_line_number(true)
-        .with_file(true)
-        .with_timer(tracing_subscriber::fmt::time::SystemTime);
-
-    let base = Registry::default().with(env_filter).with(fmt_layer);
-
-    let dispatch = match (tracer, logger) {
-        (Some(tp), Some(lp)) => {
-            let tracer = tp.tracer(config.tracer.service_name.clone());
-            let log_layer =
-                opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(lp);
-            Dispatch::new(
-                base.with(tracing_opentelemetry::layer().with_tracer(tracer))
-                    .with(log_layer),
-            )
-        }
-        (Some(tp), None) => {
-            let tracer = tp.tracer(config.tracer.service_name.clone());
-            Dispatch::new(base.with(tracing_opentelemetry::layer().with_tracer(tracer)))
-        }
-        (None, Some(lp)) => {
-            let log_layer =
-                opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(lp);
-            Dispatch::new(base.with(log_layer))
-        }
-        (None, None) => Dispatch::new(base),
-    };
+        return Err(TelemetryError::SubscriberAlreadySet);
+    }
+
+    let mut filters = layer_config.filters();
+    let tracer = layer_config.tracer(tracer);
+    let console_layer = logger::console::layer(&config.logger.console, tracer.clone())
+        .map(|layer| layer.with_filter(filters.console()));
+    let otel_layer = layer_config.otel_layer(tracer, logger, &mut filters);
 
-    dispatcher::set_global_default(dispatch)
+    dispatcher::set_global_default(Dispatch::new(
+        Registry::default().with(console_layer).with(otel_layer),
+    ))?;
+    Ok(Some(filters.into_handle()))
+}
+
+/// The parts of `Config` the subscriber layers are built from, kept by
+/// `Telemetry` for `Telemetry::layer`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LayerConfig {
+    service_name: String,
+    tracer_name: String,
+    filter: LogFilterConfig,
+}
+
+impl From<&Config> for LayerConfig {
+    fn from(config: &Config) -> Self {
+        Self {
+            service_name: config.resource.service_name.clone(),
+            tracer_name: config.tracer.service_name.clone(),
+            filter: config.logger.filter.clone(),
+        }
+    }
 }
 
-fn fallback_log_filter(service_name: &str) -> String {
-    format!("info,{service_name}=debug,{DEFAULT_LOG_FILTER_SUFFIX}")
+impl LayerConfig {
+    fn filters(&self) -> LayerFilters<'_> {
+        self.filter.build(&self.service_name)
+    }
+
+    fn tracer(&self, provider: Option<&TracerProvider>) -> Option<Tracer> {
+        provider.map(|provider| provider.tracer(self.tracer_name.clone()))
+    }
+
+    /// The span layer and log bridge for whichever providers are running, each
+    /// behind its own filter. Log records are stamped with the trace context
+    /// of the span `tracer` records the event in.
+    fn otel_layer<S>(
+        &self,
+        tracer: Option<Tracer>,
+        logger: Option<&LoggerProvider>,
+        filters: &mut LayerFilters<'_>,
+    ) -> Option<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + Send + Sync + for<'a> LookupSpan<'a>,
+    {
+        let spans = tracer.clone().map(|tracer| {
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer)
+                .with_filter(filters.spans())
+        });
+        let logs = logger.map(|provider| {
+            TraceContextBridge::new(provider, tracer).with_filter(filters.export())
+        });
+        match (spans, logs) {
+            (None, None) => None,
+            (spans, logs) => Some(Layer::and_then(spans, logs).boxed()),
+        }
+    }
+
+    /// Starts polling the configured filter file, if any. Runs after the handle
+    /// is stored so a failed watcher never loses it.
+    fn watch(&self, handle: &LogLevelHandle) -> Result<(), TelemetryError> {
+        if let Some(path) = &self.filter.watch_file {
+            handle.watch_file(path, self.filter.watch_interval)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -173,23 +578,40 @@ impl TraceContextInfo {
             KeyValue::new("trace_sampled", if self.sampled { "true" } else { "false" }),
         ]
     }
+
+    pub(crate) fn from_context(context: &Context) -> Option<Self> {
+        let span_ref = context.span();
+        let span_context = span_ref.span_context();
+
+        if !span_context.is_valid() {
+            return None;
+        }
+
+        Some(TraceContextInfo {
+            trace_id: span_context.trace_id().to_string(),
+            span_id: span_context.span_id().to_string(),
+            sampled: span_context.trace_flags().is_sampled(),
+        })
+    }
 }
 
 pub fn current_trace_context() -> Option<TraceContextInfo> {
-    let span = Span::current();
-    let context = span.context();
-    let span_ref = context.span();
-    let span_context = span_ref.span_context();
+    TraceContextInfo::from_context(&Span::current().context())
+}
 
-    if !span_context.is_valid() {
-        return None;
-    }
+fn join_scoped<T>(
+    handle: std::thread::ScopedJoinHandle<'_, anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("flush panicked")))
+}
 
-    Some(TraceContextInfo {
-        trace_id: span_context.trace_id().to_string(),
-        span_id: span_context.span_id().to_string(),
-        sampled: span_context.trace_flags().is_sampled(),
-    })
+async fn join_flush<T>(
+    task: Option<tokio::task::JoinHandle<anyhow::Result<T>>>,
+) -> Option<anyhow::Result<T>> {
+    let joined = task?.await;
+    Some(joined.unwrap_or_else(|err| Err(anyhow::anyhow!("flush task failed: {err}"))))
 }
 
 #[cfg(test)]
@@ -198,6 +620,8 @@ mod tests {
     use crate::{
         logger::LoggerConfig, meter::MeterConfig, profiler::ProfilerConfig, tracer::TracerConfig,
     };
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     #[test]
     fn test_telemetry_all_disabled() {
@@ -219,6 +643,315 @@ mod tests {
     fn test_telemetry_validation() {
         let config = Config::new("");
         let result = Telemetry::new(config);
+        assert!(matches!(
+            result,
+            Err(TelemetryError::Config(ConfigError::ServiceNameRequired))
+        ));
+    }
+
+    fn disabled_config(service: &str) -> Config {
+        Config::new(service)
+            .with_logger(LoggerConfig::new(service).enabled(false))
+            .with_tracer(TracerConfig::new(service).enabled(false))
+            .with_meter(MeterConfig::new(service).enabled(false))
+            .with_profiler(ProfilerConfig::new(service).enabled(false))
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_existing_subscriber() {
+        // Whichever test runs first installs the global subscriber.
+        Telemetry::new(disabled_config("test-service")).unwrap();
+
+        let result = Telemetry::new(disabled_config("test-service").strict(true));
+        assert!(matches!(result, Err(TelemetryError::SubscriberAlreadySet)));
+
+        let tele = Telemetry::new(disabled_config("test-service")).unwrap();
+        assert!(matches!(
+            tele.failures.as_slice(),
+            [TelemetryError::SubscriberAlreadySet]
+        ));
+    }
+
+    #[test]
+    fn test_skipping_subscriber_install() {
+        let config = disabled_config("test-service")
+            .install_subscriber(false)
+            .strict(true);
+
+        let mut tele = Telemetry::new(config).unwrap();
+        assert!(tele.failures.is_empty());
+        assert!(tele.log_level.is_none());
+
+        let _layer = tele.layer::<tracing_subscriber::Registry>().unwrap();
+        assert!(tele.log_level.is_some());
+        assert!(matches!(
+            tele.layer::<tracing_subscriber::Registry>(),
+            Err(TelemetryError::LogLevelHandleExists)
+        ));
+    }
+
+    #[cfg(feature = "grpc")]
+    #[test]
+    fn test_partial_mode_keeps_running_components() {
+        let service = "test-service";
+        let broken_tracer = TracerConfig::new(service)
+            .with_endpoint("http://localhost:4317")
+            .with_protocol(crate::Protocol::Grpc)
+            .with_credentials(crate::Credentials::new().with_header("bad header", "value"));
+
+        let result = Telemetry::new(disabled_config(service).with_tracer(broken_tracer.clone()));
+        assert!(matches!(
+            result,
+            Err(TelemetryError::Setup {
+                component: Component::Tracer,
+                ..
+            })
+        ));
+
+        let tele = Telemetry::new(
+            disabled_config(service)
+                .with_tracer(broken_tracer)
+                .allow_partial(true)
+                .install_subscriber(false),
+        )
+        .unwrap();
+        assert!(!tele.has_tracer());
+        assert!(tele.is_partial());
+        assert_eq!(tele.failures.len(), 1);
+        assert_eq!(tele.failures[0].component(), Some(Component::Tracer));
+    }
+
+    #[derive(Debug)]
+    struct SlowProcessor {
+        flush_delay: Duration,
+        shut_down: Arc<AtomicBool>,
+    }
+
+    impl opentelemetry_sdk::trace::SpanProcessor for SlowProcessor {
+        fn on_start(
+            &self,
+            _span: &mut opentelemetry_sdk::trace::Span,
+            _cx: &opentelemetry::Context,
+        ) {
+        }
+
+        fn on_end(&self, _span: opentelemetry_sdk::export::trace::SpanData) {}
+
+        fn force_flush(&self) -> opentelemetry::trace::TraceResult<()> {
+            std::thread::sleep(self.flush_delay);
+            Ok(())
+        }
+
+        fn shutdown(&self) -> opentelemetry::trace::TraceResult<()> {
+            self.shut_down.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn tracked_telemetry(flush_delay: Duration) -> (Telemetry, Arc<AtomicBool>) {
+        let shut_down = Arc::new(AtomicBool::new(false));
+        let processor = SlowProcessor {
+            flush_delay,
+            shut_down: shut_down.clone(),
+        };
+        let telemetry = Telemetry::from_providers(
+            Some(LoggerProvider::builder().build()),
+            Some(
+                TracerProvider::builder()
+                    .with_span_processor(processor)
+                    .build(),
+            ),
+            None,
+            None,
+        );
+        (telemetry, shut_down)
+    }
+
+    fn telemetry_with_tracer(flush_delay: Duration) -> Telemetry {
+        tracked_telemetry(flush_delay).0
+    }
+
+    #[test]
+    fn test_shutdown_with_timeout_reports_each_component() {
+        let report =
+            telemetry_with_tracer(Duration::ZERO).shutdown_with_timeout(Duration::from_secs(5));
+
+        assert!(report.is_success(), "{report}");
+        assert_eq!(
+            report.status(Component::Logger),
+            Some(&ShutdownStatus::Completed)
+        );
+        assert_eq!(
+            report.status(Component::Tracer),
+            Some(&ShutdownStatus::Completed)
+        );
+        assert_eq!(report.status(Component::Meter), None);
+    }
+
+    #[test]
+    fn test_shutdown_with_timeout_gives_up_on_hung_component() {
+        let started = Instant::now();
+        let report = telemetry_with_tracer(Duration::from_secs(5))
+            .shutdown_with_timeout(Duration::from_millis(100));
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(!report.is_success());
+        assert_eq!(
+            report.status(Component::Logger),
+            Some(&ShutdownStatus::Completed)
+        );
+        assert_eq!(
+            report.status(Component::Tracer),
+            Some(&ShutdownStatus::TimedOut)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_async_enforces_deadline() {
+        let report = telemetry_with_tracer(Duration::from_secs(5))
+            .shutdown_async(Duration::from_millis(100))
+            .await;
+
+        assert_eq!(
+            report.status(Component::Tracer),
+            Some(&ShutdownStatus::TimedOut)
+        );
+
+        let report = Telemetry::new(disabled_config("test-service"))
+            .unwrap()
+            .shutdown_async(Duration::from_secs(1))
+            .await;
+        assert!(report.components.is_empty());
+        assert_eq!(report.to_string(), "no components running");
+    }
+
+    #[test]
+    fn test_guard_shuts_down_on_drop() {
+        let (telemetry, shut_down) = tracked_telemetry(Duration::ZERO);
+        let guard = telemetry.into_guard().with_timeout(Duration::from_secs(5));
+        assert!(guard.has_tracer());
+
+        drop(guard);
+
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_guard_reports_incomplete_shutdown_to_hook() {
+        let incomplete = Arc::new(AtomicBool::new(false));
+        let seen = incomplete.clone();
+        let guard = telemetry_with_tracer(Duration::from_secs(5))
+            .into_guard()
+            .with_timeout(Duration::from_millis(100))
+            .on_incomplete(move |report| {
+                assert_eq!(
+                    report.status(Component::Tracer),
+                    Some(&ShutdownStatus::TimedOut)
+                );
+                seen.store(true, Ordering::SeqCst);
+            });
+
+        drop(guard);
+
+        assert!(incomplete.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_guard_shuts_down_during_panic() {
+        let (telemetry, shut_down) = tracked_telemetry(Duration::ZERO);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+            let _guard = telemetry.into_guard();
+            panic!("request handler failed");
+        }));
+
         assert!(result.is_err());
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_guard_into_inner_disarms() {
+        let (telemetry, shut_down) = tracked_telemetry(Duration::ZERO);
+
+        let telemetry = telemetry.into_guard().into_inner();
+        assert!(!shut_down.load(Ordering::SeqCst));
+
+        let report = telemetry.into_guard().shutdown();
+        assert!(report.is_success(), "{report}");
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[derive(Debug)]
+    struct FailingProcessor;
+
+    impl opentelemetry_sdk::trace::SpanProcessor for FailingProcessor {
+        fn on_start(
+            &self,
+            _span: &mut opentelemetry_sdk::trace::Span,
+            _cx: &opentelemetry::Context,
+        ) {
+        }
+
+        fn on_end(&self, _span: opentelemetry_sdk::export::trace::SpanData) {}
+
+        fn force_flush(&self) -> opentelemetry::trace::TraceResult<()> {
+            Err(opentelemetry::trace::TraceError::Other(
+                "collector unreachable".into(),
+            ))
+        }
+
+        fn shutdown(&self) -> opentelemetry::trace::TraceResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_force_flush_keeps_components_running() {
+        let (telemetry, shut_down) = tracked_telemetry(Duration::ZERO);
+
+        let report = telemetry.force_flush();
+
+        assert!(report.is_success(), "{report}");
+        assert!(matches!(report.logger, Some(Ok(()))));
+        assert!(matches!(report.tracer, Some(Ok(()))));
+        assert!(report.meter.is_none());
+        assert!(report.profiler.is_none());
+        assert!(!shut_down.load(Ordering::SeqCst));
+        assert!(telemetry.has_logger() && telemetry.has_tracer());
+    }
+
+    #[test]
+    fn test_force_flush_reports_failures_per_signal() {
+        let telemetry = Telemetry::from_providers(
+            Some(LoggerProvider::builder().build()),
+            Some(
+                TracerProvider::builder()
+                    .with_span_processor(FailingProcessor)
+                    .build(),
+            ),
+            None,
+            None,
+        );
+
+        let report = telemetry.force_flush();
+
+        assert!(!report.is_success());
+        assert!(matches!(report.result(Component::Logger), Some(Ok(()))));
+        assert!(matches!(report.result(Component::Tracer), Some(Err(_))));
+        assert_eq!(
+            report.to_string(),
+            "logger: ok, tracer: failed to flush tracer provider: collector unreachable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_force_flush_async() {
+        let (telemetry, shut_down) = tracked_telemetry(Duration::from_millis(10));
+
+        let report = telemetry.force_flush_async().await;
+
+        assert!(report.is_success(), "{report}");
+        assert_eq!(report.results().count(), 2);
+        assert!(!shut_down.load(Ordering::SeqCst));
     }
 }
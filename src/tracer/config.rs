@@ -1,9 +1,17 @@
 use crate::auth::Credentials;
+use crate::batch::{BatchConfig, BatchError};
+use crate::local::{LocalError, LocalExporter};
+use crate::otlp::{ExporterTarget, Protocol, TargetError};
+use crate::tracer::propagation::Propagator;
+use crate::tracer::sampler::{SamplerConfig, SamplerError};
+use opentelemetry_sdk::trace::SpanLimits;
 use std::time::Duration;
 use thiserror::Error;
 
+const DEFAULT_SCHEDULED_DELAY: Duration = Duration::from_secs(5);
 const DEFAULT_SAMPLE_RATIO: f64 = 1.0;
 const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_SPAN_LIMIT: u32 = 128;
 
 #[derive(Debug, Error)]
 pub enum TracerError {
@@ -11,28 +19,125 @@ pub enum TracerError {
     ServiceNameRequired,
     #[error("tracer endpoint is required when enabled")]
     EndpointRequired,
+    #[error("tracer protocol {0} requires the `{feature}` cargo feature", feature = .0.feature())]
+    UnsupportedProtocol(Protocol),
+    #[error("tracer local exporter: {0}")]
+    Local(#[from] LocalError),
+    #[error("tracer target {index}: {source}")]
+    Target { index: usize, source: TargetError },
+    #[error("tracer batch configuration error: {0}")]
+    Batch(#[from] BatchError),
+    #[error("tracer sampler configuration error: {0}")]
+    Sampler(#[from] SamplerError),
+}
+
+/// Where spans go. OTLP sends them to `TracerConfig::endpoint`; `Local`
+/// writes each one as it ends to stdout or a file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum TracerExporter {
+    #[default]
+    Otlp,
+    Local(LocalExporter),
+}
+
+impl From<LocalExporter> for TracerExporter {
+    fn from(local: LocalExporter) -> Self {
+        TracerExporter::Local(local)
+    }
+}
+
+/// Caps on what a single span keeps; anything added past a limit is dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SpanLimitsConfig {
+    pub max_attributes_per_span: u32,
+    pub max_events_per_span: u32,
+    pub max_links_per_span: u32,
+    pub max_attributes_per_event: u32,
+    pub max_attributes_per_link: u32,
+}
+
+impl Default for SpanLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_attributes_per_span: DEFAULT_SPAN_LIMIT,
+            max_events_per_span: DEFAULT_SPAN_LIMIT,
+            max_links_per_span: DEFAULT_SPAN_LIMIT,
+            max_attributes_per_event: DEFAULT_SPAN_LIMIT,
+            max_attributes_per_link: DEFAULT_SPAN_LIMIT,
+        }
+    }
+}
+
+impl From<SpanLimitsConfig> for SpanLimits {
+    fn from(limits: SpanLimitsConfig) -> Self {
+        SpanLimits {
+            max_events_per_span: limits.max_events_per_span,
+            max_attributes_per_span: limits.max_attributes_per_span,
+            max_links_per_span: limits.max_links_per_span,
+            max_attributes_per_event: limits.max_attributes_per_event,
+            max_attributes_per_link: limits.max_attributes_per_link,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct TracerConfig {
     pub enabled: bool,
+    pub exporter: TracerExporter,
     pub endpoint: Option<String>,
+    /// Uses `endpoint` as the full URL instead of appending the signal path,
+    /// as `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` requires.
+    pub exact_endpoint: bool,
+    pub protocol: Protocol,
     pub service_name: String,
     pub sample_ratio: f64,
+    /// Overrides `sample_ratio`, which otherwise samples root spans by trace id
+    /// and follows the parent's decision for everything else.
+    pub sampler: Option<SamplerConfig>,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
     pub export_timeout: Duration,
+    pub batch: BatchConfig,
+    pub span_limits: SpanLimitsConfig,
     pub credentials: Credentials,
+    /// Additional [`ExporterTarget`]s.
+    pub targets: Vec<ExporterTarget>,
+    /// Formats combined into the global text map propagator. An empty list
+    /// leaves the global propagator untouched.
+    pub propagators: Vec<Propagator>,
+    pub install_propagator: bool,
     pub use_global: bool,
 }
 
+impl Default for TracerConfig {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
 impl TracerConfig {
     pub fn new(service_name: impl Into<String>) -> Self {
         Self {
             enabled: true,
+            exporter: TracerExporter::default(),
             endpoint: None,
+            exact_endpoint: false,
+            protocol: Protocol::Grpc,
             service_name: service_name.into(),
             sample_ratio: DEFAULT_SAMPLE_RATIO,
+            sampler: None,
             export_timeout: DEFAULT_EXPORT_TIMEOUT,
+            batch: BatchConfig::default().with_scheduled_delay(DEFAULT_SCHEDULED_DELAY),
+            span_limits: SpanLimitsConfig::default(),
             credentials: Credentials::new(),
+            targets: Vec::new(),
+            propagators: vec![Propagator::TraceContext, Propagator::Baggage],
+            install_propagator: true,
             use_global: false,
         }
     }
@@ -42,26 +147,75 @@ impl TracerConfig {
         self
     }
 
+    pub fn with_exporter(mut self, exporter: impl Into<TracerExporter>) -> Self {
+        self.exporter = exporter.into();
+        self
+    }
+
     pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
         self.endpoint = Some(endpoint.into());
         self
     }
 
+    /// Sets a full signal URL that is dialled without appending a path.
+    pub fn with_signal_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self.exact_endpoint = true;
+        self
+    }
+
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
     pub fn with_sample_ratio(mut self, ratio: f64) -> Self {
         self.sample_ratio = ratio;
         self
     }
 
+    pub fn with_sampler(mut self, sampler: SamplerConfig) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
     pub fn with_export_timeout(mut self, timeout: Duration) -> Self {
         self.export_timeout = timeout;
         self
     }
 
+    pub fn with_batch(mut self, batch: BatchConfig) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    pub fn with_span_limits(mut self, span_limits: SpanLimitsConfig) -> Self {
+        self.span_limits = span_limits;
+        self
+    }
+
     pub fn with_credentials(mut self, credentials: Credentials) -> Self {
         self.credentials = credentials;
         self
     }
 
+    /// Adds an [`ExporterTarget`].
+    pub fn with_target(mut self, target: ExporterTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    pub fn with_propagators(mut self, propagators: impl IntoIterator<Item = Propagator>) -> Self {
+        self.propagators = propagators.into_iter().collect();
+        self
+    }
+
+    /// Whether `setup` replaces the global text map propagator.
+    pub fn install_propagator(mut self, install: bool) -> Self {
+        self.install_propagator = install;
+        self
+    }
+
     pub fn use_global(mut self, use_global: bool) -> Self {
         self.use_global = use_global;
         self
@@ -71,9 +225,10 @@ impl TracerConfig {
         if self.sample_ratio <= 0.0 {
             self.sample_ratio = DEFAULT_SAMPLE_RATIO;
         }
-        if self.export_timeout.as_secs() == 0 {
+        if self.export_timeout.is_zero() {
             self.export_timeout = DEFAULT_EXPORT_TIMEOUT;
         }
+        self.batch.apply_defaults(DEFAULT_SCHEDULED_DELAY);
     }
 
     pub fn validate(&self) -> Result<(), TracerError> {
@@ -83,8 +238,25 @@ impl TracerConfig {
         if self.service_name.is_empty() {
             return Err(TracerError::ServiceNameRequired);
         }
-        if self.endpoint.is_none() {
-            return Err(TracerError::EndpointRequired);
+        match &self.exporter {
+            TracerExporter::Otlp => {
+                if self.endpoint.is_none() {
+                    return Err(TracerError::EndpointRequired);
+                }
+                if !self.protocol.is_supported() {
+                    return Err(TracerError::UnsupportedProtocol(self.protocol));
+                }
+            }
+            TracerExporter::Local(local) => local.validate()?,
+        }
+        for (index, target) in self.targets.iter().enumerate() {
+            target
+                .validate(self.protocol)
+                .map_err(|source| TracerError::Target { index, source })?;
+        }
+        self.batch.validate()?;
+        if let Some(sampler) = &self.sampler {
+            sampler.validate()?;
         }
         Ok(())
     }
@@ -109,6 +281,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_tracer_config_validates_local_exporter() {
+        let config = TracerConfig::new("test").with_exporter(LocalExporter::file(""));
+
+        let expected = if cfg!(feature = "local") {
+            LocalError::FilePathRequired
+        } else {
+            LocalError::Unsupported
+        };
+        assert!(matches!(config.validate(), Err(TracerError::Local(e)) if e == expected));
+    }
+
     #[test]
     fn test_tracer_config_applies_defaults() {
         let mut config = TracerConfig::new("test");
@@ -131,4 +315,52 @@ mod tests {
         assert_eq!(config.endpoint.unwrap(), "http://localhost:4317");
         assert_eq!(config.sample_ratio, 0.5);
     }
+
+    #[test]
+    fn test_tracer_config_protocol() {
+        let config = TracerConfig::new("my-service");
+        assert_eq!(config.protocol, Protocol::Grpc);
+
+        let config = config.with_protocol(Protocol::HttpProtobuf);
+        assert_eq!(config.protocol, Protocol::HttpProtobuf);
+    }
+
+    #[test]
+    fn test_tracer_config_default_propagators() {
+        let config = TracerConfig::new("my-service");
+
+        assert_eq!(
+            config.propagators,
+            vec![Propagator::TraceContext, Propagator::Baggage]
+        );
+        assert!(config.install_propagator);
+    }
+
+    #[test]
+    fn test_tracer_config_validates_sampler() {
+        let config = TracerConfig::new("test")
+            .with_endpoint("http://localhost:4317")
+            .with_sampler(SamplerConfig::rate_limited(-1.0));
+
+        assert!(matches!(
+            config.validate(),
+            Err(TracerError::Sampler(SamplerError::InvalidRate(_)))
+        ));
+    }
+
+    #[test]
+    fn test_tracer_config_validates_targets() {
+        let config = TracerConfig::new("test")
+            .with_endpoint("http://localhost:4317")
+            .with_target(ExporterTarget::new("http://backup:4317"))
+            .with_target(ExporterTarget::default());
+
+        assert!(matches!(
+            config.validate(),
+            Err(TracerError::Target {
+                index: 1,
+                source: TargetError::EndpointRequired
+            })
+        ));
+    }
 }
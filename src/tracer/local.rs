@@ -0,0 +1,202 @@
+//! Writes finished spans to stdout or a file.
+
+use anyhow::{Context, Result};
+use opentelemetry::trace::{SpanId, SpanKind, Status, TraceError};
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
+use opentelemetry_proto::transform::trace::tonic::group_spans_by_resource_and_scope;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use std::fmt::Write as _;
+use std::future::{Future, ready};
+use std::pin::Pin;
+
+use crate::local::{LocalExporter, LocalFormat, LocalWriter, text};
+
+#[derive(Debug)]
+pub(crate) struct LocalSpanExporter {
+    format: LocalFormat,
+    writer: LocalWriter,
+    resource: Resource,
+}
+
+impl LocalSpanExporter {
+    pub(crate) fn new(config: &LocalExporter) -> Result<Self> {
+        Ok(Self {
+            format: config.format,
+            writer: LocalWriter::open(&config.output)
+                .context("failed to open the local span exporter output")?,
+            resource: Resource::empty(),
+        })
+    }
+
+    fn encode(&self, batch: Vec<SpanData>) -> Result<String, TraceError> {
+        match self.format {
+            LocalFormat::Pretty => Ok(self.pretty(&batch)),
+            LocalFormat::OtlpJson => {
+                let resource = ResourceAttributesWithSchema::from(&self.resource);
+                let request = ExportTraceServiceRequest {
+                    resource_spans: group_spans_by_resource_and_scope(batch, &resource),
+                };
+                let mut json =
+                    serde_json::to_string(&request).map_err(|e| TraceError::Other(Box::new(e)))?;
+                json.push('\n');
+                Ok(json)
+            }
+        }
+    }
+
+    fn pretty(&self, batch: &[SpanData]) -> String {
+        let service = text::service_name(&self.resource);
+        let mut out = String::new();
+        for span in batch {
+            let duration = span
+                .end_time
+                .duration_since(span.start_time)
+                .unwrap_or_default();
+            let _ = write!(
+                out,
+                "{} SPAN  [{service}] {} {duration:?}",
+                text::timestamp(span.end_time),
+                span.name
+            );
+            text::field(
+                &mut out,
+                "trace_id",
+                &span.span_context.trace_id().to_string(),
+            );
+            text::field(
+                &mut out,
+                "span_id",
+                &span.span_context.span_id().to_string(),
+            );
+            if span.parent_span_id != SpanId::INVALID {
+                text::field(&mut out, "parent_span_id", &span.parent_span_id.to_string());
+            }
+            text::field(&mut out, "kind", kind(&span.span_kind));
+            match &span.status {
+                Status::Unset => {}
+                Status::Ok => text::field(&mut out, "status", "ok"),
+                Status::Error { description } => {
+                    text::field(&mut out, "status", "error");
+                    text::field(&mut out, "error", description);
+                }
+            }
+            text::field(&mut out, "scope", span.instrumentation_scope.name());
+            text::attributes(
+                &mut out,
+                span.attributes.iter().map(|kv| (&kv.key, &kv.value)),
+            );
+            out.push('\n');
+
+            for event in span.events.iter() {
+                let _ = write!(
+                    out,
+                    "    {} EVENT {}",
+                    text::timestamp(event.timestamp),
+                    event.name
+                );
+                text::attributes(
+                    &mut out,
+                    event.attributes.iter().map(|kv| (&kv.key, &kv.value)),
+                );
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+fn kind(kind: &SpanKind) -> &'static str {
+    match kind {
+        SpanKind::Client => "client",
+        SpanKind::Server => "server",
+        SpanKind::Producer => "producer",
+        SpanKind::Consumer => "consumer",
+        SpanKind::Internal => "internal",
+    }
+}
+
+impl SpanExporter for LocalSpanExporter {
+    fn export(
+        &mut self,
+        batch: Vec<SpanData>,
+    ) -> Pin<Box<dyn Future<Output = ExportResult> + Send + 'static>> {
+        let result = self.encode(batch).and_then(|text| {
+            self.writer
+                .write(&text)
+                .map_err(|e| TraceError::Other(Box::new(e)))
+        });
+        Box::pin(ready(result))
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::KeyValue;
+    use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+    use opentelemetry_sdk::trace::TracerProvider;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn spans_in(format: LocalFormat) -> String {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("o11y-spans-{nanos}.log"));
+        let config = LocalExporter::file(&path).with_format(format);
+        let provider = TracerProvider::builder()
+            .with_resource(Resource::new([KeyValue::new("service.name", "checkout")]))
+            .with_simple_exporter(LocalSpanExporter::new(&config).unwrap())
+            .build();
+
+        let mut span = provider.tracer("test_tracer").start("charge card");
+        span.set_attribute(KeyValue::new("amount", 42));
+        span.add_event("retry", vec![KeyValue::new("attempt", 2)]);
+        span.set_status(Status::error("card declined"));
+        span.end();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        written
+    }
+
+    #[test]
+    fn test_pretty_spans() {
+        let written = spans_in(LocalFormat::Pretty);
+        let lines: Vec<&str> = written.lines().collect();
+
+        assert_eq!(lines.len(), 2, "{written}");
+        assert!(
+            lines[0].contains(" SPAN  [checkout] charge card "),
+            "{written}"
+        );
+        assert!(lines[0].contains(" kind=internal status=error error=\"card declined\""));
+        assert!(
+            lines[0].contains(" scope=test_tracer amount=42"),
+            "{written}"
+        );
+        assert!(!lines[0].contains("parent_span_id"));
+        assert!(lines[1].starts_with("    ") && lines[1].ends_with(" EVENT retry attempt=2"));
+    }
+
+    #[test]
+    fn test_otlp_json_spans() {
+        let written = spans_in(LocalFormat::OtlpJson);
+        let request: serde_json::Value = serde_json::from_str(written.trim_end()).unwrap();
+
+        let resource_spans = &request["resourceSpans"][0];
+        assert_eq!(
+            resource_spans["resource"]["attributes"][0]["value"]["stringValue"],
+            "checkout"
+        );
+        let span = &resource_spans["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["name"], "charge card");
+        assert_eq!(span["traceId"].as_str().unwrap().len(), 32);
+    }
+}
@@ -1,48 +1,133 @@
 mod config;
+#[cfg(feature = "local")]
+mod local;
+mod propagation;
+mod sampler;
 
-pub use config::{TracerConfig, TracerError};
+pub use config::{SpanLimitsConfig, TracerConfig, TracerError, TracerExporter};
+pub use propagation::{ParsePropagatorError, Propagator, composite_propagator};
+pub use sampler::{ParentBasedConfig, RuleBasedConfig, SamplerConfig, SamplerError, SamplingRule};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use opentelemetry::global;
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
 use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "grpc")]
+use opentelemetry_otlp::WithTonicConfig;
 use opentelemetry_sdk::{
-    propagation::TraceContextPropagator,
     resource::Resource,
-    trace::{RandomIdGenerator, Sampler, TracerProvider as SdkTracerProvider},
+    runtime::Tokio,
+    trace::{BatchSpanProcessor, RandomIdGenerator, Sampler, TracerProvider as SdkTracerProvider},
 };
 
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
+use crate::otlp::Protocol;
+use crate::otlp::{self, Destination};
+use sampler::BoxedSampler;
+
 pub type TracerProvider = SdkTracerProvider;
 
+const TRACES_PATH: &str = "/v1/traces";
+
 pub fn setup(config: &TracerConfig, resource: &Resource) -> Result<Option<TracerProvider>> {
     if !config.enabled {
         return Ok(None);
     }
 
-    let endpoint = config
-        .endpoint
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("tracer endpoint is required when enabled"))?;
-
-    global::set_text_map_propagator(TraceContextPropagator::new());
-
-    let exporter_builder = opentelemetry_otlp::SpanExporter::builder()
-        .with_tonic()
-        .with_endpoint(endpoint.clone());
-
-    let exporter = exporter_builder.build()?;
+    if config.install_propagator && !config.propagators.is_empty() {
+        global::set_text_map_propagator(composite_propagator(&config.propagators));
+    }
 
-    let sampler = sampler_from_ratio(config.sample_ratio);
+    let sampler = match &config.sampler {
+        Some(sampler) => sampler.build(),
+        None => BoxedSampler::parent_based(sampler_from_ratio(config.sample_ratio)),
+    };
 
-    let provider = SdkTracerProvider::builder()
-        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+    let mut builder = SdkTracerProvider::builder()
         .with_resource(resource.clone())
         .with_id_generator(RandomIdGenerator::default())
         .with_sampler(sampler)
-        .build();
+        .with_span_limits(config.span_limits.into());
+
+    let primary = match &config.exporter {
+        TracerExporter::Otlp => {
+            let endpoint = config
+                .endpoint
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("tracer endpoint is required when enabled"))?;
+            Some(Destination::new(
+                endpoint,
+                config.protocol,
+                config.export_timeout,
+                &config.credentials,
+                (!config.exact_endpoint).then_some(TRACES_PATH),
+            ))
+        }
+        #[cfg(feature = "local")]
+        TracerExporter::Local(local) => {
+            builder = builder.with_simple_exporter(local::LocalSpanExporter::new(local)?);
+            None
+        }
+        #[cfg(not(feature = "local"))]
+        TracerExporter::Local(_) => {
+            anyhow::bail!("tracer local exporter requires the `local` cargo feature")
+        }
+    };
+    let targets = config
+        .targets
+        .iter()
+        .map(|target| target.destination(config.protocol, config.export_timeout, TRACES_PATH));
+
+    for destination in primary.into_iter().chain(targets) {
+        builder = builder.with_span_processor(otlp_processor(config, &destination)?);
+    }
+    let provider = builder.build();
 
     Ok(Some(provider))
 }
 
+#[cfg(any(feature = "grpc", feature = "http-proto", feature = "http-json"))]
+fn otlp_processor(
+    config: &TracerConfig,
+    destination: &Destination<'_>,
+) -> Result<BatchSpanProcessor<Tokio>> {
+    let exporter = match destination.protocol {
+        #[cfg(feature = "grpc")]
+        Protocol::Grpc => opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(&destination.endpoint)
+            .with_timeout(destination.timeout)
+            .with_metadata(destination.credentials.metadata_map()?)
+            .build(),
+        #[cfg(any(feature = "http-proto", feature = "http-json"))]
+        Protocol::HttpProtobuf | Protocol::HttpJson => {
+            let exporter_builder = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(&destination.endpoint)
+                .with_timeout(destination.timeout)
+                .with_protocol(destination.protocol.into());
+            otlp::with_http_headers(exporter_builder, destination.credentials).build()
+        }
+        #[allow(unreachable_patterns)]
+        protocol => return Err(otlp::unsupported(protocol)),
+    }
+    .with_context(|| format!("failed to build span exporter for {}", destination.endpoint))?;
+
+    let processor = BatchSpanProcessor::builder(exporter, Tokio)
+        .with_batch_config(config.batch.span_config(destination.timeout))
+        .build();
+
+    Ok(processor)
+}
+
+#[cfg(not(any(feature = "grpc", feature = "http-proto", feature = "http-json")))]
+fn otlp_processor(
+    _: &TracerConfig,
+    destination: &Destination<'_>,
+) -> Result<BatchSpanProcessor<Tokio>> {
+    Err(otlp::unsupported(destination.protocol))
+}
+
 pub fn init(config: &TracerConfig, resource: &Resource) -> Result<Option<TracerProvider>> {
     let provider = setup(config, resource)?;
 
@@ -61,6 +146,25 @@ pub fn shutdown(provider: TracerProvider) {
     }
 }
 
+/// Flushes buffered spans and shuts the provider down, reporting the first failure.
+pub fn try_shutdown(provider: TracerProvider) -> Result<()> {
+    let flushed = try_flush(&provider);
+    provider
+        .shutdown()
+        .context("failed to shut down tracer provider")?;
+    flushed
+}
+
+/// Flushes buffered spans without shutting the provider down.
+pub fn try_flush(provider: &TracerProvider) -> Result<()> {
+    provider
+        .force_flush()
+        .into_iter()
+        .collect::<Result<Vec<()>, _>>()
+        .context("failed to flush tracer provider")?;
+    Ok(())
+}
+
 fn sampler_from_ratio(ratio: f64) -> Sampler {
     if ratio <= 0.0 {
         Sampler::AlwaysOff
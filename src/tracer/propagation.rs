@@ -0,0 +1,620 @@
+use opentelemetry::Context;
+use opentelemetry::propagation::{
+    Extractor, Injector, TextMapCompositePropagator, TextMapPropagator,
+    text_map_propagator::FieldIter,
+};
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+const B3_SINGLE_HEADER: &str = "b3";
+const B3_TRACE_ID_HEADER: &str = "x-b3-traceid";
+const B3_SPAN_ID_HEADER: &str = "x-b3-spanid";
+const B3_SAMPLED_HEADER: &str = "x-b3-sampled";
+const B3_FLAGS_HEADER: &str = "x-b3-flags";
+const JAEGER_HEADER: &str = "uber-trace-id";
+const XRAY_HEADER: &str = "x-amzn-trace-id";
+
+/// Marks the remote parent with this span id as having left the sampling
+/// decision to us. Kept in the extracted `Context` because the trace flags are
+/// copied onto every child span and exported as-is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DeferredSampling(SpanId);
+
+#[derive(Debug, Error)]
+#[error("unknown propagator {0:?}, expected tracecontext, baggage, b3, b3multi, jaeger or xray")]
+pub struct ParsePropagatorError(String);
+
+/// Context propagation formats, named as in `OTEL_PROPAGATORS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Propagator {
+    /// W3C `traceparent` and `tracestate`.
+    TraceContext,
+    /// W3C `baggage`.
+    Baggage,
+    /// Zipkin B3 single `b3` header.
+    B3,
+    /// Zipkin B3 `X-B3-*` headers.
+    B3Multi,
+    /// Jaeger `uber-trace-id`.
+    Jaeger,
+    /// AWS X-Ray `X-Amzn-Trace-Id`.
+    XRay,
+}
+
+impl Propagator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Propagator::TraceContext => "tracecontext",
+            Propagator::Baggage => "baggage",
+            Propagator::B3 => "b3",
+            Propagator::B3Multi => "b3multi",
+            Propagator::Jaeger => "jaeger",
+            Propagator::XRay => "xray",
+        }
+    }
+
+    fn build(&self) -> Box<dyn TextMapPropagator + Send + Sync> {
+        match self {
+            Propagator::TraceContext => Box::new(TraceContextPropagator::new()),
+            Propagator::Baggage => Box::new(BaggagePropagator::new()),
+            Propagator::B3 => Box::new(B3Propagator::new(false)),
+            Propagator::B3Multi => Box::new(B3Propagator::new(true)),
+            Propagator::Jaeger => Box::new(JaegerPropagator::new()),
+            Propagator::XRay => Box::new(XRayPropagator::new()),
+        }
+    }
+}
+
+impl FromStr for Propagator {
+    type Err = ParsePropagatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "tracecontext" => Ok(Propagator::TraceContext),
+            "baggage" => Ok(Propagator::Baggage),
+            "b3" => Ok(Propagator::B3),
+            "b3multi" => Ok(Propagator::B3Multi),
+            "jaeger" => Ok(Propagator::Jaeger),
+            "xray" => Ok(Propagator::XRay),
+            other => Err(ParsePropagatorError(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Propagator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Combines the given formats; every format is injected, and extraction runs
+/// them in order so later formats win when several headers are present.
+pub fn composite_propagator(propagators: &[Propagator]) -> TextMapCompositePropagator {
+    TextMapCompositePropagator::new(propagators.iter().map(Propagator::build).collect())
+}
+
+/// Whether the span in `cx` is a remote parent that deferred its sampling
+/// decision, so parent-based samplers should ask their root sampler.
+pub(crate) fn is_deferred(cx: &Context) -> bool {
+    let span = cx.span();
+    let span_context = span.span_context();
+    span_context.is_remote()
+        && !span_context.is_sampled()
+        && cx.get::<DeferredSampling>() == Some(&DeferredSampling(span_context.span_id()))
+}
+
+fn remote_span_context(trace_id: TraceId, span_id: SpanId, sampled: bool) -> Option<SpanContext> {
+    if trace_id == TraceId::INVALID || span_id == SpanId::INVALID {
+        return None;
+    }
+    let flags = if sampled {
+        TraceFlags::SAMPLED
+    } else {
+        TraceFlags::default()
+    };
+    Some(SpanContext::new(
+        trace_id,
+        span_id,
+        flags,
+        true,
+        TraceState::default(),
+    ))
+}
+
+fn with_remote(cx: &Context, span_context: Option<SpanContext>) -> Context {
+    match span_context {
+        Some(span_context) => cx.with_remote_span_context(span_context),
+        None => cx.clone(),
+    }
+}
+
+/// Like `with_remote`, where a `None` decision means the sender deferred it.
+fn with_remote_decision(cx: &Context, parent: Option<(SpanContext, Option<bool>)>) -> Context {
+    match parent {
+        Some((span_context, None)) => {
+            let deferred = DeferredSampling(span_context.span_id());
+            cx.with_remote_span_context(span_context)
+                .with_value(deferred)
+        }
+        Some((span_context, Some(_))) => cx.with_remote_span_context(span_context),
+        None => cx.clone(),
+    }
+}
+
+/// The sampled flag to forward: an extracted parent that deferred is passed on
+/// undecided, while spans we started carry our own decision.
+fn sampled_flag(cx: &Context) -> Option<bool> {
+    if cx.span().span_context().is_sampled() {
+        Some(true)
+    } else if is_deferred(cx) {
+        None
+    } else {
+        Some(false)
+    }
+}
+
+fn remote_parent(
+    trace_id: TraceId,
+    span_id: SpanId,
+    sampled: Option<bool>,
+) -> Option<(SpanContext, Option<bool>)> {
+    let span_context = remote_span_context(trace_id, span_id, sampled.unwrap_or(false))?;
+    Some((span_context, sampled))
+}
+
+/// Accepts 64-bit and 128-bit hex trace ids, left-padding the short form.
+fn parse_trace_id(value: &str) -> Option<TraceId> {
+    if value.is_empty() || value.len() > 32 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    TraceId::from_hex(&format!("{value:0>32}")).ok()
+}
+
+fn parse_span_id(value: &str) -> Option<SpanId> {
+    if value.is_empty() || value.len() > 16 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    SpanId::from_hex(&format!("{value:0>16}")).ok()
+}
+
+#[derive(Debug)]
+struct B3Propagator {
+    multi: bool,
+    fields: Vec<String>,
+}
+
+impl B3Propagator {
+    fn new(multi: bool) -> Self {
+        let fields = if multi {
+            vec![
+                B3_TRACE_ID_HEADER,
+                B3_SPAN_ID_HEADER,
+                B3_SAMPLED_HEADER,
+                B3_FLAGS_HEADER,
+            ]
+        } else {
+            vec![B3_SINGLE_HEADER]
+        };
+        Self {
+            multi,
+            fields: fields.into_iter().map(String::from).collect(),
+        }
+    }
+
+    fn extract_single(value: &str) -> Option<(SpanContext, Option<bool>)> {
+        let mut parts = value.trim().split('-');
+        let trace_id = parse_trace_id(parts.next()?)?;
+        let span_id = parse_span_id(parts.next()?)?;
+        let sampled = parts.next().map(|flag| matches!(flag, "1" | "d"));
+        remote_parent(trace_id, span_id, sampled)
+    }
+
+    fn extract_multi(extractor: &dyn Extractor) -> Option<(SpanContext, Option<bool>)> {
+        let trace_id = parse_trace_id(extractor.get(B3_TRACE_ID_HEADER)?.trim())?;
+        let span_id = parse_span_id(extractor.get(B3_SPAN_ID_HEADER)?.trim())?;
+        let debug = extractor.get(B3_FLAGS_HEADER).map(str::trim) == Some("1");
+        let sampled = match extractor.get(B3_SAMPLED_HEADER).map(str::trim) {
+            _ if debug => Some(true),
+            Some(flag) => Some(matches!(flag, "1" | "true")),
+            None => None,
+        };
+        remote_parent(trace_id, span_id, sampled)
+    }
+}
+
+impl TextMapPropagator for B3Propagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+        let sampled = sampled_flag(cx).map(|sampled| if sampled { "1" } else { "0" });
+
+        if self.multi {
+            injector.set(B3_TRACE_ID_HEADER, span_context.trace_id().to_string());
+            injector.set(B3_SPAN_ID_HEADER, span_context.span_id().to_string());
+            if let Some(sampled) = sampled {
+                injector.set(B3_SAMPLED_HEADER, sampled.to_string());
+            }
+        } else {
+            let ids = format!("{}-{}", span_context.trace_id(), span_context.span_id());
+            let value = match sampled {
+                Some(sampled) => format!("{ids}-{sampled}"),
+                None => ids,
+            };
+            injector.set(B3_SINGLE_HEADER, value);
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let span_context = if self.multi {
+            Self::extract_multi(extractor)
+        } else {
+            extractor
+                .get(B3_SINGLE_HEADER)
+                .and_then(Self::extract_single)
+        };
+        with_remote_decision(cx, span_context)
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+#[derive(Debug)]
+struct JaegerPropagator {
+    fields: Vec<String>,
+}
+
+impl JaegerPropagator {
+    fn new() -> Self {
+        Self {
+            fields: vec![JAEGER_HEADER.to_string()],
+        }
+    }
+
+    fn extract(value: &str) -> Option<SpanContext> {
+        let value = value.replace("%3A", ":").replace("%3a", ":");
+        let parts: Vec<&str> = value.trim().split(':').collect();
+        let [trace_id, span_id, _parent, flags] = parts.as_slice() else {
+            return None;
+        };
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        // Bit 1 marks the trace as sampled and bit 2 as debug, which implies sampled.
+        remote_span_context(
+            parse_trace_id(trace_id)?,
+            parse_span_id(span_id)?,
+            flags & 0x03 != 0,
+        )
+    }
+}
+
+impl TextMapPropagator for JaegerPropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+        let flags = if span_context.is_sampled() { "1" } else { "0" };
+        injector.set(
+            JAEGER_HEADER,
+            format!(
+                "{}:{}:0:{flags}",
+                span_context.trace_id(),
+                span_context.span_id()
+            ),
+        );
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        with_remote(cx, extractor.get(JAEGER_HEADER).and_then(Self::extract))
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+#[derive(Debug)]
+struct XRayPropagator {
+    fields: Vec<String>,
+}
+
+impl XRayPropagator {
+    fn new() -> Self {
+        Self {
+            fields: vec![XRAY_HEADER.to_string()],
+        }
+    }
+
+    /// Parses `Root=1-{epoch}-{unique};Parent={span};Sampled={0|1|?}`, where
+    /// `?` or a missing `Sampled` defers the decision.
+    fn extract(value: &str) -> Option<(SpanContext, Option<bool>)> {
+        let mut trace_id = None;
+        let mut span_id = None;
+        let mut sampled = None;
+
+        for part in value.split(';') {
+            match part.trim().split_once('=') {
+                Some(("Root", root)) => {
+                    let mut segments = root.split('-');
+                    if segments.next() != Some("1") {
+                        return None;
+                    }
+                    let (epoch, unique) = (segments.next()?, segments.next()?);
+                    if epoch.len() != 8 || unique.len() != 24 {
+                        return None;
+                    }
+                    trace_id = parse_trace_id(&format!("{epoch}{unique}"));
+                }
+                Some(("Parent", parent)) if parent.len() == 16 => span_id = parse_span_id(parent),
+                Some(("Sampled", "?")) => sampled = None,
+                Some(("Sampled", flag)) => sampled = Some(flag == "1"),
+                _ => {}
+            }
+        }
+
+        remote_parent(trace_id?, span_id?, sampled)
+    }
+}
+
+impl TextMapPropagator for XRayPropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return;
+        }
+        let trace_id = span_context.trace_id().to_string();
+        let (epoch, unique) = trace_id.split_at(8);
+        let sampled = match sampled_flag(cx) {
+            Some(true) => "1",
+            Some(false) => "0",
+            None => "?",
+        };
+        injector.set(
+            XRAY_HEADER,
+            format!(
+                "Root=1-{epoch}-{unique};Parent={};Sampled={sampled}",
+                span_context.span_id()
+            ),
+        );
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        with_remote_decision(cx, extractor.get(XRAY_HEADER).and_then(Self::extract))
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::KeyValue;
+    use opentelemetry::baggage::BaggageExt;
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const SPAN_ID: &str = "00f067aa0ba902b7";
+
+    fn sampled_context() -> Context {
+        Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_hex(TRACE_ID).unwrap(),
+            SpanId::from_hex(SPAN_ID).unwrap(),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        ))
+    }
+
+    fn inject(propagator: Propagator, cx: &Context) -> HashMap<String, String> {
+        let mut carrier = HashMap::new();
+        propagator.build().inject_context(cx, &mut carrier);
+        carrier
+    }
+
+    fn extract_context(propagator: Propagator, headers: &[(&str, &str)]) -> Context {
+        let carrier: HashMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        propagator
+            .build()
+            .extract_with_context(&Context::new(), &carrier)
+    }
+
+    fn extract(propagator: Propagator, headers: &[(&str, &str)]) -> SpanContext {
+        extract_context(propagator, headers)
+            .span()
+            .span_context()
+            .clone()
+    }
+
+    #[test]
+    fn test_round_trip_each_propagator() {
+        let cx = sampled_context();
+
+        for propagator in [
+            Propagator::TraceContext,
+            Propagator::B3,
+            Propagator::B3Multi,
+            Propagator::Jaeger,
+            Propagator::XRay,
+        ] {
+            let carrier = inject(propagator, &cx);
+            let headers: Vec<(&str, &str)> = carrier
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            let extracted = extract(propagator, &headers);
+
+            assert_eq!(extracted.trace_id().to_string(), TRACE_ID, "{propagator}");
+            assert_eq!(extracted.span_id().to_string(), SPAN_ID, "{propagator}");
+            assert!(extracted.is_sampled(), "{propagator}");
+            assert!(extracted.is_remote(), "{propagator}");
+        }
+    }
+
+    #[test]
+    fn test_injected_header_formats() {
+        let cx = sampled_context();
+
+        assert_eq!(
+            inject(Propagator::B3, &cx)["b3"],
+            format!("{TRACE_ID}-{SPAN_ID}-1")
+        );
+        assert_eq!(
+            inject(Propagator::Jaeger, &cx)["uber-trace-id"],
+            format!("{TRACE_ID}:{SPAN_ID}:0:1")
+        );
+        assert_eq!(
+            inject(Propagator::XRay, &cx)["x-amzn-trace-id"],
+            format!("Root=1-4bf92f35-77b34da6a3ce929d0e0e4736;Parent={SPAN_ID};Sampled=1")
+        );
+    }
+
+    #[test]
+    fn test_extract_short_ids_and_flags() {
+        let b3 = extract(
+            Propagator::B3Multi,
+            &[
+                ("x-b3-traceid", "a3ce929d0e0e4736"),
+                ("x-b3-spanid", SPAN_ID),
+                ("x-b3-flags", "1"),
+            ],
+        );
+        assert_eq!(
+            b3.trace_id().to_string(),
+            "0000000000000000a3ce929d0e0e4736"
+        );
+        assert!(b3.is_sampled());
+
+        let jaeger = extract(
+            Propagator::Jaeger,
+            &[("uber-trace-id", "a3ce929d0e0e4736%3Af067aa0ba902b7%3A0%3A0")],
+        );
+        assert!(jaeger.is_valid());
+        assert!(!jaeger.is_sampled());
+
+        assert!(!extract(Propagator::B3, &[("b3", "0")]).is_valid());
+        assert!(!extract(Propagator::XRay, &[("x-amzn-trace-id", "Root=2-x")]).is_valid());
+    }
+
+    #[test]
+    fn test_b3_defers_missing_sampling_decision() {
+        let single = extract_context(Propagator::B3, &[("b3", &format!("{TRACE_ID}-{SPAN_ID}"))]);
+        let multi = extract_context(
+            Propagator::B3Multi,
+            &[("x-b3-traceid", TRACE_ID), ("x-b3-spanid", SPAN_ID)],
+        );
+
+        for cx in [&single, &multi] {
+            let span = cx.span();
+            assert!(span.span_context().is_valid());
+            assert_eq!(span.span_context().trace_flags(), TraceFlags::default());
+            assert!(is_deferred(cx));
+        }
+
+        assert_eq!(
+            inject(Propagator::B3, &single)["b3"],
+            format!("{TRACE_ID}-{SPAN_ID}")
+        );
+        assert!(!inject(Propagator::B3Multi, &single).contains_key("x-b3-sampled"));
+
+        let denied = extract_context(
+            Propagator::B3Multi,
+            &[
+                ("x-b3-traceid", TRACE_ID),
+                ("x-b3-spanid", SPAN_ID),
+                ("x-b3-sampled", "0"),
+            ],
+        );
+        assert!(!is_deferred(&denied));
+        assert!(!is_deferred(&sampled_context()));
+    }
+
+    #[test]
+    fn test_xray_defers_missing_sampling_decision() {
+        let root = "Root=1-4bf92f35-77b34da6a3ce929d0e0e4736";
+        for header in [
+            format!("{root};Parent={SPAN_ID};Sampled=?"),
+            format!("{root};Parent={SPAN_ID}"),
+        ] {
+            let cx = extract_context(Propagator::XRay, &[("x-amzn-trace-id", &header)]);
+            assert!(cx.span().span_context().is_valid());
+            assert!(is_deferred(&cx), "{header}");
+            assert_eq!(
+                inject(Propagator::XRay, &cx)["x-amzn-trace-id"],
+                format!("{root};Parent={SPAN_ID};Sampled=?")
+            );
+        }
+
+        let denied = extract_context(
+            Propagator::XRay,
+            &[(
+                "x-amzn-trace-id",
+                &format!("{root};Parent={SPAN_ID};Sampled=0"),
+            )],
+        );
+        assert!(!is_deferred(&denied));
+    }
+
+    #[test]
+    fn test_children_of_deferred_parent_export_plain_flags() {
+        use crate::tracer::SamplerConfig;
+        use opentelemetry::trace::{Span, Tracer, TracerProvider as _};
+        use opentelemetry_sdk::trace::TracerProvider;
+
+        let provider = TracerProvider::builder()
+            .with_sampler(SamplerConfig::parent_based(SamplerConfig::AlwaysOn).build())
+            .build();
+        let tracer = provider.tracer("propagation-tests");
+        let parent = extract_context(Propagator::B3, &[("b3", &format!("{TRACE_ID}-{SPAN_ID}"))]);
+
+        let child = tracer.start_with_context("child", &parent);
+        let flags = child.span_context().trace_flags();
+        assert!(flags.is_sampled());
+        assert_eq!(flags & TraceFlags::new(0x02), TraceFlags::default());
+
+        let grandchild = tracer.start_with_context("grandchild", &parent.with_span(child));
+        assert_eq!(grandchild.span_context().trace_flags(), TraceFlags::SAMPLED);
+    }
+
+    #[test]
+    fn test_composite_keeps_baggage() {
+        let propagator = composite_propagator(&[Propagator::TraceContext, Propagator::Baggage]);
+        let cx = sampled_context().with_baggage([KeyValue::new("tenant", "acme")]);
+
+        let mut carrier = HashMap::new();
+        propagator.inject_context(&cx, &mut carrier);
+        let extracted = propagator.extract_with_context(&Context::new(), &carrier);
+
+        assert!(carrier.contains_key("traceparent"));
+        assert_eq!(
+            extracted.baggage().get("tenant").map(|v| v.to_string()),
+            Some("acme".to_string())
+        );
+        assert!(extracted.span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_propagator_from_str() {
+        assert_eq!(
+            "b3multi".parse::<Propagator>().unwrap(),
+            Propagator::B3Multi
+        );
+        assert_eq!(" xray ".parse::<Propagator>().unwrap(), Propagator::XRay);
+        assert!("ottrace".parse::<Propagator>().is_err());
+    }
+}
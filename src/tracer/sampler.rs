@@ -0,0 +1,619 @@
+use crate::tracer::propagation;
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanKind, TraceContextExt, TraceId,
+};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_sdk::trace::{Sampler, ShouldSample};
+use opentelemetry_semantic_conventions::trace::HTTP_ROUTE;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SamplerError {
+    #[error("sampling ratio {0} must be between 0 and 1")]
+    InvalidRatio(f64),
+    #[error("rate limit {0} must be a positive number of spans per second")]
+    InvalidRate(f64),
+}
+
+/// Sampling strategy for the tracer. Takes precedence over `TracerConfig::sample_ratio`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum SamplerConfig {
+    AlwaysOn,
+    AlwaysOff,
+    TraceIdRatio {
+        ratio: f64,
+    },
+    /// Follows the parent's decision, with a separate policy for each kind of parent.
+    ParentBased(ParentBasedConfig),
+    /// Uses the sampler of the first matching rule, or `fallback` when none match.
+    RuleBased(RuleBasedConfig),
+    /// Samples at most `spans_per_second` spans, refilling continuously.
+    RateLimited {
+        spans_per_second: f64,
+    },
+}
+
+impl SamplerConfig {
+    pub fn ratio(ratio: f64) -> Self {
+        SamplerConfig::TraceIdRatio { ratio }
+    }
+
+    pub fn parent_based(root: SamplerConfig) -> Self {
+        SamplerConfig::ParentBased(ParentBasedConfig::new(root))
+    }
+
+    pub fn rate_limited(spans_per_second: f64) -> Self {
+        SamplerConfig::RateLimited { spans_per_second }
+    }
+
+    pub fn validate(&self) -> Result<(), SamplerError> {
+        match self {
+            SamplerConfig::AlwaysOn | SamplerConfig::AlwaysOff => Ok(()),
+            SamplerConfig::TraceIdRatio { ratio } => {
+                if (0.0..=1.0).contains(ratio) {
+                    Ok(())
+                } else {
+                    Err(SamplerError::InvalidRatio(*ratio))
+                }
+            }
+            SamplerConfig::ParentBased(config) => [
+                &config.root,
+                &config.remote_parent_sampled,
+                &config.remote_parent_not_sampled,
+                &config.local_parent_sampled,
+                &config.local_parent_not_sampled,
+            ]
+            .into_iter()
+            .try_for_each(|sampler| sampler.validate()),
+            SamplerConfig::RuleBased(config) => {
+                for rule in &config.rules {
+                    rule.sampler.validate()?;
+                }
+                config.fallback.validate()
+            }
+            SamplerConfig::RateLimited { spans_per_second } => {
+                if spans_per_second.is_finite() && *spans_per_second > 0.0 {
+                    Ok(())
+                } else {
+                    Err(SamplerError::InvalidRate(*spans_per_second))
+                }
+            }
+        }
+    }
+
+    pub(crate) fn build(&self) -> BoxedSampler {
+        match self {
+            SamplerConfig::AlwaysOn => BoxedSampler::new(Sampler::AlwaysOn),
+            SamplerConfig::AlwaysOff => BoxedSampler::new(Sampler::AlwaysOff),
+            SamplerConfig::TraceIdRatio { ratio } => {
+                BoxedSampler::new(Sampler::TraceIdRatioBased(*ratio))
+            }
+            SamplerConfig::ParentBased(config) => BoxedSampler::new(ParentBasedSampler {
+                root: config.root.build(),
+                remote_parent_sampled: config.remote_parent_sampled.build(),
+                remote_parent_not_sampled: config.remote_parent_not_sampled.build(),
+                local_parent_sampled: config.local_parent_sampled.build(),
+                local_parent_not_sampled: config.local_parent_not_sampled.build(),
+            }),
+            SamplerConfig::RuleBased(config) => BoxedSampler::new(RuleBasedSampler {
+                rules: config
+                    .rules
+                    .iter()
+                    .map(|rule| (rule.clone(), rule.sampler.build()))
+                    .collect(),
+                fallback: config.fallback.build(),
+            }),
+            SamplerConfig::RateLimited { spans_per_second } => {
+                BoxedSampler::new(RateLimitingSampler::new(*spans_per_second))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ParentBasedConfig {
+    pub root: Box<SamplerConfig>,
+    pub remote_parent_sampled: Box<SamplerConfig>,
+    pub remote_parent_not_sampled: Box<SamplerConfig>,
+    pub local_parent_sampled: Box<SamplerConfig>,
+    pub local_parent_not_sampled: Box<SamplerConfig>,
+}
+
+impl Default for ParentBasedConfig {
+    fn default() -> Self {
+        Self::new(SamplerConfig::AlwaysOn)
+    }
+}
+
+impl ParentBasedConfig {
+    /// Samples roots with `root` and otherwise honours the parent's decision.
+    pub fn new(root: SamplerConfig) -> Self {
+        Self {
+            root: Box::new(root),
+            remote_parent_sampled: Box::new(SamplerConfig::AlwaysOn),
+            remote_parent_not_sampled: Box::new(SamplerConfig::AlwaysOff),
+            local_parent_sampled: Box::new(SamplerConfig::AlwaysOn),
+            local_parent_not_sampled: Box::new(SamplerConfig::AlwaysOff),
+        }
+    }
+
+    pub fn with_remote_parent_sampled(mut self, sampler: SamplerConfig) -> Self {
+        self.remote_parent_sampled = Box::new(sampler);
+        self
+    }
+
+    pub fn with_remote_parent_not_sampled(mut self, sampler: SamplerConfig) -> Self {
+        self.remote_parent_not_sampled = Box::new(sampler);
+        self
+    }
+
+    pub fn with_local_parent_sampled(mut self, sampler: SamplerConfig) -> Self {
+        self.local_parent_sampled = Box::new(sampler);
+        self
+    }
+
+    pub fn with_local_parent_not_sampled(mut self, sampler: SamplerConfig) -> Self {
+        self.local_parent_not_sampled = Box::new(sampler);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RuleBasedConfig {
+    pub rules: Vec<SamplingRule>,
+    pub fallback: Box<SamplerConfig>,
+}
+
+impl Default for RuleBasedConfig {
+    fn default() -> Self {
+        Self::new(SamplerConfig::AlwaysOn)
+    }
+}
+
+impl RuleBasedConfig {
+    pub fn new(fallback: SamplerConfig) -> Self {
+        Self {
+            rules: Vec::new(),
+            fallback: Box::new(fallback),
+        }
+    }
+
+    pub fn with_rule(mut self, rule: SamplingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+/// Matches spans on their name, start attributes and `http.route`. Every
+/// configured condition must hold; `*` in the span name or route matches any
+/// sequence of characters.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SamplingRule {
+    pub span_name: Option<String>,
+    pub route: Option<String>,
+    pub attributes: HashMap<String, String>,
+    pub sampler: SamplerConfig,
+}
+
+impl Default for SamplingRule {
+    fn default() -> Self {
+        Self::new(SamplerConfig::AlwaysOn)
+    }
+}
+
+impl SamplingRule {
+    pub fn new(sampler: SamplerConfig) -> Self {
+        Self {
+            span_name: None,
+            route: None,
+            attributes: HashMap::new(),
+            sampler,
+        }
+    }
+
+    pub fn with_span_name(mut self, pattern: impl Into<String>) -> Self {
+        self.span_name = Some(pattern.into());
+        self
+    }
+
+    pub fn with_route(mut self, pattern: impl Into<String>) -> Self {
+        self.route = Some(pattern.into());
+        self
+    }
+
+    pub fn with_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    fn matches(&self, name: &str, attributes: &[KeyValue]) -> bool {
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.as_str())
+        };
+
+        self.span_name
+            .as_deref()
+            .is_none_or(|pattern| glob_match(pattern, name))
+            && self.route.as_deref().is_none_or(|pattern| {
+                attribute(HTTP_ROUTE).is_some_and(|route| glob_match(pattern, &route))
+            })
+            && self
+                .attributes
+                .iter()
+                .all(|(key, value)| attribute(key).is_some_and(|actual| actual == value.as_str()))
+    }
+}
+
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// A type-erased sampler, since the provider builder needs a concrete type.
+#[derive(Clone, Debug)]
+pub(crate) struct BoxedSampler(Box<dyn ShouldSample>);
+
+impl BoxedSampler {
+    pub(crate) fn new(sampler: impl ShouldSample + 'static) -> Self {
+        Self(Box::new(sampler))
+    }
+
+    /// Like `Sampler::ParentBased`, but also asks `root` about remote parents
+    /// whose sampling decision was deferred.
+    pub(crate) fn parent_based(root: impl ShouldSample + 'static) -> Self {
+        Self::new(ParentBasedSampler {
+            root: Self::new(root),
+            remote_parent_sampled: Self::new(Sampler::AlwaysOn),
+            remote_parent_not_sampled: Self::new(Sampler::AlwaysOff),
+            local_parent_sampled: Self::new(Sampler::AlwaysOn),
+            local_parent_not_sampled: Self::new(Sampler::AlwaysOff),
+        })
+    }
+}
+
+impl ShouldSample for BoxedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        self.0
+            .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ParentBasedSampler {
+    root: BoxedSampler,
+    remote_parent_sampled: BoxedSampler,
+    remote_parent_not_sampled: BoxedSampler,
+    local_parent_sampled: BoxedSampler,
+    local_parent_not_sampled: BoxedSampler,
+}
+
+impl ShouldSample for ParentBasedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        let delegate = match parent_context.filter(|cx| cx.has_active_span()) {
+            None => &self.root,
+            Some(cx) => {
+                let span = cx.span();
+                let parent = span.span_context();
+                match (parent.is_remote(), parent.is_sampled()) {
+                    _ if propagation::is_deferred(cx) => &self.root,
+                    (true, true) => &self.remote_parent_sampled,
+                    (true, false) => &self.remote_parent_not_sampled,
+                    (false, true) => &self.local_parent_sampled,
+                    (false, false) => &self.local_parent_not_sampled,
+                }
+            }
+        };
+
+        delegate.should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RuleBasedSampler {
+    rules: Vec<(SamplingRule, BoxedSampler)>,
+    fallback: BoxedSampler,
+}
+
+impl ShouldSample for RuleBasedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        self.rules
+            .iter()
+            .find(|(rule, _)| rule.matches(name, attributes))
+            .map_or(&self.fallback, |(_, sampler)| sampler)
+            .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+/// Token bucket holding up to one second of spans. Clones share the bucket.
+#[derive(Clone, Debug)]
+struct RateLimitingSampler {
+    spans_per_second: f64,
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimitingSampler {
+    fn new(spans_per_second: f64) -> Self {
+        Self {
+            spans_per_second,
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                tokens: spans_per_second.max(1.0),
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    fn try_acquire(&self) -> bool {
+        let mut bucket = self
+            .bucket
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * self.spans_per_second).min(self.spans_per_second.max(1.0));
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl ShouldSample for RateLimitingSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        _trace_id: TraceId,
+        _name: &str,
+        _span_kind: &SpanKind,
+        _attributes: &[KeyValue],
+        _links: &[Link],
+    ) -> SamplingResult {
+        let decision = if self.try_acquire() {
+            SamplingDecision::RecordAndSample
+        } else {
+            SamplingDecision::Drop
+        };
+
+        SamplingResult {
+            decision,
+            attributes: Vec::new(),
+            trace_state: parent_context
+                .map(|cx| cx.span().span_context().trace_state().clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceState};
+
+    fn decide(
+        sampler: &SamplerConfig,
+        parent: Option<&Context>,
+        name: &str,
+        attributes: &[KeyValue],
+    ) -> SamplingDecision {
+        sampler
+            .build()
+            .should_sample(
+                parent,
+                TraceId::from_bytes([1; 16]),
+                name,
+                &SpanKind::Server,
+                attributes,
+                &[],
+            )
+            .decision
+    }
+
+    fn parent(remote: bool, sampled: bool) -> Context {
+        let flags = if sampled {
+            TraceFlags::SAMPLED
+        } else {
+            TraceFlags::default()
+        };
+        Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_bytes([1; 16]),
+            SpanId::from_bytes([1; 8]),
+            flags,
+            remote,
+            TraceState::default(),
+        ))
+    }
+
+    #[test]
+    fn test_parent_based_policies() {
+        let sampler = SamplerConfig::ParentBased(
+            ParentBasedConfig::new(SamplerConfig::AlwaysOff)
+                .with_remote_parent_not_sampled(SamplerConfig::AlwaysOn)
+                .with_local_parent_sampled(SamplerConfig::AlwaysOff),
+        );
+
+        assert_eq!(decide(&sampler, None, "root", &[]), SamplingDecision::Drop);
+        assert_eq!(
+            decide(&sampler, Some(&parent(true, true)), "child", &[]),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decide(&sampler, Some(&parent(true, false)), "child", &[]),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decide(&sampler, Some(&parent(false, true)), "child", &[]),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decide(&sampler, Some(&parent(false, false)), "child", &[]),
+            SamplingDecision::Drop
+        );
+    }
+
+    #[test]
+    fn test_parent_based_asks_root_for_deferred_remote_parent() {
+        let carrier = HashMap::from([(
+            "b3".to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7".to_string(),
+        )]);
+        let deferred =
+            propagation::composite_propagator(&[propagation::Propagator::B3]).extract(&carrier);
+
+        let sampler = SamplerConfig::parent_based(SamplerConfig::AlwaysOn);
+        assert_eq!(
+            decide(&sampler, Some(&deferred), "child", &[]),
+            SamplingDecision::RecordAndSample
+        );
+        let sampler = SamplerConfig::parent_based(SamplerConfig::AlwaysOff);
+        assert_eq!(
+            decide(&sampler, Some(&deferred), "child", &[]),
+            SamplingDecision::Drop
+        );
+    }
+
+    #[test]
+    fn test_rule_based_first_match_wins() {
+        let sampler = SamplerConfig::RuleBased(
+            RuleBasedConfig::new(SamplerConfig::AlwaysOn)
+                .with_rule(SamplingRule::new(SamplerConfig::AlwaysOff).with_route("/health*"))
+                .with_rule(
+                    SamplingRule::new(SamplerConfig::AlwaysOff)
+                        .with_span_name("db.*")
+                        .with_attribute("db.system", "redis"),
+                ),
+        );
+
+        let route = [KeyValue::new(HTTP_ROUTE, "/healthz")];
+        let redis = [KeyValue::new("db.system", "redis")];
+        let postgres = [KeyValue::new("db.system", "postgresql")];
+
+        assert_eq!(
+            decide(&sampler, None, "GET", &route),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decide(&sampler, None, "db.query", &redis),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decide(&sampler, None, "db.query", &postgres),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decide(&sampler, None, "cache.get", &redis),
+            SamplingDecision::RecordAndSample
+        );
+    }
+
+    #[test]
+    fn test_rate_limited_caps_burst() {
+        let sampler = SamplerConfig::rate_limited(2.0).build();
+        let sampled = (0..10)
+            .filter(|_| {
+                sampler
+                    .should_sample(
+                        None,
+                        TraceId::from_bytes([1; 16]),
+                        "span",
+                        &SpanKind::Internal,
+                        &[],
+                        &[],
+                    )
+                    .decision
+                    == SamplingDecision::RecordAndSample
+            })
+            .count();
+
+        assert_eq!(sampled, 2);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("db.*", "db.query"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("/users/*/orders", "/users/42/orders"));
+        assert!(!glob_match("/users/*/orders", "/users/42/items"));
+        assert!(!glob_match("db", "db.query"));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_values() {
+        assert!(matches!(
+            SamplerConfig::parent_based(SamplerConfig::ratio(1.5)).validate(),
+            Err(SamplerError::InvalidRatio(_))
+        ));
+        assert!(matches!(
+            SamplerConfig::rate_limited(0.0).validate(),
+            Err(SamplerError::InvalidRate(_))
+        ));
+        assert!(
+            SamplerConfig::parent_based(SamplerConfig::ratio(0.1))
+                .validate()
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,215 @@
+// Shared by several test binaries, each of which only uses part of it.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use opentelemetry_proto::tonic::collector::logs::v1::logs_service_server::{
+    LogsService, LogsServiceServer,
+};
+use opentelemetry_proto::tonic::collector::logs::v1::{
+    ExportLogsServiceRequest, ExportLogsServiceResponse,
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_server::{
+    MetricsService, MetricsServiceServer,
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::{
+    TraceService, TraceServiceServer,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{Instant, sleep};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+
+pub const GRPC_TRACES_PATH: &str = "/opentelemetry.proto.collector.trace.v1.TraceService/Export";
+pub const GRPC_LOGS_PATH: &str = "/opentelemetry.proto.collector.logs.v1.LogsService/Export";
+pub const GRPC_METRICS_PATH: &str =
+    "/opentelemetry.proto.collector.metrics.v1.MetricsService/Export";
+
+#[derive(Clone, Debug)]
+pub struct ReceivedRequest {
+    pub path: String,
+    pub headers: HashMap<String, String>,
+}
+
+type Requests = Arc<Mutex<Vec<ReceivedRequest>>>;
+
+/// Minimal OTLP receiver that records the path and headers of every export.
+pub struct Collector {
+    addr: SocketAddr,
+    requests: Requests,
+}
+
+impl Collector {
+    pub async fn http() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let requests = Requests::default();
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_http(stream, recorded.clone()));
+            }
+        });
+
+        Ok(Self { addr, requests })
+    }
+
+    pub async fn grpc() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let requests = Requests::default();
+
+        let receiver = GrpcReceiver {
+            requests: requests.clone(),
+        };
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(TraceServiceServer::new(receiver.clone()))
+                .add_service(LogsServiceServer::new(receiver.clone()))
+                .add_service(MetricsServiceServer::new(receiver))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        Ok(Self { addr, requests })
+    }
+
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub async fn wait_for(&self, path: &str) -> Result<ReceivedRequest> {
+        self.wait_for_within(path, Duration::from_secs(10)).await
+    }
+
+    pub async fn wait_for_within(&self, path: &str, timeout: Duration) -> Result<ReceivedRequest> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let found = self
+                .requests
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|r| r.path == path)
+                .cloned();
+            if let Some(request) = found {
+                return Ok(request);
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!("timed out waiting for a request to {path}"));
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+async fn serve_http(stream: TcpStream, requests: Requests) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+        let path = request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string();
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let length = headers
+            .get("content-length")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await?;
+
+        requests
+            .lock()
+            .unwrap()
+            .push(ReceivedRequest { path, headers });
+
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .await?;
+    }
+}
+
+#[derive(Clone)]
+struct GrpcReceiver {
+    requests: Requests,
+}
+
+impl GrpcReceiver {
+    fn record<T>(&self, path: &str, request: &Request<T>) {
+        let headers = request
+            .metadata()
+            .clone()
+            .into_headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        self.requests.lock().unwrap().push(ReceivedRequest {
+            path: path.to_string(),
+            headers,
+        });
+    }
+}
+
+#[tonic::async_trait]
+impl TraceService for GrpcReceiver {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        self.record(GRPC_TRACES_PATH, &request);
+        Ok(Response::new(ExportTraceServiceResponse::default()))
+    }
+}
+
+#[tonic::async_trait]
+impl LogsService for GrpcReceiver {
+    async fn export(
+        &self,
+        request: Request<ExportLogsServiceRequest>,
+    ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        self.record(GRPC_LOGS_PATH, &request);
+        Ok(Response::new(ExportLogsServiceResponse::default()))
+    }
+}
+
+#[tonic::async_trait]
+impl MetricsService for GrpcReceiver {
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+        self.record(GRPC_METRICS_PATH, &request);
+        Ok(Response::new(ExportMetricsServiceResponse::default()))
+    }
+}
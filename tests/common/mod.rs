@@ -2,15 +2,10 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Result, anyhow};
 use opentelemetry::KeyValue;
-use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _, Severity};
-use opentelemetry::trace::SpanContext;
-use opentelemetry_sdk::logs::LogRecord;
 use reqwest::{Client, StatusCode};
 use serde_json::Value;
 use tokio::time::{Instant, sleep};
 
-use o11y::logger::LoggerProvider;
-
 pub struct ObservationCase {
     pub service_name: String,
     pub test_case: String,
@@ -92,39 +87,6 @@ impl Targets {
     }
 }
 
-pub fn emit_log(
-    provider: &LoggerProvider,
-    span_context: &SpanContext,
-    message: &str,
-    test_case: &str,
-) -> Result<()> {
-    let logger = provider.logger("rust-o11y/tests");
-
-    let mut record = LogRecord::default();
-    record.set_body(AnyValue::from(message.to_string()));
-    record.set_timestamp(SystemTime::now());
-    record.set_observed_timestamp(SystemTime::now());
-    record.set_severity_number(Severity::Info);
-    record.set_severity_text("INFO");
-    record.add_attribute("message", message.to_string());
-    record.add_attribute("test_case", test_case.to_string());
-    record.add_attribute("trace_id", span_context.trace_id().to_string());
-    record.add_attribute("span_id", span_context.span_id().to_string());
-    record.set_trace_context(
-        span_context.trace_id(),
-        span_context.span_id(),
-        Some(span_context.trace_flags()),
-    );
-
-    logger.emit(record);
-
-    for result in provider.force_flush() {
-        result?;
-    }
-
-    Ok(())
-}
-
 pub async fn record_metric(
     metric_name: &str,
     test_case: &str,
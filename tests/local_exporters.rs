@@ -0,0 +1,72 @@
+#![cfg(feature = "local")]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow, ensure};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _};
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+
+use o11y::local::LocalFormat;
+use o11y::logger::{self, LoggerConfig};
+use o11y::meter::{self, MeterConfig};
+use o11y::tracer::{self, TracerConfig};
+use o11y::{LocalExporter, ResourceConfig};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn signals_share_one_otlp_json_file() -> Result<()> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let path = std::env::temp_dir().join(format!("o11y-local-{nanos}/telemetry.jsonl"));
+    let local = LocalExporter::file(&path).with_format(LocalFormat::OtlpJson);
+    let resource = ResourceConfig::new("local-tests").build();
+
+    let tracer = tracer::setup(
+        &TracerConfig::new("local-tests").with_exporter(local.clone()),
+        &resource,
+    )?
+    .ok_or_else(|| anyhow!("tracer provider not initialised"))?;
+    let logger = logger::setup(
+        &LoggerConfig::new("local-tests").with_exporter(local.clone()),
+        &resource,
+    )?
+    .ok_or_else(|| anyhow!("logger provider not initialised"))?;
+    let meter = meter::setup(
+        &MeterConfig::new("local-tests").with_exporter(local),
+        &resource,
+    )?
+    .ok_or_else(|| anyhow!("meter provider not initialised"))?;
+
+    tracer.tracer("rust-o11y/tests").start("local-span").end();
+    let log = logger.logger("rust-o11y/tests");
+    let mut record = log.create_log_record();
+    record.set_body(AnyValue::from("local log"));
+    log.emit(record);
+    meter
+        .meter("rust-o11y/tests")
+        .u64_counter("local_total")
+        .build()
+        .add(1, &[]);
+    meter.force_flush()?;
+
+    let written = std::fs::read_to_string(&path)?;
+    let mut kinds = Vec::new();
+    for line in written.lines() {
+        let request: serde_json::Value = serde_json::from_str(line)?;
+        let kind = ["resourceSpans", "resourceLogs", "resourceMetrics"]
+            .into_iter()
+            .find(|kind| request.get(kind).is_some())
+            .ok_or_else(|| anyhow!("unexpected line {line}"))?;
+        kinds.push(kind);
+    }
+    ensure!(
+        kinds == ["resourceSpans", "resourceLogs", "resourceMetrics"],
+        "{written}"
+    );
+    ensure!(written.contains("\"local-span\"") && written.contains("\"local log\""));
+
+    tracer::shutdown(tracer);
+    logger::shutdown(logger);
+    meter::shutdown(meter);
+    std::fs::remove_dir_all(path.parent().unwrap())?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow, ensure};
+use tracing::Level;
+
+use o11y::logger::{LogFilterConfig, LoggerConfig};
+use o11y::meter::MeterConfig;
+use o11y::profiler::ProfilerConfig;
+use o11y::tracer::TracerConfig;
+use o11y::{Config, Telemetry};
+
+fn wait_until(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn log_level_handle_and_watch_file_reload_the_filter() -> Result<()> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let path = std::env::temp_dir().join(format!("o11y-log-level-{nanos}"));
+    let filter = LogFilterConfig::new("warn")
+        .with_env_var("O11Y_LOG_LEVEL_TESTS")
+        .with_watch_file(&path)
+        .with_watch_interval(Duration::from_millis(20));
+    let config = Config::new("log-level-tests")
+        .with_logger(
+            LoggerConfig::new("log-level-tests")
+                .enabled(false)
+                .with_filter_config(filter),
+        )
+        .with_tracer(TracerConfig::new("log-level-tests").enabled(false))
+        .with_meter(MeterConfig::new("log-level-tests").enabled(false))
+        .with_profiler(ProfilerConfig::new("log-level-tests").enabled(false));
+
+    let telemetry = Telemetry::new(config)?;
+    let handle = telemetry
+        .log_level
+        .clone()
+        .ok_or_else(|| anyhow!("subscriber not installed"))?;
+    ensure!(handle.directives() == "warn");
+    ensure!(!tracing::enabled!(Level::INFO));
+
+    handle.set_directives("info")?;
+    ensure!(tracing::enabled!(Level::INFO) && !tracing::enabled!(Level::DEBUG));
+    handle.reset()?;
+    ensure!(!tracing::enabled!(Level::INFO));
+
+    std::fs::write(&path, "# incident\ndebug\n")?;
+    ensure!(wait_until(|| tracing::enabled!(Level::DEBUG)));
+    ensure!(handle.directives() == "debug");
+
+    std::fs::remove_file(&path)?;
+    ensure!(wait_until(|| !tracing::enabled!(Level::INFO)));
+    ensure!(handle.directives() == "warn");
+
+    telemetry.shutdown();
+    Ok(())
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "prometheus")]
+
+use std::net::{SocketAddr, TcpListener};
+
+use anyhow::{Result, anyhow, ensure};
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::MeterProvider as _;
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+
+use o11y::ResourceConfig;
+use o11y::meter::{self, MeterConfig, MeterExporter};
+
+fn free_addr() -> Result<SocketAddr> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?)
+}
+
+#[tokio::test]
+async fn prometheus_exporter_serves_scrapes() -> Result<()> {
+    let addr = free_addr()?;
+    let config =
+        MeterConfig::new("prometheus-tests").with_exporter(MeterExporter::prometheus(addr));
+    config.validate()?;
+    let resource = ResourceConfig::new("prometheus-tests").build();
+    let provider = meter::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("meter provider not initialised"))?;
+
+    provider
+        .meter("rust-o11y/tests")
+        .u64_counter("orders")
+        .build()
+        .add(2, &[KeyValue::new("region", "eu")]);
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{addr}/metrics");
+
+    let text = client.get(&url).send().await?;
+    ensure!(text.status() == StatusCode::OK, "status {}", text.status());
+    ensure!(
+        text.headers()[CONTENT_TYPE]
+            .to_str()?
+            .starts_with("text/plain; version=0.0.4")
+    );
+    let body = text.text().await?;
+    ensure!(
+        body.contains("service_name=\"prometheus-tests\""),
+        "missing target_info in {body}"
+    );
+    ensure!(
+        body.contains("orders_total{region=\"eu\",otel_scope_name=\"rust-o11y/tests\"} 2"),
+        "missing counter in {body}"
+    );
+
+    let openmetrics = client
+        .get(&url)
+        .header(ACCEPT, "application/openmetrics-text; version=1.0.0")
+        .send()
+        .await?;
+    ensure!(
+        openmetrics.headers()[CONTENT_TYPE]
+            .to_str()?
+            .starts_with("application/openmetrics-text")
+    );
+    let body = openmetrics.text().await?;
+    ensure!(body.contains("# TYPE orders counter"), "{body}");
+    ensure!(body.ends_with("# EOF\n"), "{body}");
+
+    let missing = client.get(format!("http://{addr}/other")).send().await?;
+    ensure!(missing.status() == StatusCode::NOT_FOUND);
+
+    meter::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test]
+async fn prometheus_exporter_reports_taken_port() -> Result<()> {
+    let taken = TcpListener::bind("127.0.0.1:0")?;
+    let config = MeterConfig::new("prometheus-tests")
+        .with_exporter(MeterExporter::prometheus(taken.local_addr()?));
+
+    let result = meter::setup(&config, &ResourceConfig::new("prometheus-tests").build());
+
+    ensure!(result.is_err(), "binding a taken port should fail setup");
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, anyhow};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _};
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+
+use o11y::logger::{self, LoggerConfig};
+use o11y::meter::{self, MeterConfig};
+use o11y::tracer::{self, TracerConfig};
+use o11y::{BatchConfig, Protocol, ResourceConfig};
+
+#[path = "collector/mod.rs"]
+mod collector;
+
+use collector::Collector;
+
+// Every export below would miss the deadline with the SDK defaults, and none of them flush.
+const DEADLINE: Duration = Duration::from_secs(2);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn logger_exports_full_batch_without_waiting_for_delay() -> Result<()> {
+    let collector = Collector::http().await?;
+    let resource = ResourceConfig::new("batching-logger").build();
+
+    let config = LoggerConfig::new("batching-logger")
+        .with_endpoint(collector.endpoint())
+        .with_batch(
+            BatchConfig::default()
+                .with_scheduled_delay(Duration::from_secs(60))
+                .with_max_queue_size(16)
+                .with_max_export_batch_size(1),
+        );
+    let provider = logger::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("logger provider not initialised"))?;
+
+    let logger = provider.logger("rust-o11y/tests");
+    let mut record = logger.create_log_record();
+    record.set_body(AnyValue::from("batched log"));
+    record.set_timestamp(SystemTime::now());
+    logger.emit(record);
+
+    collector.wait_for_within("/v1/logs", DEADLINE).await?;
+
+    logger::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tracer_honours_scheduled_delay() -> Result<()> {
+    let collector = Collector::http().await?;
+    let resource = ResourceConfig::new("batching-tracer").build();
+
+    let config = TracerConfig::new("batching-tracer")
+        .with_endpoint(collector.endpoint())
+        .with_protocol(Protocol::HttpProtobuf)
+        .with_batch(
+            BatchConfig::default()
+                .with_scheduled_delay(Duration::from_millis(100))
+                .with_max_concurrent_exports(2),
+        );
+    let provider = tracer::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("tracer provider not initialised"))?;
+
+    provider
+        .tracer("rust-o11y/tests")
+        .start("batched-span")
+        .end();
+
+    collector.wait_for_within("/v1/traces", DEADLINE).await?;
+
+    tracer::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn meter_honours_export_interval() -> Result<()> {
+    let collector = Collector::http().await?;
+    let resource = ResourceConfig::new("batching-meter").build();
+
+    let config = MeterConfig::new("batching-meter")
+        .with_endpoint(collector.endpoint())
+        .with_export_interval(Duration::from_millis(100));
+    let provider = meter::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("meter provider not initialised"))?;
+
+    provider
+        .meter("rust-o11y/tests")
+        .u64_counter("interval_total")
+        .build()
+        .add(1, &[]);
+
+    collector.wait_for_within("/v1/metrics", DEADLINE).await?;
+
+    meter::shutdown(provider);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, anyhow};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _};
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+
+use o11y::logger::{self, LoggerConfig};
+use o11y::meter::{self, MeterConfig};
+use o11y::tracer::{self, TracerConfig};
+use o11y::{Credentials, ExporterTarget, Protocol, ResourceConfig};
+
+#[path = "collector/mod.rs"]
+mod collector;
+
+use collector::{Collector, GRPC_TRACES_PATH};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tracer_writes_to_every_target() -> Result<()> {
+    let old = Collector::http().await?;
+    let new = Collector::grpc().await?;
+    let resource = ResourceConfig::new("fanout-tracer").build();
+
+    let config = TracerConfig::new("fanout-tracer")
+        .with_endpoint(old.endpoint())
+        .with_protocol(Protocol::HttpProtobuf)
+        .with_target(
+            ExporterTarget::new(new.endpoint())
+                .with_protocol(Protocol::Grpc)
+                .with_credentials(Credentials::new().with_bearer("new-token")),
+        );
+    let provider = tracer::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("tracer provider not initialised"))?;
+
+    provider
+        .tracer("rust-o11y/tests")
+        .start("fanout-span")
+        .end();
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    let old_request = old.wait_for("/v1/traces").await?;
+    let new_request = new.wait_for(GRPC_TRACES_PATH).await?;
+    assert!(!old_request.headers.contains_key("authorization"));
+    assert_eq!(new_request.headers["authorization"], "Bearer new-token");
+
+    tracer::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn logger_writes_to_every_target() -> Result<()> {
+    let old = Collector::http().await?;
+    let new = Collector::http().await?;
+    let resource = ResourceConfig::new("fanout-logger").build();
+
+    let config = LoggerConfig::new("fanout-logger")
+        .with_endpoint(old.endpoint())
+        .with_target(ExporterTarget::new(format!("{}/otlp", new.endpoint())));
+    let provider = logger::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("logger provider not initialised"))?;
+
+    let logger = provider.logger("rust-o11y/tests");
+    let mut record = logger.create_log_record();
+    record.set_body(AnyValue::from("fanout log"));
+    record.set_timestamp(SystemTime::now());
+    logger.emit(record);
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    old.wait_for("/v1/logs").await?;
+    new.wait_for("/otlp/v1/logs").await?;
+
+    logger::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn meter_writes_to_every_target() -> Result<()> {
+    let old = Collector::http().await?;
+    let new = Collector::http().await?;
+    let resource = ResourceConfig::new("fanout-meter").build();
+
+    let config = MeterConfig::new("fanout-meter")
+        .with_endpoint(old.endpoint())
+        .with_export_interval(Duration::from_secs(60))
+        .with_target(ExporterTarget::new(new.endpoint()).with_protocol(Protocol::HttpJson));
+    let provider = meter::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("meter provider not initialised"))?;
+
+    provider
+        .meter("rust-o11y/tests")
+        .u64_counter("fanout_total")
+        .build()
+        .add(1, &[]);
+    provider.force_flush()?;
+
+    let old_request = old.wait_for("/v1/metrics").await?;
+    let new_request = new.wait_for("/v1/metrics").await?;
+    assert_eq!(
+        old_request.headers["content-type"],
+        "application/x-protobuf"
+    );
+    assert_eq!(new_request.headers["content-type"], "application/json");
+
+    meter::shutdown(provider);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, anyhow};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+
+use o11y::logger::{self, LoggerConfig};
+use o11y::meter::{self, MeterConfig};
+use o11y::tracer::{self, TracerConfig};
+use o11y::{Credentials, ResourceConfig};
+
+#[path = "collector/mod.rs"]
+mod collector;
+
+use collector::{Collector, GRPC_TRACES_PATH};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn logger_sends_credential_headers() -> Result<()> {
+    let collector = Collector::http().await?;
+    let resource = ResourceConfig::new("headers-logger").build();
+
+    let config = LoggerConfig::new("headers-logger")
+        .with_endpoint(collector.endpoint())
+        .with_credentials(
+            Credentials::new()
+                .with_basic("user", "pass")
+                .with_header("X-Scope-OrgID", "tenant-a"),
+        );
+    let provider = logger::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("logger provider not initialised"))?;
+
+    let logger = provider.logger("rust-o11y/tests");
+    let mut record = logger.create_log_record();
+    record.set_body(AnyValue::from("credential headers"));
+    record.set_timestamp(SystemTime::now());
+    record.set_severity_number(Severity::Info);
+    logger.emit(record);
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    let request = collector.wait_for("/v1/logs").await?;
+    assert_eq!(request.headers["authorization"], "Basic dXNlcjpwYXNz");
+    assert_eq!(request.headers["x-scope-orgid"], "tenant-a");
+
+    logger::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tracer_sends_credential_metadata() -> Result<()> {
+    let collector = Collector::grpc().await?;
+    let resource = ResourceConfig::new("headers-tracer").build();
+
+    let config = TracerConfig::new("headers-tracer")
+        .with_endpoint(collector.endpoint())
+        .with_credentials(
+            Credentials::new()
+                .with_bearer("secret-token")
+                .with_header("X-Scope-OrgID", "tenant-b"),
+        );
+    let provider = tracer::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("tracer provider not initialised"))?;
+
+    provider
+        .tracer("rust-o11y/tests")
+        .start("credential-span")
+        .end();
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    let request = collector.wait_for(GRPC_TRACES_PATH).await?;
+    assert_eq!(request.headers["authorization"], "Bearer secret-token");
+    assert_eq!(request.headers["x-scope-orgid"], "tenant-b");
+
+    tracer::shutdown(provider);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn meter_sends_credential_headers() -> Result<()> {
+    let collector = Collector::http().await?;
+    let resource = ResourceConfig::new("headers-meter").build();
+
+    let config = MeterConfig::new("headers-meter")
+        .with_endpoint(collector.endpoint())
+        .with_export_interval(Duration::from_secs(60))
+        .with_credentials(
+            Credentials::new()
+                .with_api_key("my-api-key")
+                .with_api_key_header("X-Custom-Key"),
+        );
+    let provider = meter::setup(&config, &resource)?
+        .ok_or_else(|| anyhow!("meter provider not initialised"))?;
+
+    let counter = provider
+        .meter("rust-o11y/tests")
+        .u64_counter("credential_headers_total")
+        .build();
+    counter.add(1, &[]);
+    provider.force_flush()?;
+
+    let request = collector.wait_for("/v1/metrics").await?;
+    assert_eq!(request.headers["x-custom-key"], "my-api-key");
+
+    meter::shutdown(provider);
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, anyhow};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _};
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+use opentelemetry_sdk::resource::Resource;
+
+use o11y::logger::{self, LoggerConfig};
+use o11y::meter::{self, MeterConfig};
+use o11y::tracer::{self, TracerConfig};
+use o11y::{Protocol, ResourceConfig};
+
+#[path = "collector/mod.rs"]
+mod collector;
+
+use collector::{Collector, GRPC_LOGS_PATH, GRPC_METRICS_PATH, GRPC_TRACES_PATH, ReceivedRequest};
+
+fn resource() -> Resource {
+    ResourceConfig::new("protocol-tests").build()
+}
+
+async fn collector_for(protocol: Protocol) -> Result<Collector> {
+    match protocol {
+        Protocol::Grpc => Collector::grpc().await,
+        Protocol::HttpProtobuf | Protocol::HttpJson => Collector::http().await,
+    }
+}
+
+async fn export_log(
+    protocol: Protocol,
+    endpoint_suffix: &str,
+    path: &str,
+) -> Result<ReceivedRequest> {
+    let collector = collector_for(protocol).await?;
+    let config = LoggerConfig::new("protocol-tests")
+        .with_endpoint(format!("{}{}", collector.endpoint(), endpoint_suffix))
+        .with_protocol(protocol);
+    let provider = logger::setup(&config, &resource())?
+        .ok_or_else(|| anyhow!("logger provider not initialised"))?;
+
+    let logger = provider.logger("rust-o11y/tests");
+    let mut record = logger.create_log_record();
+    record.set_body(AnyValue::from("protocol log"));
+    record.set_timestamp(SystemTime::now());
+    logger.emit(record);
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    let request = collector.wait_for(path).await?;
+    logger::shutdown(provider);
+    Ok(request)
+}
+
+async fn export_span(
+    protocol: Protocol,
+    endpoint_suffix: &str,
+    path: &str,
+) -> Result<ReceivedRequest> {
+    let collector = collector_for(protocol).await?;
+    let config = TracerConfig::new("protocol-tests")
+        .with_endpoint(format!("{}{}", collector.endpoint(), endpoint_suffix))
+        .with_protocol(protocol);
+    let provider = tracer::setup(&config, &resource())?
+        .ok_or_else(|| anyhow!("tracer provider not initialised"))?;
+
+    provider
+        .tracer("rust-o11y/tests")
+        .start("protocol-span")
+        .end();
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    let request = collector.wait_for(path).await?;
+    tracer::shutdown(provider);
+    Ok(request)
+}
+
+async fn export_metric(
+    protocol: Protocol,
+    endpoint_suffix: &str,
+    path: &str,
+) -> Result<ReceivedRequest> {
+    let collector = collector_for(protocol).await?;
+    let config = MeterConfig::new("protocol-tests")
+        .with_endpoint(format!("{}{}", collector.endpoint(), endpoint_suffix))
+        .with_protocol(protocol)
+        .with_export_interval(Duration::from_secs(60));
+    let provider = meter::setup(&config, &resource())?
+        .ok_or_else(|| anyhow!("meter provider not initialised"))?;
+
+    provider
+        .meter("rust-o11y/tests")
+        .u64_counter("protocol_total")
+        .build()
+        .add(1, &[]);
+    provider.force_flush()?;
+
+    let request = collector.wait_for(path).await?;
+    meter::shutdown(provider);
+    Ok(request)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn signals_export_over_grpc() -> Result<()> {
+    let request = export_log(Protocol::Grpc, "", GRPC_LOGS_PATH).await?;
+    assert_eq!(request.headers["content-type"], "application/grpc");
+
+    // A signal path left on a gRPC endpoint is stripped rather than dialled.
+    let request = export_span(Protocol::Grpc, "/v1/traces", GRPC_TRACES_PATH).await?;
+    assert_eq!(request.headers["content-type"], "application/grpc");
+
+    let request = export_metric(Protocol::Grpc, "/", GRPC_METRICS_PATH).await?;
+    assert_eq!(request.headers["content-type"], "application/grpc");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn signals_export_over_http_protobuf() -> Result<()> {
+    let request = export_log(Protocol::HttpProtobuf, "/otlp", "/otlp/v1/logs").await?;
+    assert_eq!(request.headers["content-type"], "application/x-protobuf");
+
+    let request = export_span(Protocol::HttpProtobuf, "", "/v1/traces").await?;
+    assert_eq!(request.headers["content-type"], "application/x-protobuf");
+
+    let request = export_metric(Protocol::HttpProtobuf, "/v1/metrics", "/v1/metrics").await?;
+    assert_eq!(request.headers["content-type"], "application/x-protobuf");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn signals_export_over_http_json() -> Result<()> {
+    let request = export_log(Protocol::HttpJson, "", "/v1/logs").await?;
+    assert_eq!(request.headers["content-type"], "application/json");
+
+    let request = export_span(Protocol::HttpJson, "/", "/v1/traces").await?;
+    assert_eq!(request.headers["content-type"], "application/json");
+
+    let request = export_metric(Protocol::HttpJson, "", "/v1/metrics").await?;
+    assert_eq!(request.headers["content-type"], "application/json");
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "local")]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow, ensure};
+use tracing_subscriber::Registry;
+use tracing_subscriber::layer::SubscriberExt;
+
+use o11y::local::LocalFormat;
+use o11y::logger::LoggerConfig;
+use o11y::meter::MeterConfig;
+use o11y::profiler::ProfilerConfig;
+use o11y::tracer::TracerConfig;
+use o11y::{Config, LocalExporter, Telemetry, current_trace_context};
+
+#[test]
+fn layer_composes_into_an_application_subscriber() -> Result<()> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let path = std::env::temp_dir().join(format!("o11y-layer-{nanos}/telemetry.jsonl"));
+    let local = LocalExporter::file(&path).with_format(LocalFormat::OtlpJson);
+    let config = Config::new("layer-tests")
+        .install_subscriber(false)
+        .with_logger(
+            LoggerConfig::new("layer-tests")
+                .with_exporter(local.clone())
+                .with_filter("info"),
+        )
+        .with_tracer(TracerConfig::new("layer-tests").with_exporter(local))
+        .with_meter(MeterConfig::new("layer-tests").enabled(false))
+        .with_profiler(ProfilerConfig::new("layer-tests").enabled(false));
+
+    let mut telemetry = Telemetry::new(config)?;
+    ensure!(!tracing::dispatcher::has_been_set());
+    ensure!(telemetry.log_level.is_none());
+
+    let subscriber = Registry::default().with(telemetry.layer()?);
+    let trace_id = tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("checkout");
+        let _entered = span.enter();
+        tracing::info!("order placed");
+        tracing::debug!("cart contents");
+        current_trace_context().map(|context| context.trace_id)
+    })
+    .ok_or_else(|| anyhow!("no trace context inside the span"))?;
+    ensure!(telemetry.log_level.is_some());
+
+    let written = std::fs::read_to_string(&path)?;
+    ensure!(written.contains("\"checkout\""), "{written}");
+    ensure!(written.contains("\"order placed\""), "{written}");
+    ensure!(!written.contains("cart contents"), "{written}");
+    // Once on the span and once on the log record it was emitted in.
+    ensure!(written.matches(&trace_id).count() >= 2, "{written}");
+
+    telemetry.shutdown();
+    std::fs::remove_dir_all(path.parent().unwrap())?;
+    Ok(())
+}
@@ -1,9 +1,6 @@
 use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
-use opentelemetry::KeyValue;
-use opentelemetry::global;
-use opentelemetry::trace::{Span, Tracer};
 use reqwest::Client;
 use tokio::time::sleep;
 
@@ -11,14 +8,13 @@ use o11y::logger::LoggerConfig;
 use o11y::meter::{MeterConfig, RuntimeConfig};
 use o11y::profiler::ProfilerConfig;
 use o11y::tracer::TracerConfig;
-use o11y::{Config, Telemetry};
+use o11y::{Config, Telemetry, current_trace_context};
 
 #[path = "common/mod.rs"]
 mod common;
 
 use common::{
-    ObservationCase, Targets, emit_log, record_metric, wait_for_loki, wait_for_mimir,
-    wait_for_tempo,
+    ObservationCase, Targets, record_metric, wait_for_loki, wait_for_mimir, wait_for_tempo,
 };
 #[cfg(all(unix, feature = "profiler"))]
 use common::{generate_cpu_load, wait_for_pyroscope};
@@ -62,24 +58,18 @@ async fn telemetry_pushes_to_backends() -> Result<()> {
     #[cfg(not(all(unix, feature = "profiler")))]
     let expect_profiler = false;
 
-    let tracer = global::tracer("rust-o11y/tests");
-    let mut span = tracer.start("telemetry-integration-span");
-    span.set_attribute(KeyValue::new("test_case", case.test_case.clone()));
-    let span_context = span.span_context().clone();
-    let trace_id_str = span_context.trace_id().to_string();
-    let span_id_str = span_context.span_id().to_string();
-
-    let logger_provider = telemetry
-        .logger
-        .as_ref()
-        .ok_or_else(|| anyhow!("logger provider not initialised"))?;
-
-    emit_log(
-        logger_provider,
-        &span_context,
-        &case.log_message,
-        &case.test_case,
-    )?;
+    // The log is emitted through the installed subscriber, so the only trace
+    // ids it carries are the ones the bridge stamps from the enclosing span.
+    let span = tracing::info_span!("telemetry-integration-span", test_case = %case.test_case);
+    let trace_context = span
+        .in_scope(|| {
+            tracing::info!(test_case = %case.test_case, "{}", case.log_message);
+            current_trace_context()
+        })
+        .ok_or_else(|| anyhow!("no trace context inside the span"))?;
+    let trace_id_str = trace_context.trace_id;
+    let span_id_str = trace_context.span_id;
+
     record_metric(
         &case.metric_name,
         &case.test_case,
@@ -94,7 +84,7 @@ async fn telemetry_pushes_to_backends() -> Result<()> {
     }
 
     sleep(Duration::from_secs(2)).await;
-    span.end();
+    drop(span);
     telemetry.shutdown();
 
     let client = Client::builder()
@@ -0,0 +1,36 @@
+#![cfg(all(unix, feature = "signal"))]
+
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Result, ensure};
+
+use o11y::signal::Signal;
+use o11y::{LoggerProvider, Telemetry, TracerProvider};
+
+#[tokio::test]
+async fn sigterm_triggers_bounded_shutdown() -> Result<()> {
+    let telemetry = Telemetry::from_providers(
+        Some(LoggerProvider::builder().build()),
+        Some(TracerProvider::builder().build()),
+        None,
+        None,
+    );
+    let guard = telemetry.into_guard().with_timeout(Duration::from_secs(2));
+
+    let waiter = tokio::spawn(guard.shutdown_on_signal());
+    // Give the task a chance to install its handlers before signalling.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let status = Command::new("kill")
+        .args(["-TERM", &std::process::id().to_string()])
+        .status()?;
+    ensure!(status.success(), "kill exited with {status}");
+
+    let (signal, report) = tokio::time::timeout(Duration::from_secs(5), waiter).await???;
+    ensure!(signal == Signal::Terminate, "unexpected signal {signal}");
+    ensure!(signal.exit_code() == 143);
+    ensure!(report.is_success(), "shutdown failed: {report}");
+    ensure!(report.components.len() == 2);
+    Ok(())
+}
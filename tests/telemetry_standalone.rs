@@ -1,15 +1,17 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result, anyhow};
 use opentelemetry::KeyValue;
 use opentelemetry::global;
-use opentelemetry::trace::{Span, SpanContext, TraceFlags, TraceState, Tracer};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+use opentelemetry::trace::{Span, SpanContext, TraceContextExt, TraceFlags, TraceState, Tracer};
+use opentelemetry_sdk::logs::LogRecord;
 use opentelemetry_sdk::trace::{IdGenerator, RandomIdGenerator};
 use reqwest::Client;
 use tokio::time::sleep;
 
 use o11y::ResourceConfig;
-use o11y::logger::{self, LoggerConfig};
+use o11y::logger::{self, LoggerConfig, LoggerProvider};
 use o11y::meter::{self, MeterConfig, RuntimeConfig};
 use o11y::tracer::{self, TracerConfig};
 
@@ -20,8 +22,7 @@ use o11y::profiler::{self, ProfilerConfig};
 mod common;
 
 use common::{
-    ObservationCase, Targets, emit_log, record_metric, wait_for_loki, wait_for_mimir,
-    wait_for_tempo,
+    ObservationCase, Targets, record_metric, wait_for_loki, wait_for_mimir, wait_for_tempo,
 };
 #[cfg(all(unix, feature = "profiler"))]
 use common::{generate_cpu_load, wait_for_pyroscope};
@@ -202,3 +203,34 @@ fn random_span_context() -> (SpanContext, String, String) {
 
     (context, trace_id.to_string(), span_id.to_string())
 }
+
+/// Emits a record while `span_context` is current, leaving the SDK to stamp
+/// the record's trace context as it would for instrumented code.
+fn emit_log(
+    provider: &LoggerProvider,
+    span_context: &SpanContext,
+    message: &str,
+    test_case: &str,
+) -> Result<()> {
+    let logger = provider.logger("rust-o11y/tests");
+
+    let mut record = LogRecord::default();
+    record.set_body(AnyValue::from(message.to_string()));
+    record.set_timestamp(SystemTime::now());
+    record.set_observed_timestamp(SystemTime::now());
+    record.set_severity_number(Severity::Info);
+    record.set_severity_text("INFO");
+    record.add_attribute("message", message.to_string());
+    record.add_attribute("test_case", test_case.to_string());
+
+    let _attached = opentelemetry::Context::new()
+        .with_remote_span_context(span_context.clone())
+        .attach();
+    logger.emit(record);
+
+    for result in provider.force_flush() {
+        result?;
+    }
+
+    Ok(())
+}